@@ -6,4 +6,5 @@ use libfuzzer_sys::fuzz_target;
 
 fuzz_target!(|data: &[u8]| {
     let _ = pe_parser::pe::parse_portable_executable(data);
+    let _ = pe_parser::file::parse_file(data);
 });
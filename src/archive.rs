@@ -0,0 +1,683 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::{fmt, str};
+use alloc::collections::BTreeMap;
+use crate::{import::{ImportObject, is_import_object, parse_import_object}, object::{CoffObject, parse_coff_object}, pe::{read_u16, read_u32}, prelude::*, Error};
+
+/// Signature at the start of an archive ("!<arch>\n")
+pub const ARCHIVE_SIGNATURE: [u8; 8] = *b"!<arch>\n";
+
+/// Name of the first and second linker members
+pub const LINKER_MEMBER_NAME: &str = "/";
+/// Name of the long names member
+pub const LONG_NAMES_MEMBER_NAME: &str = "//";
+/// Name of the ARM64EC symbol table member
+pub const EC_SYMBOLS_MEMBER_NAME: &str = "/<ECSYMBOLS>/";
+
+/// Representation of an archive (.lib) file
+pub struct Archive {
+    /// Object and import members of the archive, in file order.
+    /// The linker, long names and EC symbol members are not included.
+    pub members: Vec<ArchiveMember>,
+    /// Symbols listed in the first linker member, in file order
+    pub first_linker_member: Option<Vec<ArchiveSymbol>>,
+    /// Symbols listed in the second linker member, sorted by name
+    pub second_linker_member: Option<Vec<ArchiveSymbol>>,
+    /// ARM64EC symbols listed in the `/<ECSYMBOLS>/` member, sorted by name
+    pub ec_symbol_table: Option<Vec<ArchiveSymbol>>,
+}
+
+/// A public symbol from an archive symbol index
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveSymbol {
+    /// The symbol name.
+    pub name: String,
+    /// File offset of the header of the member that defines the symbol.
+    pub member_offset: u32
+}
+
+/// Returns true if the binary starts with the archive signature
+pub fn is_archive(binary: &[u8]) -> bool {
+    binary.get(0..ARCHIVE_SIGNATURE.len()) == Some(&ARCHIVE_SIGNATURE[..])
+}
+
+/// Parse an archive from a given byte array, enumerating its members
+/// and decoding the linker member symbol indices
+pub fn parse_archive(binary: &[u8]) -> Result<Archive, Error> {
+    if !is_archive(binary) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let mut members: Vec<ArchiveMember> = Vec::new();
+    let mut first_linker_member: Option<&[u8]> = None;
+    let mut second_linker_member: Option<&[u8]> = None;
+    let mut ec_symbols: Option<&[u8]> = None;
+    let mut long_names: Option<&[u8]> = None;
+    let mut offset = ARCHIVE_SIGNATURE.len();
+    let header_size = size_of::<ArchiveMemberHeader>();
+
+    while offset < binary.len() {
+        let slice = match binary.get(offset..offset+header_size) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        let header = match try_pod_read_unaligned::<ArchiveMemberHeader>(slice) {
+            Ok(header) => header,
+            Err(_) => {
+                return Err(Error::BadArchiveMember);
+            }
+        };
+
+        if header.end_of_header != *b"`\n" {
+            return Err(Error::BadArchiveMember);
+        }
+
+        let size = match header.get_size() {
+            Some(size) => size,
+            None => {
+                return Err(Error::BadArchiveMember);
+            }
+        };
+
+        let data_offset = offset + header_size;
+        let data_end = match data_offset.checked_add(size as usize) {
+            Some(end) if end <= binary.len() => end,
+            _ => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+        let data = &binary[data_offset..data_end];
+
+        let raw_name = header.get_name().unwrap_or_default();
+        match raw_name.as_str() {
+            LINKER_MEMBER_NAME if first_linker_member.is_none() => first_linker_member = Some(data),
+            LINKER_MEMBER_NAME if second_linker_member.is_none() => second_linker_member = Some(data),
+            LONG_NAMES_MEMBER_NAME => long_names = Some(data),
+            EC_SYMBOLS_MEMBER_NAME => ec_symbols = Some(data),
+            _ => {
+                let name = match resolve_member_name(&raw_name, long_names) {
+                    Some(name) => name,
+                    None => {
+                        return Err(Error::BadArchiveMember);
+                    }
+                };
+
+                members.push(ArchiveMember { header, name, header_offset: offset, data_offset });
+            }
+        }
+
+        // Each member starts on an even byte boundary
+        offset = data_end + (data_end & 1);
+    }
+
+    let first_linker_member = match first_linker_member {
+        Some(data) => Some(parse_first_linker_member(data)?),
+        None => None,
+    };
+
+    let (member_offsets, second_linker_member) = match second_linker_member {
+        Some(data) => {
+            let (member_offsets, symbols) = parse_second_linker_member(data)?;
+            (member_offsets, Some(symbols))
+        }
+        None => (Vec::new(), None),
+    };
+
+    let ec_symbol_table = match ec_symbols {
+        Some(data) => Some(parse_ec_symbol_table(data, &member_offsets)?),
+        None => None,
+    };
+
+    Ok(Archive {
+        members,
+        first_linker_member,
+        second_linker_member,
+        ec_symbol_table,
+    })
+}
+
+/// Resolve a member name, looking up `/n` names in the long names member.
+/// Names are terminated by a slash, or in the long names member by a null (MS)
+/// or by "/\n" (GNU).
+fn resolve_member_name(raw_name: &str, long_names: Option<&[u8]>) -> Option<String> {
+    if let Some(long_name_offset) = raw_name.strip_prefix('/')
+        && let Ok(start) = long_name_offset.parse::<usize>() {
+        let data = long_names?.get(start..)?;
+        let end = data.iter().position(|&c| c == 0 || c == b'\n').unwrap_or(data.len());
+        let name = str::from_utf8(&data[..end]).ok()?;
+        return Some(String::from(name.strip_suffix('/').unwrap_or(name)));
+    }
+
+    Some(String::from(raw_name.strip_suffix('/').unwrap_or(raw_name)))
+}
+
+/// Parse the first linker member.
+/// Unlike the rest of the file, its numbers are big-endian.
+fn parse_first_linker_member(data: &[u8]) -> Result<Vec<ArchiveSymbol>, Error> {
+    let number_of_symbols = read_u32_be(data, 0)? as usize;
+    let offsets_size = match number_of_symbols.checked_mul(4) {
+        Some(size) => size,
+        None => {
+            return Err(Error::BadArchiveMember);
+        }
+    };
+
+    let mut names = read_names(data, 4 + offsets_size, number_of_symbols)?;
+    let mut symbols: Vec<ArchiveSymbol> = Vec::with_capacity(names.len());
+    for (i, name) in names.drain(..).enumerate() {
+        symbols.push(ArchiveSymbol {
+            name,
+            member_offset: read_u32_be(data, 4 + i * 4)?,
+        });
+    }
+
+    Ok(symbols)
+}
+
+/// Parse the second linker member, returning its member offset array
+/// along with its symbols
+fn parse_second_linker_member(data: &[u8]) -> Result<(Vec<u32>, Vec<ArchiveSymbol>), Error> {
+    let number_of_members = read_u32(data, 0)? as usize;
+    let mut member_offsets: Vec<u32> = Vec::new();
+    for i in 0..number_of_members {
+        member_offsets.push(read_u32(data, 4 + i * 4)?);
+    }
+
+    let offset = 4 + number_of_members * 4;
+    let symbols = parse_indexed_symbols(data, offset, &member_offsets)?;
+
+    Ok((member_offsets, symbols))
+}
+
+/// Parse the ARM64EC symbol table, whose indices refer to
+/// the member offset array of the second linker member
+fn parse_ec_symbol_table(data: &[u8], member_offsets: &[u32]) -> Result<Vec<ArchiveSymbol>, Error> {
+    parse_indexed_symbols(data, 0, member_offsets)
+}
+
+/// Parse a symbol count, an array of 1-based 16-bit member indices
+/// and the symbol names, as used by the second linker member and EC symbol table
+fn parse_indexed_symbols(data: &[u8], offset: usize, member_offsets: &[u32]) -> Result<Vec<ArchiveSymbol>, Error> {
+    let number_of_symbols = read_u32(data, offset)? as usize;
+    let indices_offset = offset + 4;
+    let indices_size = match number_of_symbols.checked_mul(2) {
+        Some(size) => size,
+        None => {
+            return Err(Error::BadArchiveMember);
+        }
+    };
+
+    let mut names = read_names(data, indices_offset + indices_size, number_of_symbols)?;
+    let mut symbols: Vec<ArchiveSymbol> = Vec::with_capacity(names.len());
+    for (i, name) in names.drain(..).enumerate() {
+        let index = read_u16(data, indices_offset + i * 2)? as usize;
+        let member_offset = match index.checked_sub(1).and_then(|index| member_offsets.get(index)) {
+            Some(member_offset) => *member_offset,
+            None => {
+                return Err(Error::BadArchiveMember);
+            }
+        };
+
+        symbols.push(ArchiveSymbol { name, member_offset });
+    }
+
+    Ok(symbols)
+}
+
+/// Read `count` null-terminated strings starting at `offset`
+fn read_names(data: &[u8], offset: usize, count: usize) -> Result<Vec<String>, Error> {
+    let table = match data.get(offset..) {
+        Some(table) => table,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    let mut strings = table.split(|&c| c == 0);
+    for _ in 0..count {
+        let name = match strings.next() {
+            Some(name) => name,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+        names.push(String::from_utf8(name.to_vec()).map_err(Error::BadString)?);
+    }
+
+    Ok(names)
+}
+
+fn read_u32_be(binary: &[u8], offset: usize) -> Result<u32, Error> {
+    let slice = match binary.get(offset..offset+4) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    Ok(u32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+impl Archive {
+    /// Get the member whose header starts at the given file offset
+    pub fn get_member_at(&self, offset: u32) -> Option<&ArchiveMember> {
+        let index = self.members.binary_search_by_key(&(offset as usize), |member| member.header_offset).ok()?;
+        self.members.get(index)
+    }
+
+    /// Find the member that defines a symbol, using the second linker member
+    /// if present and the first linker member otherwise
+    pub fn find_symbol(&self, name: &str) -> Option<&ArchiveMember> {
+        let symbol = match (&self.second_linker_member, &self.first_linker_member) {
+            (Some(symbols), _) => {
+                let index = symbols.binary_search_by(|symbol| symbol.name.as_str().cmp(name)).ok()?;
+                symbols.get(index)?
+            }
+            (None, Some(symbols)) => symbols.iter().find(|symbol| symbol.name == name)?,
+            (None, None) => {
+                return None;
+            }
+        };
+
+        self.get_member_at(symbol.member_offset)
+    }
+
+    /// Find the member that defines an ARM64EC symbol
+    pub fn find_ec_symbol(&self, name: &str) -> Option<&ArchiveMember> {
+        let symbols = self.ec_symbol_table.as_ref()?;
+        let index = symbols.binary_search_by(|symbol| symbol.name.as_str().cmp(name)).ok()?;
+        self.get_member_at(symbols[index].member_offset)
+    }
+
+    /// Map each symbol to the index in `members` of the member that defines it,
+    /// using the second linker member if present and the first linker member otherwise
+    pub fn get_symbol_map(&self) -> BTreeMap<String, usize> {
+        match (&self.second_linker_member, &self.first_linker_member) {
+            (Some(symbols), _) | (None, Some(symbols)) => self.build_symbol_map(symbols),
+            (None, None) => BTreeMap::new(),
+        }
+    }
+
+    /// Map each ARM64EC symbol to the index in `members` of the member that defines it
+    pub fn get_ec_symbol_map(&self) -> BTreeMap<String, usize> {
+        match &self.ec_symbol_table {
+            Some(symbols) => self.build_symbol_map(symbols),
+            None => BTreeMap::new(),
+        }
+    }
+
+    fn build_symbol_map(&self, symbols: &[ArchiveSymbol]) -> BTreeMap<String, usize> {
+        let mut map: BTreeMap<String, usize> = BTreeMap::new();
+        for symbol in symbols {
+            if let Ok(index) = self.members.binary_search_by_key(&(symbol.member_offset as usize), |member| member.header_offset) {
+                map.entry(symbol.name.clone()).or_insert(index);
+            }
+        }
+
+        map
+    }
+}
+
+/// Archive Member Header
+/// Every field is ASCII text, padded on the right with spaces.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct ArchiveMemberHeader {
+    /// The name of the archive member, terminated by a slash.
+    /// `/` and `//` are the linker and long name members;
+    /// `/n` refers to offset n in the long names member.
+    pub name: [u8; 16],
+    /// The date and time that the archive member was created, as a decimal Unix timestamp.
+    pub date: [u8; 12],
+    /// A decimal user ID. Blank on Windows.
+    pub user_id: [u8; 6],
+    /// A decimal group ID. Blank on Windows.
+    pub group_id: [u8; 6],
+    /// An octal number that represents the member's file mode.
+    pub mode: [u8; 8],
+    /// A decimal number that represents the total size of the archive member, not including the header.
+    pub size: [u8; 10],
+    /// The two bytes "`\n".
+    pub end_of_header: [u8; 2]
+}
+
+impl ArchiveMemberHeader {
+    /// Get the raw member name with trailing spaces removed
+    pub fn get_name(&self) -> Option<String> {
+        parse_field(&self.name).map(String::from)
+    }
+
+    /// Returns the member size in bytes
+    pub fn get_size(&self) -> Option<u32> {
+        parse_field(&self.size)?.parse().ok()
+    }
+
+    /// Returns the creation date as a Unix timestamp
+    pub fn get_date(&self) -> Option<u64> {
+        parse_field(&self.date)?.parse().ok()
+    }
+
+    /// Returns the file mode
+    pub fn get_mode(&self) -> Option<u32> {
+        u32::from_str_radix(parse_field(&self.mode)?, 8).ok()
+    }
+}
+
+fn parse_field(field: &[u8]) -> Option<&str> {
+    str::from_utf8(field).ok().map(|field| field.trim_end_matches(' '))
+}
+
+/// A member of an archive
+#[derive(Clone)]
+pub struct ArchiveMember {
+    /// The member header.
+    pub header: ArchiveMemberHeader,
+    /// The member name, with long names resolved and the terminating slash removed.
+    pub name: String,
+    /// File offset of the member header.
+    pub header_offset: usize,
+    /// File offset of the member contents.
+    pub data_offset: usize
+}
+
+/// The parsed contents of an archive member
+pub enum ArchiveMemberContents {
+    /// COFF object file, or big object file
+    CoffObject(CoffObject),
+    /// Short import object
+    ImportObject(ImportObject),
+}
+
+impl ArchiveMember {
+    /// Get the contents of the member from the archive it was parsed from
+    pub fn get_data<'a>(&self, binary: &'a [u8]) -> Option<&'a [u8]> {
+        let size = self.header.get_size()? as usize;
+        binary.get(self.data_offset..self.data_offset.checked_add(size)?)
+    }
+
+    /// Parse the member as a short import object or COFF object
+    pub fn parse(&self, binary: &[u8]) -> Result<ArchiveMemberContents, Error> {
+        let data = match self.get_data(binary) {
+            Some(data) => data,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        if is_import_object(data) {
+            Ok(ArchiveMemberContents::ImportObject(parse_import_object(data)?))
+        } else {
+            Ok(ArchiveMemberContents::CoffObject(parse_coff_object(data)?))
+        }
+    }
+}
+
+impl fmt::Display for ArchiveMember {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Archive Member")?;
+        writeln!(f, "--------------")?;
+        writeln!(f, "Name:                    {:?}", self.name)?;
+        writeln!(f, "Size:                    {}", self.header.get_size().unwrap_or_default())?;
+        writeln!(f, "Data Offset:             {:#010x}", self.data_offset)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ArchiveMemberContents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveMemberContents::CoffObject(object) => write!(f, "{}", object),
+            ArchiveMemberContents::ImportObject(import) => write!(f, "{}", import),
+        }
+    }
+}
+
+impl fmt::Display for ArchiveSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010x}  {}", self.member_offset, self.name)
+    }
+}
+
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbols = match (&self.second_linker_member, &self.first_linker_member) {
+            (Some(symbols), _) | (None, Some(symbols)) => Some(symbols),
+            (None, None) => None,
+        };
+
+        if let Some(symbols) = symbols {
+            writeln!(f, "Archive Symbols")?;
+            writeln!(f, "---------------")?;
+            for symbol in symbols.iter() {
+                writeln!(f, "{}", symbol)?;
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(symbols) = &self.ec_symbol_table {
+            writeln!(f, "Archive EC Symbols")?;
+            writeln!(f, "------------------")?;
+            for symbol in symbols.iter() {
+                writeln!(f, "{}", symbol)?;
+            }
+            writeln!(f)?;
+        }
+
+        for member in self.members.iter() {
+            writeln!(f, "{}", member)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A member to be written into a new archive
+pub struct NewArchiveMember {
+    /// The member name.
+    pub name: String,
+    /// The member contents.
+    pub data: Vec<u8>,
+    /// Public symbols the member defines, listed in the linker members.
+    pub symbols: Vec<String>,
+}
+
+/// Write an archive with first and second linker members,
+/// a long names member if needed, and the given members in order
+pub fn write_archive(members: &[NewArchiveMember]) -> Vec<u8> {
+    let header_size = size_of::<ArchiveMemberHeader>();
+    let padded = |size: usize| size + (size & 1);
+
+    // Names longer than 15 characters go in the long names member
+    let mut long_names: Vec<u8> = Vec::new();
+    let mut member_names: Vec<String> = Vec::new();
+    for member in members {
+        if member.name.len() < 16 {
+            member_names.push(format!("{}/", member.name));
+        } else {
+            member_names.push(format!("/{}", long_names.len()));
+            long_names.extend_from_slice(member.name.as_bytes());
+            long_names.push(0);
+        }
+    }
+
+    let symbol_count: usize = members.iter().map(|member| member.symbols.len()).sum();
+    let string_size: usize = members.iter().flat_map(|member| member.symbols.iter()).map(|symbol| symbol.len() + 1).sum();
+    let first_linker_size = 4 + symbol_count * 4 + string_size;
+    let second_linker_size = 4 + members.len() * 4 + 4 + symbol_count * 2 + string_size;
+
+    let mut offset = ARCHIVE_SIGNATURE.len()
+        + header_size + padded(first_linker_size)
+        + header_size + padded(second_linker_size);
+    if !long_names.is_empty() {
+        offset += header_size + padded(long_names.len());
+    }
+
+    let mut member_offsets: Vec<u32> = Vec::new();
+    for member in members {
+        member_offsets.push(offset as u32);
+        offset += header_size + padded(member.data.len());
+    }
+
+    let mut binary = ARCHIVE_SIGNATURE.to_vec();
+
+    // First linker member: big-endian offsets, symbols in member order
+    let mut first_linker: Vec<u8> = Vec::with_capacity(first_linker_size);
+    first_linker.extend_from_slice(&(symbol_count as u32).to_be_bytes());
+    for (member, member_offset) in members.iter().zip(member_offsets.iter()) {
+        for _ in member.symbols.iter() {
+            first_linker.extend_from_slice(&member_offset.to_be_bytes());
+        }
+    }
+    for symbol in members.iter().flat_map(|member| member.symbols.iter()) {
+        first_linker.extend_from_slice(symbol.as_bytes());
+        first_linker.push(0);
+    }
+    write_member(&mut binary, LINKER_MEMBER_NAME, &first_linker);
+
+    // Second linker member: little-endian, symbols sorted by name
+    let mut sorted_symbols: Vec<(&String, u16)> = Vec::with_capacity(symbol_count);
+    for (i, member) in members.iter().enumerate() {
+        for symbol in member.symbols.iter() {
+            sorted_symbols.push((symbol, (i + 1) as u16));
+        }
+    }
+    sorted_symbols.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+    let mut second_linker: Vec<u8> = Vec::with_capacity(second_linker_size);
+    second_linker.extend_from_slice(&(members.len() as u32).to_le_bytes());
+    for member_offset in member_offsets.iter() {
+        second_linker.extend_from_slice(&member_offset.to_le_bytes());
+    }
+    second_linker.extend_from_slice(&(symbol_count as u32).to_le_bytes());
+    for (_, index) in sorted_symbols.iter() {
+        second_linker.extend_from_slice(&index.to_le_bytes());
+    }
+    for (symbol, _) in sorted_symbols.iter() {
+        second_linker.extend_from_slice(symbol.as_bytes());
+        second_linker.push(0);
+    }
+    write_member(&mut binary, LINKER_MEMBER_NAME, &second_linker);
+
+    if !long_names.is_empty() {
+        write_member(&mut binary, LONG_NAMES_MEMBER_NAME, &long_names);
+    }
+
+    for (member, name) in members.iter().zip(member_names.iter()) {
+        write_member(&mut binary, name, &member.data);
+    }
+
+    binary
+}
+
+fn write_member(binary: &mut Vec<u8>, name: &str, data: &[u8]) {
+    let mut header = ArchiveMemberHeader {
+        name: [b' '; 16],
+        date: [b' '; 12],
+        user_id: [b' '; 6],
+        group_id: [b' '; 6],
+        mode: [b' '; 8],
+        size: [b' '; 10],
+        end_of_header: *b"`\n",
+    };
+    write_field(&mut header.name, name);
+    write_field(&mut header.date, "0");
+    write_field(&mut header.mode, "0");
+    write_field(&mut header.size, &format!("{}", data.len()));
+
+    binary.extend_from_slice(bytemuck::bytes_of(&header));
+    binary.extend_from_slice(data);
+    if data.len() & 1 != 0 {
+        binary.push(b'\n');
+    }
+}
+
+fn write_field(field: &mut [u8], value: &str) {
+    let length = value.len().min(field.len());
+    field[..length].copy_from_slice(&value.as_bytes()[..length]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, data: &[u8], symbols: &[&str]) -> NewArchiveMember {
+        NewArchiveMember {
+            name: String::from(name),
+            data: data.to_vec(),
+            symbols: symbols.iter().map(|&symbol| String::from(symbol)).collect(),
+        }
+    }
+
+    #[test]
+    fn write_then_parse() {
+        let binary = write_archive(&[
+            member("short.obj", b"odd", &["zeta", "alpha"]),
+            member("a_member_with_a_long_name.obj", b"even", &["mid"]),
+        ]);
+        let archive = parse_archive(&binary).unwrap();
+
+        let names: Vec<&str> = archive.members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["short.obj", "a_member_with_a_long_name.obj"]);
+        assert_eq!(archive.members[0].get_data(&binary), Some(&b"odd"[..]));
+        assert_eq!(archive.members[1].get_data(&binary), Some(&b"even"[..]));
+
+        let offsets: Vec<u32> = archive.members.iter().map(|member| member.header_offset as u32).collect();
+        assert_eq!(archive.first_linker_member.as_deref().unwrap(), [
+            ArchiveSymbol { name: String::from("zeta"), member_offset: offsets[0] },
+            ArchiveSymbol { name: String::from("alpha"), member_offset: offsets[0] },
+            ArchiveSymbol { name: String::from("mid"), member_offset: offsets[1] },
+        ]);
+        let sorted: Vec<&str> = archive.second_linker_member.as_ref().unwrap().iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(sorted, ["alpha", "mid", "zeta"]);
+
+        assert_eq!(archive.find_symbol("mid").map(|member| member.name.as_str()), Some("a_member_with_a_long_name.obj"));
+        assert_eq!(archive.find_symbol("zeta").map(|member| member.name.as_str()), Some("short.obj"));
+        assert!(archive.find_symbol("missing").is_none());
+    }
+
+    #[test]
+    fn gnu_long_names() {
+        let mut binary = ARCHIVE_SIGNATURE.to_vec();
+        write_member(&mut binary, LONG_NAMES_MEMBER_NAME, b"first_long_member_name.o/\nsecond_long_member_name.o/\n");
+        write_member(&mut binary, "/26", b"data");
+        write_member(&mut binary, "/0", b"data");
+        let archive = parse_archive(&binary).unwrap();
+
+        let names: Vec<&str> = archive.members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["second_long_member_name.o", "first_long_member_name.o"]);
+        assert!(archive.first_linker_member.is_none());
+    }
+
+    #[test]
+    fn ec_symbols_use_the_second_linker_member_offsets() {
+        let mut binary = write_archive(&[member("a.obj", b"a", &["a"]), member("b.obj", b"b", &["b"])]);
+
+        let mut ec_symbols = 2u32.to_le_bytes().to_vec();
+        ec_symbols.extend_from_slice(&2u16.to_le_bytes());
+        ec_symbols.extend_from_slice(&1u16.to_le_bytes());
+        ec_symbols.extend_from_slice(b"#b\0#a\0");
+        write_member(&mut binary, EC_SYMBOLS_MEMBER_NAME, &ec_symbols);
+        let archive = parse_archive(&binary).unwrap();
+
+        assert_eq!(archive.members.len(), 2);
+        assert_eq!(archive.find_ec_symbol("#a").map(|member| member.name.as_str()), Some("a.obj"));
+        assert_eq!(archive.get_ec_symbol_map().get("#b"), Some(&1));
+    }
+
+    #[test]
+    fn bad_member_index() {
+        let mut binary = ARCHIVE_SIGNATURE.to_vec();
+        write_member(&mut binary, LINKER_MEMBER_NAME, &0u32.to_be_bytes());
+        let mut second_linker = 0u32.to_le_bytes().to_vec();
+        second_linker.extend_from_slice(&1u32.to_le_bytes());
+        second_linker.extend_from_slice(&1u16.to_le_bytes());
+        second_linker.extend_from_slice(b"a\0");
+        write_member(&mut binary, LINKER_MEMBER_NAME, &second_linker);
+
+        assert!(matches!(parse_archive(&binary), Err(Error::BadArchiveMember)));
+    }
+}
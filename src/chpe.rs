@@ -0,0 +1,387 @@
+use alloc::boxed::Box;
+use bytemuck::{Pod, Zeroable};
+use core::fmt;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use crate::{debug::parse_records, load_config::{LoadConfigDirectory64, read_versioned}, pe::{PortableExecutable, read_u32}, prelude::*, Error};
+
+/// ARM64EC metadata, pointed to by the load configuration of
+/// ARM64EC and ARM64X images. All addresses are RVAs.
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct Arm64ECMetadata {
+    /// The metadata version, 1 or 2.
+    pub version: u32,
+    /// RVA of the code map, a table of `Arm64ECCodeRange`.
+    pub code_map: u32,
+    /// Number of entries in the code map.
+    pub code_map_count: u32,
+    /// RVA of the table mapping code ranges to entry points.
+    pub code_ranges_to_entry_points: u32,
+    /// RVA of the redirection metadata, a table of `Arm64ECRedirection`.
+    pub redirection_metadata: u32,
+    /// RVA of `__os_arm64x_dispatch_call_no_redirect`.
+    pub os_arm64x_dispatch_call_no_redirect: u32,
+    /// RVA of `__os_arm64x_dispatch_ret`.
+    pub os_arm64x_dispatch_ret: u32,
+    /// RVA of `__os_arm64x_dispatch_call`.
+    pub os_arm64x_dispatch_call: u32,
+    /// RVA of `__os_arm64x_dispatch_icall`.
+    pub os_arm64x_dispatch_icall: u32,
+    /// RVA of `__os_arm64x_dispatch_icall_cfg`.
+    pub os_arm64x_dispatch_icall_cfg: u32,
+    /// RVA of the alternate entry point.
+    pub alternate_entry_point: u32,
+    /// RVA of the auxiliary IAT, parallel to the import address table.
+    pub auxiliary_iat: u32,
+    /// Number of entries in the code range to entry point table.
+    pub code_ranges_to_entry_points_count: u32,
+    /// Number of entries in the redirection metadata.
+    pub redirection_metadata_count: u32,
+    /// RVA of the `GetX64Information` function pointer.
+    pub get_x64_information_function_pointer: u32,
+    /// RVA of the `SetX64Information` function pointer.
+    pub set_x64_information_function_pointer: u32,
+    /// RVA of the extra return flow exception table.
+    pub extra_rfe_table: u32,
+    /// Size of the extra return flow exception table.
+    pub extra_rfe_table_size: u32,
+    /// RVA of `__os_arm64x_dispatch_fptr`.
+    pub os_arm64x_dispatch_fptr: u32,
+    /// RVA of the copy of the auxiliary IAT.
+    pub auxiliary_iat_copy: u32,
+    /// RVA of the auxiliary delay-load IAT (version 2).
+    pub auxiliary_delayload_iat: u32,
+    /// RVA of the copy of the auxiliary delay-load IAT (version 2).
+    pub auxiliary_delayload_iat_copy: u32,
+    /// Hybrid image flags (version 2).
+    pub hybrid_image_info_bitfield: u32,
+}
+
+/// CHPE metadata of an x86 image containing ARM64 code,
+/// pointed to by the load configuration. Function pointers are VAs.
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct ChpeX86Metadata {
+    /// The metadata version, 1 to 4.
+    pub version: u32,
+    /// RVA of the code ranges, a table of `ChpeX86CodeRange`.
+    pub code_address_range_offset: u32,
+    /// Number of code ranges.
+    pub code_address_range_count: u32,
+    /// Exception handler function pointer.
+    pub wow_a64_exception_handler_function_pointer: u32,
+    /// Dispatch call function pointer.
+    pub wow_a64_dispatch_call_function_pointer: u32,
+    /// Dispatch indirect call function pointer.
+    pub wow_a64_dispatch_indirect_call_function_pointer: u32,
+    /// Dispatch indirect call function pointer, with CFG check.
+    pub wow_a64_dispatch_indirect_call_cfg_function_pointer: u32,
+    /// Dispatch return function pointer.
+    pub wow_a64_dispatch_ret_function_pointer: u32,
+    /// Dispatch leaf return function pointer.
+    pub wow_a64_dispatch_ret_leaf_function_pointer: u32,
+    /// Dispatch jump function pointer.
+    pub wow_a64_dispatch_jump_function_pointer: u32,
+    /// Compiler IAT pointer (version 2).
+    pub compiler_iat_pointer: u32,
+    /// `rdtsc` emulation function pointer (version 3).
+    pub wow_a64_rdtsc_function_pointer: u32,
+}
+
+/// The kind of code in an ARM64EC code range
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum Arm64ECRangeType {
+    /// Native ARM64 code.
+    Arm64 = 0,
+    /// ARM64EC code.
+    Arm64EC = 1,
+    /// x64 code.
+    Amd64 = 2,
+}
+
+/// An entry of the ARM64EC code map
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arm64ECCodeRange {
+    /// The RVA of the start of the range.
+    pub rva: u32,
+    /// The length of the range in bytes.
+    pub length: u32,
+    /// The kind of code; see `Arm64ECRangeType`.
+    pub range_type: u8
+}
+
+/// Maps a range of ARM64EC code to its x64-callable entry point
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct Arm64ECEntryPointRange {
+    /// The RVA of the start of the range.
+    pub start_rva: u32,
+    /// The RVA of the end of the range.
+    pub end_rva: u32,
+    /// The RVA of the entry point.
+    pub entry_point: u32,
+}
+
+/// Redirects calls to an ARM64EC function through an entry thunk
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct Arm64ECRedirection {
+    /// The RVA of the function.
+    pub source: u32,
+    /// The RVA of the redirection target.
+    pub destination: u32,
+}
+
+/// An entry of the CHPE x86 code ranges
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChpeX86CodeRange {
+    /// The RVA of the start of the range.
+    pub rva: u32,
+    /// The length of the range in bytes.
+    pub length: u32,
+    /// True if the range contains native ARM64 code, false for x86.
+    pub native: bool
+}
+
+/// The ARM64EC metadata of an image and the tables it points to
+#[derive(Clone, Debug)]
+pub struct Arm64ECInfo {
+    /// The metadata header.
+    pub metadata: Arm64ECMetadata,
+    /// The code map.
+    pub code_ranges: Vec<Arm64ECCodeRange>,
+    /// Code ranges and their entry points.
+    pub entry_point_ranges: Vec<Arm64ECEntryPointRange>,
+    /// Redirection metadata.
+    pub redirections: Vec<Arm64ECRedirection>,
+    /// The auxiliary IAT, one entry per import address table entry.
+    pub auxiliary_iat: Vec<u64>
+}
+
+/// The CHPE metadata of an x86 image and its code ranges
+#[derive(Clone, Debug)]
+pub struct ChpeX86Info {
+    /// The metadata header.
+    pub metadata: ChpeX86Metadata,
+    /// The code ranges.
+    pub code_ranges: Vec<ChpeX86CodeRange>
+}
+
+/// Hybrid (CHPE) metadata of an image
+#[derive(Clone, Debug)]
+pub enum ChpeInfo {
+    /// ARM64EC metadata, from a PE32+ image.
+    Arm64EC(Box<Arm64ECInfo>),
+    /// CHPE metadata, from a PE32 image.
+    X86(ChpeX86Info),
+}
+
+/// Parse the hybrid metadata the load configuration points to.
+/// PE32+ images carry ARM64EC metadata and PE32 images carry CHPE x86 metadata.
+/// Returns `None` if the image has no hybrid metadata.
+pub fn parse_chpe_metadata(binary: &[u8], pe: &PortableExecutable, config: &LoadConfigDirectory64) -> Result<Option<ChpeInfo>, Error> {
+    if config.chpe_metadata_pointer == 0 {
+        return Ok(None);
+    }
+
+    let data = match pe.va_to_rva(config.chpe_metadata_pointer).and_then(|rva| pe.rva_to_offset(rva)).and_then(|offset| binary.get(offset..)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let version = read_u32(data, 0)?;
+    if pe.is_64_bit() {
+        // Version 1 ends at the auxiliary IAT copy
+        let size = if version < 2 { 80 } else { size_of::<Arm64ECMetadata>() };
+        let metadata = read_versioned::<Arm64ECMetadata>(data, size)?;
+
+        let mut code_ranges: Vec<Arm64ECCodeRange> = Vec::new();
+        for entry in read_table(binary, pe, metadata.code_map, metadata.code_map_count, 8)?.chunks_exact(8) {
+            let start = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            code_ranges.push(Arm64ECCodeRange {
+                rva: start & !3,
+                length: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                range_type: (start & 3) as u8,
+            });
+        }
+
+        let entry_point_ranges = parse_records(read_table(binary, pe, metadata.code_ranges_to_entry_points, metadata.code_ranges_to_entry_points_count, size_of::<Arm64ECEntryPointRange>())?)?;
+        let redirections = parse_records(read_table(binary, pe, metadata.redirection_metadata, metadata.redirection_metadata_count, size_of::<Arm64ECRedirection>())?)?;
+
+        // The auxiliary IAT mirrors the import address table entry for entry
+        let iat_size = pe.get_data_directories().map_or(0, |directories| directories.import_address_table.size);
+        let mut auxiliary_iat: Vec<u64> = Vec::new();
+        for entry in read_table(binary, pe, metadata.auxiliary_iat, iat_size / 8, 8)?.chunks_exact(8) {
+            auxiliary_iat.push(u64::from_le_bytes([entry[0], entry[1], entry[2], entry[3], entry[4], entry[5], entry[6], entry[7]]));
+        }
+
+        Ok(Some(ChpeInfo::Arm64EC(Box::new(Arm64ECInfo {
+            metadata,
+            code_ranges,
+            entry_point_ranges,
+            redirections,
+            auxiliary_iat,
+        }))))
+    } else {
+        let size = match version {
+            0 | 1 => 40,
+            2 => 44,
+            _ => size_of::<ChpeX86Metadata>(),
+        };
+        let metadata = read_versioned::<ChpeX86Metadata>(data, size)?;
+
+        let mut code_ranges: Vec<ChpeX86CodeRange> = Vec::new();
+        for entry in read_table(binary, pe, metadata.code_address_range_offset, metadata.code_address_range_count, 8)?.chunks_exact(8) {
+            let start = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            code_ranges.push(ChpeX86CodeRange {
+                rva: start & !1,
+                length: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                native: start & 1 != 0,
+            });
+        }
+
+        Ok(Some(ChpeInfo::X86(ChpeX86Info { metadata, code_ranges })))
+    }
+}
+
+fn read_table<'a>(binary: &'a [u8], pe: &PortableExecutable, rva: u32, count: u32, entry_size: usize) -> Result<&'a [u8], Error> {
+    if rva == 0 || count == 0 {
+        return Ok(&[]);
+    }
+
+    match pe.rva_to_offset(rva).and_then(|offset| binary.get(offset..offset.checked_add((count as usize).checked_mul(entry_size)?)?)) {
+        Some(table) => Ok(table),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+impl Arm64ECCodeRange {
+    /// Returns the kind of code in the range as an enum
+    pub fn get_range_type(&self) -> Option<Arm64ECRangeType> {
+        Arm64ECRangeType::from_u8(self.range_type)
+    }
+
+    /// Returns true if the range contains the given RVA
+    pub fn contains(&self, rva: u32) -> bool {
+        rva >= self.rva && rva - self.rva < self.length
+    }
+}
+
+impl ChpeX86CodeRange {
+    /// Returns true if the range contains the given RVA
+    pub fn contains(&self, rva: u32) -> bool {
+        rva >= self.rva && rva - self.rva < self.length
+    }
+}
+
+impl Arm64ECInfo {
+    /// Find the code map entry containing an RVA
+    pub fn find_code_range(&self, rva: u32) -> Option<&Arm64ECCodeRange> {
+        self.code_ranges.iter().find(|range| range.contains(rva))
+    }
+
+    /// Returns the redirection target of a function, if it has one
+    pub fn get_redirection(&self, rva: u32) -> Option<u32> {
+        self.redirections.iter()
+            .find(|redirection| redirection.source == rva)
+            .map(|redirection| redirection.destination)
+    }
+}
+
+impl ChpeX86Info {
+    /// Find the code range containing an RVA
+    pub fn find_code_range(&self, rva: u32) -> Option<&ChpeX86CodeRange> {
+        self.code_ranges.iter().find(|range| range.contains(rva))
+    }
+}
+
+impl fmt::Display for ChpeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChpeInfo::Arm64EC(info) => fmt::Display::fmt(info, f),
+            ChpeInfo::X86(info) => fmt::Display::fmt(info, f),
+        }
+    }
+}
+
+impl fmt::Display for Arm64ECInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metadata = &self.metadata;
+
+        writeln!(f, "ARM64EC Metadata")?;
+        writeln!(f, "----------------")?;
+        writeln!(f, "Version:                 {}", metadata.version)?;
+        writeln!(f, "Code Map:                {:#010x} ({})", metadata.code_map, metadata.code_map_count)?;
+        writeln!(f, "Entry Point Ranges:      {:#010x} ({})", metadata.code_ranges_to_entry_points, metadata.code_ranges_to_entry_points_count)?;
+        writeln!(f, "Redirection Metadata:    {:#010x} ({})", metadata.redirection_metadata, metadata.redirection_metadata_count)?;
+        writeln!(f, "Dispatch Call No Redir:  {:#010x}", metadata.os_arm64x_dispatch_call_no_redirect)?;
+        writeln!(f, "Dispatch Return:         {:#010x}", metadata.os_arm64x_dispatch_ret)?;
+        writeln!(f, "Dispatch Call:           {:#010x}", metadata.os_arm64x_dispatch_call)?;
+        writeln!(f, "Dispatch Indirect Call:  {:#010x}", metadata.os_arm64x_dispatch_icall)?;
+        writeln!(f, "Dispatch ICall CFG:      {:#010x}", metadata.os_arm64x_dispatch_icall_cfg)?;
+        writeln!(f, "Alternate Entry Point:   {:#010x}", metadata.alternate_entry_point)?;
+        writeln!(f, "Auxiliary IAT:           {:#010x}", metadata.auxiliary_iat)?;
+        writeln!(f, "Get X64 Information:     {:#010x}", metadata.get_x64_information_function_pointer)?;
+        writeln!(f, "Set X64 Information:     {:#010x}", metadata.set_x64_information_function_pointer)?;
+        writeln!(f, "Extra RFE Table:         {:#010x} ({})", metadata.extra_rfe_table, metadata.extra_rfe_table_size)?;
+        writeln!(f, "Dispatch Function Ptr:   {:#010x}", metadata.os_arm64x_dispatch_fptr)?;
+        writeln!(f, "Auxiliary IAT Copy:      {:#010x}", metadata.auxiliary_iat_copy)?;
+        if metadata.version >= 2 {
+            writeln!(f, "Aux Delay-Load IAT:      {:#010x}", metadata.auxiliary_delayload_iat)?;
+            writeln!(f, "Aux Delay-Load IAT Copy: {:#010x}", metadata.auxiliary_delayload_iat_copy)?;
+            writeln!(f, "Hybrid Image Info:       {:#010x}", metadata.hybrid_image_info_bitfield)?;
+        }
+
+        for range in self.code_ranges.iter() {
+            match range.get_range_type() {
+                Some(range_type) => writeln!(f, "Code Range:              {:#010x}-{:#010x} {:?}", range.rva, range.rva.wrapping_add(range.length), range_type)?,
+                None => writeln!(f, "Code Range:              {:#010x}-{:#010x} {}", range.rva, range.rva.wrapping_add(range.length), range.range_type)?,
+            }
+        }
+        for range in self.entry_point_ranges.iter() {
+            writeln!(f, "Entry Point:             {:#010x}-{:#010x} -> {:#010x}", range.start_rva, range.end_rva, range.entry_point)?;
+        }
+        for redirection in self.redirections.iter() {
+            writeln!(f, "Redirection:             {:#010x} -> {:#010x}", redirection.source, redirection.destination)?;
+        }
+        for entry in self.auxiliary_iat.iter() {
+            writeln!(f, "Auxiliary IAT Entry:     {:#018x}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ChpeX86Info {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metadata = &self.metadata;
+
+        writeln!(f, "CHPE Metadata")?;
+        writeln!(f, "-------------")?;
+        writeln!(f, "Version:                 {}", metadata.version)?;
+        writeln!(f, "Code Ranges:             {:#010x} ({})", metadata.code_address_range_offset, metadata.code_address_range_count)?;
+        writeln!(f, "Exception Handler:       {:#010x}", metadata.wow_a64_exception_handler_function_pointer)?;
+        writeln!(f, "Dispatch Call:           {:#010x}", metadata.wow_a64_dispatch_call_function_pointer)?;
+        writeln!(f, "Dispatch Indirect Call:  {:#010x}", metadata.wow_a64_dispatch_indirect_call_function_pointer)?;
+        writeln!(f, "Dispatch ICall CFG:      {:#010x}", metadata.wow_a64_dispatch_indirect_call_cfg_function_pointer)?;
+        writeln!(f, "Dispatch Return:         {:#010x}", metadata.wow_a64_dispatch_ret_function_pointer)?;
+        writeln!(f, "Dispatch Return Leaf:    {:#010x}", metadata.wow_a64_dispatch_ret_leaf_function_pointer)?;
+        writeln!(f, "Dispatch Jump:           {:#010x}", metadata.wow_a64_dispatch_jump_function_pointer)?;
+        if metadata.version >= 2 {
+            writeln!(f, "Compiler IAT:            {:#010x}", metadata.compiler_iat_pointer)?;
+        }
+        if metadata.version >= 3 {
+            writeln!(f, "Rdtsc Function:          {:#010x}", metadata.wow_a64_rdtsc_function_pointer)?;
+        }
+
+        for range in self.code_ranges.iter() {
+            writeln!(f, "Code Range:              {:#010x}-{:#010x} {}", range.rva, range.rva.wrapping_add(range.length), if range.native { "ARM64" } else { "x86" })?;
+        }
+
+        Ok(())
+    }
+}
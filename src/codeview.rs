@@ -0,0 +1,866 @@
+use core::fmt;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use crate::{pe::{read_c_string, read_u16, read_u32}, prelude::*, section::SectionHeader, Error};
+
+/// Name of the CodeView symbol section in object files
+pub const DEBUG_SYMBOLS_SECTION_NAME: [u8; 8] = *b".debug$S";
+/// Name of the CodeView type section in object files
+pub const DEBUG_TYPES_SECTION_NAME: [u8; 8] = *b".debug$T";
+/// Signature at the start of `.debug$S` and `.debug$T` (CV_SIGNATURE_C13)
+pub const CV_SIGNATURE_C13: u32 = 4;
+/// Subsections with this bit set in their kind are ignored by the linker
+pub const DEBUG_S_IGNORE: u32 = 0x80000000;
+/// The first type index that refers to a type record rather than a simple type
+pub const FIRST_TYPE_INDEX: u32 = 0x1000;
+
+/// Kinds of `.debug$S` subsections
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum DebugSubsectionKind {
+    /// Symbol records.
+    Symbols = 0xf1,
+    /// Line numbers of a contiguous range of code.
+    Lines = 0xf2,
+    /// Null-terminated file names, referenced by the file checksums.
+    StringTable = 0xf3,
+    /// File names and checksums, referenced by line blocks.
+    FileChecksums = 0xf4,
+    /// Frame pointer omission data.
+    FrameData = 0xf5,
+    /// Source lines of inlined functions.
+    InlineeLines = 0xf6,
+    /// Symbols imported from other modules.
+    CrossScopeImports = 0xf7,
+    /// Symbols exported to other modules.
+    CrossScopeExports = 0xf8,
+    /// IL line numbers.
+    IlLines = 0xf9,
+    /// Function metadata token map.
+    FuncMdTokenMap = 0xfa,
+    /// Type metadata token map.
+    TypeMdTokenMap = 0xfb,
+    /// Merged assembly input.
+    MergedAssemblyInput = 0xfc,
+    /// RVAs of COFF symbols.
+    CoffSymbolRva = 0xfd,
+}
+
+/// A subsection of `.debug$S`
+#[derive(Clone, Debug)]
+pub enum DebugSubsection {
+    /// Symbol records.
+    Symbols(Vec<SymbolRecord>),
+    /// Line numbers of a contiguous range of code.
+    Lines(LinesSubsection),
+    /// File name strings.
+    StringTable(Vec<u8>),
+    /// File checksums.
+    FileChecksums(Vec<FileChecksum>),
+    /// Source lines of inlined functions.
+    InlineeLines(Vec<InlineeSourceLine>),
+    /// Any other subsection, left undecoded.
+    Other {
+        /// The subsection kind.
+        kind: u32,
+        /// The subsection contents.
+        data: Vec<u8>
+    },
+}
+
+/// Parse the `.debug$S` section of an object file
+pub fn parse_debug_symbols_section(binary: &[u8], section: &SectionHeader) -> Result<Vec<DebugSubsection>, Error> {
+    parse_debug_subsections(get_section_data(binary, section)?)
+}
+
+/// Parse the `.debug$T` section of an object file
+pub fn parse_debug_types_section(binary: &[u8], section: &SectionHeader) -> Result<Vec<TypeRecord>, Error> {
+    parse_type_records(get_section_data(binary, section)?)
+}
+
+fn get_section_data<'a>(binary: &'a [u8], section: &SectionHeader) -> Result<&'a [u8], Error> {
+    let start = section.pointer_to_raw_data as usize;
+    match start.checked_add(section.size_of_raw_data as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => Ok(data),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Parse the contents of a `.debug$S` section:
+/// the CodeView signature followed by 4-byte aligned subsections
+pub fn parse_debug_subsections(data: &[u8]) -> Result<Vec<DebugSubsection>, Error> {
+    if read_u32(data, 0)? != CV_SIGNATURE_C13 {
+        return Err(Error::BadCodeView);
+    }
+
+    parse_subsection_list(&data[4..])
+}
+
+/// Parse a list of subsections not preceded by a signature,
+/// as found in the C13 part of PDB module streams
+pub(crate) fn parse_subsection_list(data: &[u8]) -> Result<Vec<DebugSubsection>, Error> {
+    let mut subsections: Vec<DebugSubsection> = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let kind = read_u32(data, offset)?;
+        let length = read_u32(data, offset + 4)? as usize;
+        let start = offset + 8;
+        let contents = match start.checked_add(length).and_then(|end| data.get(start..end)) {
+            Some(contents) => contents,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        let subsection = match DebugSubsectionKind::from_u32(kind & !DEBUG_S_IGNORE) {
+            Some(DebugSubsectionKind::Symbols) => DebugSubsection::Symbols(parse_symbol_records(contents)?),
+            Some(DebugSubsectionKind::Lines) => DebugSubsection::Lines(parse_lines(contents)?),
+            Some(DebugSubsectionKind::StringTable) => DebugSubsection::StringTable(contents.to_vec()),
+            Some(DebugSubsectionKind::FileChecksums) => DebugSubsection::FileChecksums(parse_file_checksums(contents)?),
+            Some(DebugSubsectionKind::InlineeLines) => DebugSubsection::InlineeLines(parse_inlinee_lines(contents)?),
+            _ => DebugSubsection::Other { kind, data: contents.to_vec() },
+        };
+
+        subsections.push(subsection);
+        offset = align4(start + length);
+    }
+
+    Ok(subsections)
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// A CodeView symbol record
+#[derive(Clone, Debug)]
+pub struct SymbolRecord {
+    /// The record kind (`S_*`).
+    pub kind: u16,
+    /// The record contents following the kind.
+    pub data: Vec<u8>
+}
+
+/// Parse a sequence of symbol records, each a 16-bit length
+/// (not including itself) followed by a 16-bit kind
+pub fn parse_symbol_records(data: &[u8]) -> Result<Vec<SymbolRecord>, Error> {
+    let mut records: Vec<SymbolRecord> = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let length = read_u16(data, offset)? as usize;
+        if length < 2 {
+            return Err(Error::BadCodeView);
+        }
+
+        let kind = read_u16(data, offset + 2)?;
+        let record = match data.get(offset+4..offset+2+length) {
+            Some(record) => record,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        records.push(SymbolRecord { kind, data: record.to_vec() });
+        offset += 2 + length;
+    }
+
+    Ok(records)
+}
+
+impl SymbolRecord {
+    /// Returns the record kind as an enum
+    pub fn get_kind(&self) -> Option<SymbolKind> {
+        SymbolKind::from_u16(self.kind)
+    }
+
+    /// Returns the name of records that have one
+    pub fn get_name(&self) -> Option<String> {
+        let offset = match self.get_kind()? {
+            SymbolKind::ObjName | SymbolKind::Udt | SymbolKind::Export => 4,
+            SymbolKind::Local => 6,
+            SymbolKind::Label32 => 7,
+            SymbolKind::LData32 | SymbolKind::GData32 | SymbolKind::Pub32 | SymbolKind::RegRel32
+                | SymbolKind::LThread32 | SymbolKind::GThread32 | SymbolKind::FileStatic
+                | SymbolKind::ProcRef | SymbolKind::DataRef | SymbolKind::LProcRef => 10,
+            SymbolKind::CoffGroup => 14,
+            SymbolKind::Section => 16,
+            SymbolKind::Thunk32 => 21,
+            SymbolKind::Compile3 => 22,
+            SymbolKind::LProc32 | SymbolKind::GProc32 | SymbolKind::LProc32Id | SymbolKind::GProc32Id => 35,
+            SymbolKind::Constant => {
+                let (_, size) = read_numeric_leaf(&self.data, 4)?;
+                4 + size
+            }
+            SymbolKind::UNamespace => 0,
+            _ => {
+                return None;
+            }
+        };
+
+        read_c_string(&self.data, offset).ok()
+    }
+}
+
+impl fmt::Display for SymbolRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_kind() {
+            Some(kind) => write!(f, "{:?}", kind)?,
+            None => write!(f, "{:#06x}", self.kind)?,
+        }
+        if let Some(name) = self.get_name() {
+            write!(f, " {}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a numeric leaf: values below `LF_NUMERIC` are stored directly,
+/// larger values are preceded by a leaf giving their size.
+/// Returns the value and the number of bytes read.
+pub fn read_numeric_leaf(data: &[u8], offset: usize) -> Option<(u64, usize)> {
+    let leaf = read_u16(data, offset).ok()?;
+    if leaf < 0x8000 {
+        return Some((leaf as u64, 2));
+    }
+
+    let size = match leaf {
+        0x8000 => 1,
+        0x8001 | 0x8002 => 2,
+        0x8003 | 0x8004 => 4,
+        0x8009 | 0x800a => 8,
+        _ => {
+            return None;
+        }
+    };
+
+    let bytes = data.get(offset+2..offset+2+size)?;
+    let mut value = [0u8; 8];
+    value[..size].copy_from_slice(bytes);
+    Some((u64::from_le_bytes(value), 2 + size))
+}
+
+/// Symbol record kinds
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum SymbolKind {
+    /// S_END: end of a block, procedure or thunk.
+    End = 0x0006,
+    /// S_FRAMEPROC: extra frame and procedure information.
+    FrameProc = 0x1012,
+    /// S_OBJNAME: path to the object file.
+    ObjName = 0x1101,
+    /// S_THUNK32: thunk start.
+    Thunk32 = 0x1102,
+    /// S_BLOCK32: block start.
+    Block32 = 0x1103,
+    /// S_LABEL32: code label.
+    Label32 = 0x1105,
+    /// S_REGISTER: register variable.
+    Register = 0x1106,
+    /// S_CONSTANT: constant symbol.
+    Constant = 0x1107,
+    /// S_UDT: user-defined type.
+    Udt = 0x1108,
+    /// S_BPREL32: BP-relative variable.
+    BpRel32 = 0x110b,
+    /// S_LDATA32: module-local data.
+    LData32 = 0x110c,
+    /// S_GDATA32: global data.
+    GData32 = 0x110d,
+    /// S_PUB32: public symbol.
+    Pub32 = 0x110e,
+    /// S_LPROC32: local procedure start.
+    LProc32 = 0x110f,
+    /// S_GPROC32: global procedure start.
+    GProc32 = 0x1110,
+    /// S_REGREL32: register-relative variable.
+    RegRel32 = 0x1111,
+    /// S_LTHREAD32: module-local thread storage.
+    LThread32 = 0x1112,
+    /// S_GTHREAD32: global thread storage.
+    GThread32 = 0x1113,
+    /// S_UNAMESPACE: using namespace.
+    UNamespace = 0x1124,
+    /// S_PROCREF: reference to a procedure.
+    ProcRef = 0x1125,
+    /// S_DATAREF: reference to data.
+    DataRef = 0x1126,
+    /// S_LPROCREF: reference to a module-local procedure.
+    LProcRef = 0x1127,
+    /// S_TRAMPOLINE: incremental linking trampoline.
+    Trampoline = 0x112c,
+    /// S_SECTION: image section.
+    Section = 0x1136,
+    /// S_COFFGROUP: COFF section group in an image.
+    CoffGroup = 0x1137,
+    /// S_EXPORT: exported symbol.
+    Export = 0x1138,
+    /// S_CALLSITEINFO: indirect call site information.
+    CallSiteInfo = 0x1139,
+    /// S_FRAMECOOKIE: security cookie information.
+    FrameCookie = 0x113a,
+    /// S_COMPILE3: compiler and flags.
+    Compile3 = 0x113c,
+    /// S_ENVBLOCK: environment block, such as the working directory and command line.
+    EnvBlock = 0x113d,
+    /// S_LOCAL: local variable.
+    Local = 0x113e,
+    /// S_DEFRANGE_REGISTER: live range of a variable in a register.
+    DefRangeRegister = 0x1141,
+    /// S_DEFRANGE_FRAMEPOINTER_REL: live range of a frame-pointer-relative variable.
+    DefRangeFramePointerRel = 0x1142,
+    /// S_DEFRANGE_SUBFIELD_REGISTER: live range of part of a variable in a register.
+    DefRangeSubfieldRegister = 0x1143,
+    /// S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE: frame-pointer-relative variable live for the whole function.
+    DefRangeFramePointerRelFullScope = 0x1144,
+    /// S_DEFRANGE_REGISTER_REL: live range of a register-relative variable.
+    DefRangeRegisterRel = 0x1145,
+    /// S_LPROC32_ID: local procedure start, with an item ID.
+    LProc32Id = 0x1146,
+    /// S_GPROC32_ID: global procedure start, with an item ID.
+    GProc32Id = 0x1147,
+    /// S_BUILDINFO: build information.
+    BuildInfo = 0x114c,
+    /// S_INLINESITE: inlined function call site.
+    InlineSite = 0x114d,
+    /// S_INLINESITE_END: end of an inlined function call site.
+    InlineSiteEnd = 0x114e,
+    /// S_PROC_ID_END: end of a procedure with an item ID.
+    ProcIdEnd = 0x114f,
+    /// S_FILESTATIC: file static variable.
+    FileStatic = 0x1153,
+    /// S_CALLEES: functions called by a procedure.
+    Callees = 0x115a,
+    /// S_CALLERS: functions calling a procedure.
+    Callers = 0x115b,
+    /// S_HEAPALLOCSITE: heap allocation site.
+    HeapAllocSite = 0x115e,
+    /// S_INLINEES: functions inlined into a procedure.
+    Inlinees = 0x1168,
+}
+
+/// The header of a lines subsection
+#[derive(Clone, Debug)]
+pub struct LinesSubsection {
+    /// Offset of the code, relocated by a SECREL relocation.
+    pub offset: u32,
+    /// Section of the code, relocated by a SECTION relocation.
+    pub segment: u16,
+    /// Flags; bit 0 set if the blocks have column entries.
+    pub flags: u16,
+    /// Size of the code the lines describe.
+    pub code_size: u32,
+    /// Line blocks, one per source file.
+    pub blocks: Vec<LineBlock>
+}
+
+/// The lines of one source file within a lines subsection
+#[derive(Clone, Debug)]
+pub struct LineBlock {
+    /// Offset of the file in the file checksums subsection.
+    pub file_checksum_offset: u32,
+    /// Line entries, ordered by code offset.
+    pub lines: Vec<LineEntry>,
+    /// Column entries, parallel to `lines`, if the subsection has columns.
+    pub columns: Vec<ColumnEntry>
+}
+
+/// A CodeView line entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineEntry {
+    /// Code offset, relative to the subsection's offset.
+    pub offset: u32,
+    /// The first line of the statement.
+    pub line_start: u32,
+    /// The number of lines the statement spans beyond the first.
+    pub delta_line_end: u8,
+    /// True if this is a statement rather than an expression.
+    pub is_statement: bool
+}
+
+/// A CodeView column entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnEntry {
+    /// The first column of the statement.
+    pub start: u16,
+    /// The column after the end of the statement.
+    pub end: u16
+}
+
+/// Lines subsection flag indicating column entries follow the line entries
+pub const CV_LINES_HAVE_COLUMNS: u16 = 0x0001;
+
+fn parse_lines(data: &[u8]) -> Result<LinesSubsection, Error> {
+    let mut lines = LinesSubsection {
+        offset: read_u32(data, 0)?,
+        segment: read_u16(data, 4)?,
+        flags: read_u16(data, 6)?,
+        code_size: read_u32(data, 8)?,
+        blocks: Vec::new(),
+    };
+
+    let has_columns = lines.flags & CV_LINES_HAVE_COLUMNS != 0;
+    let mut offset = 12;
+    while offset < data.len() {
+        let file_checksum_offset = read_u32(data, offset)?;
+        let number_of_lines = read_u32(data, offset + 4)? as usize;
+        let block_size = read_u32(data, offset + 8)? as usize;
+        if block_size < 12 {
+            return Err(Error::BadCodeView);
+        }
+
+        let mut block = LineBlock {
+            file_checksum_offset,
+            lines: Vec::new(),
+            columns: Vec::new(),
+        };
+
+        let mut cursor = offset + 12;
+        for _ in 0..number_of_lines {
+            let flags = read_u32(data, cursor + 4)?;
+            block.lines.push(LineEntry {
+                offset: read_u32(data, cursor)?,
+                line_start: flags & 0x00ffffff,
+                delta_line_end: ((flags >> 24) & 0x7f) as u8,
+                is_statement: flags & 0x80000000 != 0,
+            });
+            cursor += 8;
+        }
+
+        if has_columns {
+            for _ in 0..number_of_lines {
+                block.columns.push(ColumnEntry {
+                    start: read_u16(data, cursor)?,
+                    end: read_u16(data, cursor + 2)?,
+                });
+                cursor += 4;
+            }
+        }
+
+        lines.blocks.push(block);
+        offset += block_size;
+    }
+
+    Ok(lines)
+}
+
+/// A source file and its checksum
+#[derive(Clone, Debug)]
+pub struct FileChecksum {
+    /// Offset of this entry within the subsection, as referenced by line blocks.
+    pub offset: u32,
+    /// Offset of the file name in the string table subsection.
+    pub file_name_offset: u32,
+    /// The checksum algorithm: 0 none, 1 MD5, 2 SHA1, 3 SHA256.
+    pub checksum_kind: u8,
+    /// The checksum bytes.
+    pub checksum: Vec<u8>
+}
+
+fn parse_file_checksums(data: &[u8]) -> Result<Vec<FileChecksum>, Error> {
+    let mut checksums: Vec<FileChecksum> = Vec::new();
+    let mut offset = 0;
+
+    while offset + 6 <= data.len() {
+        let size = data[offset + 4] as usize;
+        let checksum = match data.get(offset+6..offset+6+size) {
+            Some(checksum) => checksum,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        checksums.push(FileChecksum {
+            offset: offset as u32,
+            file_name_offset: read_u32(data, offset)?,
+            checksum_kind: data[offset + 5],
+            checksum: checksum.to_vec(),
+        });
+        offset = align4(offset + 6 + size);
+    }
+
+    Ok(checksums)
+}
+
+/// The source location of an inlined function
+#[derive(Clone, Debug)]
+pub struct InlineeSourceLine {
+    /// The item ID of the inlined function.
+    pub inlinee: u32,
+    /// Offset of the file in the file checksums subsection.
+    pub file_checksum_offset: u32,
+    /// The line the function starts on.
+    pub source_line: u32,
+    /// Additional files contributing to the function (extended format only).
+    pub extra_files: Vec<u32>
+}
+
+/// Inlinee lines subsection signature with extra files
+const CV_INLINEE_SOURCE_LINE_SIGNATURE_EX: u32 = 1;
+
+fn parse_inlinee_lines(data: &[u8]) -> Result<Vec<InlineeSourceLine>, Error> {
+    let extended = read_u32(data, 0)? == CV_INLINEE_SOURCE_LINE_SIGNATURE_EX;
+    let mut inlinees: Vec<InlineeSourceLine> = Vec::new();
+    let mut offset = 4;
+
+    while offset < data.len() {
+        let mut inlinee = InlineeSourceLine {
+            inlinee: read_u32(data, offset)?,
+            file_checksum_offset: read_u32(data, offset + 4)?,
+            source_line: read_u32(data, offset + 8)?,
+            extra_files: Vec::new(),
+        };
+        offset += 12;
+
+        if extended {
+            let count = read_u32(data, offset)?;
+            offset += 4;
+            for _ in 0..count {
+                inlinee.extra_files.push(read_u32(data, offset)?);
+                offset += 4;
+            }
+        }
+
+        inlinees.push(inlinee);
+    }
+
+    Ok(inlinees)
+}
+
+impl DebugSubsection {
+    /// Returns the subsection kind
+    pub fn get_kind(&self) -> Option<DebugSubsectionKind> {
+        match self {
+            DebugSubsection::Symbols(_) => Some(DebugSubsectionKind::Symbols),
+            DebugSubsection::Lines(_) => Some(DebugSubsectionKind::Lines),
+            DebugSubsection::StringTable(_) => Some(DebugSubsectionKind::StringTable),
+            DebugSubsection::FileChecksums(_) => Some(DebugSubsectionKind::FileChecksums),
+            DebugSubsection::InlineeLines(_) => Some(DebugSubsectionKind::InlineeLines),
+            DebugSubsection::Other { kind, .. } => DebugSubsectionKind::from_u32(kind & !DEBUG_S_IGNORE),
+        }
+    }
+}
+
+/// Get a string from a string table subsection
+pub fn get_string(string_table: &[u8], offset: u32) -> Option<String> {
+    read_c_string(string_table, offset as usize).ok()
+}
+
+impl fmt::Display for DebugSubsection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugSubsection::Symbols(records) => {
+                writeln!(f, "Symbols")?;
+                writeln!(f, "-------")?;
+                for record in records.iter() {
+                    writeln!(f, "{}", record)?;
+                }
+            }
+            DebugSubsection::Lines(lines) => {
+                writeln!(f, "Lines")?;
+                writeln!(f, "-----")?;
+                writeln!(f, "Code Size:               {}", lines.code_size)?;
+                for block in lines.blocks.iter() {
+                    writeln!(f, "File:                    {:#x}", block.file_checksum_offset)?;
+                    for line in block.lines.iter() {
+                        writeln!(f, "  {:#010x}  {}", line.offset, line.line_start)?;
+                    }
+                }
+            }
+            DebugSubsection::StringTable(strings) => {
+                writeln!(f, "String Table")?;
+                writeln!(f, "------------")?;
+                for string in strings.split(|&c| c == 0).filter(|string| !string.is_empty()) {
+                    writeln!(f, "{}", String::from_utf8_lossy(string))?;
+                }
+            }
+            DebugSubsection::FileChecksums(checksums) => {
+                writeln!(f, "File Checksums")?;
+                writeln!(f, "--------------")?;
+                for checksum in checksums.iter() {
+                    write!(f, "{:#06x}  {:#x}  ", checksum.offset, checksum.file_name_offset)?;
+                    for byte in checksum.checksum.iter() {
+                        write!(f, "{:02x}", byte)?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            DebugSubsection::InlineeLines(inlinees) => {
+                writeln!(f, "Inlinee Lines")?;
+                writeln!(f, "-------------")?;
+                for inlinee in inlinees.iter() {
+                    writeln!(f, "{:#x}  {:#x}  {}", inlinee.inlinee, inlinee.file_checksum_offset, inlinee.source_line)?;
+                }
+            }
+            DebugSubsection::Other { kind, data } => {
+                writeln!(f, "Subsection {:#x} ({} bytes)", kind, data.len())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A CodeView type record
+#[derive(Clone, Debug)]
+pub struct TypeRecord {
+    /// The type index of the record, starting at `FIRST_TYPE_INDEX`.
+    pub index: u32,
+    /// The record kind (`LF_*`).
+    pub kind: u16,
+    /// The record contents following the kind.
+    pub data: Vec<u8>
+}
+
+/// Parse the contents of a `.debug$T` section:
+/// the CodeView signature followed by type records
+pub fn parse_type_records(data: &[u8]) -> Result<Vec<TypeRecord>, Error> {
+    if read_u32(data, 0)? != CV_SIGNATURE_C13 {
+        return Err(Error::BadCodeView);
+    }
+
+    let mut records: Vec<TypeRecord> = Vec::new();
+    for (i, record) in parse_symbol_records(&data[4..])?.into_iter().enumerate() {
+        records.push(TypeRecord {
+            index: FIRST_TYPE_INDEX + i as u32,
+            kind: record.kind,
+            data: record.data,
+        });
+    }
+
+    Ok(records)
+}
+
+impl TypeRecord {
+    /// Returns the record kind as an enum
+    pub fn get_kind(&self) -> Option<TypeLeafKind> {
+        TypeLeafKind::from_u16(self.kind)
+    }
+
+    /// Returns the name of records that have one
+    pub fn get_name(&self) -> Option<String> {
+        let offset = match self.get_kind()? {
+            TypeLeafKind::Class | TypeLeafKind::Structure | TypeLeafKind::Interface => {
+                let (_, size) = read_numeric_leaf(&self.data, 16)?;
+                16 + size
+            }
+            TypeLeafKind::Union => {
+                let (_, size) = read_numeric_leaf(&self.data, 8)?;
+                8 + size
+            }
+            TypeLeafKind::Enum => 12,
+            TypeLeafKind::FuncId | TypeLeafKind::MFuncId => 8,
+            TypeLeafKind::StringId => 4,
+            _ => {
+                return None;
+            }
+        };
+
+        read_c_string(&self.data, offset).ok()
+    }
+}
+
+impl fmt::Display for TypeRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x}  ", self.index)?;
+        match self.get_kind() {
+            Some(kind) => write!(f, "{:?}", kind)?,
+            None => write!(f, "{:#06x}", self.kind)?,
+        }
+        if let Some(name) = self.get_name() {
+            write!(f, " {}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Type record kinds
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum TypeLeafKind {
+    /// LF_VTSHAPE: virtual function table shape.
+    VtShape = 0x000a,
+    /// LF_LABEL: label.
+    Label = 0x000e,
+    /// LF_MODIFIER: const, volatile or unaligned type.
+    Modifier = 0x1001,
+    /// LF_POINTER: pointer.
+    Pointer = 0x1002,
+    /// LF_PROCEDURE: procedure signature.
+    Procedure = 0x1008,
+    /// LF_MFUNCTION: member function signature.
+    MFunction = 0x1009,
+    /// LF_ARGLIST: argument list.
+    ArgList = 0x1201,
+    /// LF_FIELDLIST: field list of a class, structure, union or enum.
+    FieldList = 0x1203,
+    /// LF_BITFIELD: bit field.
+    BitField = 0x1205,
+    /// LF_METHODLIST: overloaded method list.
+    MethodList = 0x1206,
+    /// LF_BCLASS: real base class.
+    BClass = 0x1400,
+    /// LF_VBCLASS: direct virtual base class.
+    VbClass = 0x1401,
+    /// LF_IVBCLASS: indirect virtual base class.
+    IvbClass = 0x1402,
+    /// LF_INDEX: continuation of a field list.
+    Index = 0x1404,
+    /// LF_VFUNCTAB: virtual function table pointer.
+    VFuncTab = 0x1409,
+    /// LF_ENUMERATE: enumerator.
+    Enumerate = 0x1502,
+    /// LF_ARRAY: array.
+    Array = 0x1503,
+    /// LF_CLASS: class.
+    Class = 0x1504,
+    /// LF_STRUCTURE: structure.
+    Structure = 0x1505,
+    /// LF_UNION: union.
+    Union = 0x1506,
+    /// LF_ENUM: enumeration.
+    Enum = 0x1507,
+    /// LF_MEMBER: data member.
+    Member = 0x150d,
+    /// LF_STMEMBER: static data member.
+    StMember = 0x150e,
+    /// LF_METHOD: overloaded method.
+    Method = 0x150f,
+    /// LF_NESTTYPE: nested type.
+    NestType = 0x1510,
+    /// LF_ONEMETHOD: non-overloaded method.
+    OneMethod = 0x1511,
+    /// LF_TYPESERVER2: reference to a PDB holding the types.
+    TypeServer2 = 0x1515,
+    /// LF_INTERFACE: interface.
+    Interface = 0x1519,
+    /// LF_FUNC_ID: global function.
+    FuncId = 0x1601,
+    /// LF_MFUNC_ID: member function.
+    MFuncId = 0x1602,
+    /// LF_BUILDINFO: build information.
+    BuildInfo = 0x1603,
+    /// LF_SUBSTR_LIST: list of string IDs.
+    SubstrList = 0x1604,
+    /// LF_STRING_ID: string.
+    StringId = 0x1605,
+    /// LF_UDT_SRC_LINE: source location of a user-defined type.
+    UdtSrcLine = 0x1606,
+    /// LF_UDT_MOD_SRC_LINE: source location and module of a user-defined type.
+    UdtModSrcLine = 0x1607,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = ((data.len() + 2) as u16).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&kind.to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn subsection(kind: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = kind.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes.resize(align4(bytes.len()), 0);
+        bytes
+    }
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn debug_symbols_section() {
+        let mut proc = vec![0u8; 35];
+        proc[32..34].copy_from_slice(&1u16.to_le_bytes());
+        proc.extend_from_slice(b"main\0");
+        let mut symbols = record(SymbolKind::ObjName as u16, b"\0\0\0\0a.obj\0");
+        symbols.extend(record(SymbolKind::GProc32 as u16, &proc));
+
+        // Two lines with columns, in one block for the file at checksum offset 0x18
+        let mut lines = words(&[0x10, 1 | (CV_LINES_HAVE_COLUMNS as u32) << 16, 0x20]);
+        lines.extend(words(&[0x18, 2, 12 + 2 * 8 + 2 * 4, 0, 3 | 0x80000000, 8, 4 | (1 << 24)]));
+        lines.extend(words(&[5 | 9 << 16, 1 | 2 << 16]));
+
+        // One file without a checksum, padded to 4 bytes, and one with a 4-byte checksum
+        let mut checksums = words(&[1]);
+        checksums.extend_from_slice(&[0, 0, 0, 0]);
+        checksums.extend(words(&[1]));
+        checksums.extend_from_slice(&[4, 1, 0x9a, 0xbc, 0xde, 0xf0]);
+
+        let mut data = words(&[CV_SIGNATURE_C13]);
+        data.extend(subsection(DebugSubsectionKind::Symbols as u32, &symbols));
+        data.extend(subsection(DebugSubsectionKind::Lines as u32, &lines));
+        data.extend(subsection(DebugSubsectionKind::StringTable as u32, b"\0a.c\0"));
+        data.extend(subsection(DebugSubsectionKind::FileChecksums as u32, &checksums));
+        data.extend(subsection(DebugSubsectionKind::FrameData as u32 | DEBUG_S_IGNORE, &[1, 2, 3]));
+
+        let subsections = parse_debug_subsections(&data).unwrap();
+        let kinds: Vec<Option<DebugSubsectionKind>> = subsections.iter().map(|subsection| subsection.get_kind()).collect();
+        assert_eq!(kinds, [
+            Some(DebugSubsectionKind::Symbols),
+            Some(DebugSubsectionKind::Lines),
+            Some(DebugSubsectionKind::StringTable),
+            Some(DebugSubsectionKind::FileChecksums),
+            Some(DebugSubsectionKind::FrameData),
+        ]);
+
+        let DebugSubsection::Symbols(records) = &subsections[0] else { unreachable!() };
+        let names: Vec<Option<String>> = records.iter().map(|record| record.get_name()).collect();
+        assert_eq!(names, [Some(String::from("a.obj")), Some(String::from("main"))]);
+
+        let DebugSubsection::Lines(lines) = &subsections[1] else { unreachable!() };
+        assert_eq!((lines.offset, lines.segment, lines.code_size), (0x10, 1, 0x20));
+        assert_eq!(lines.blocks[0].file_checksum_offset, 0x18);
+        assert_eq!(lines.blocks[0].lines, [
+            LineEntry { offset: 0, line_start: 3, delta_line_end: 0, is_statement: true },
+            LineEntry { offset: 8, line_start: 4, delta_line_end: 1, is_statement: false },
+        ]);
+        assert_eq!(lines.blocks[0].columns, [ColumnEntry { start: 5, end: 9 }, ColumnEntry { start: 1, end: 2 }]);
+
+        let DebugSubsection::StringTable(strings) = &subsections[2] else { unreachable!() };
+        let DebugSubsection::FileChecksums(checksums) = &subsections[3] else { unreachable!() };
+        let offsets: Vec<u32> = checksums.iter().map(|checksum| checksum.offset).collect();
+        assert_eq!(offsets, [0, 8]);
+        assert_eq!(get_string(strings, checksums[0].file_name_offset).as_deref(), Some("a.c"));
+        assert_eq!((checksums[1].checksum_kind, &checksums[1].checksum[..]), (1, &[0x9a, 0xbc, 0xde, 0xf0][..]));
+    }
+
+    #[test]
+    fn truncated_records() {
+        assert!(matches!(parse_symbol_records(&[4, 0, 0x01, 0x11]), Err(Error::OffsetOutOfRange)));
+        assert!(matches!(parse_symbol_records(&[1, 0, 0x01]), Err(Error::BadCodeView)));
+        assert!(matches!(parse_debug_subsections(&words(&[1])), Err(Error::BadCodeView)));
+    }
+
+    #[test]
+    fn numeric_leaves() {
+        assert_eq!(read_numeric_leaf(&[0x34, 0x12], 0), Some((0x1234, 2)));
+        assert_eq!(read_numeric_leaf(&[0x03, 0x80, 0x78, 0x56, 0x34, 0x12], 0), Some((0x12345678, 6)));
+        assert_eq!(read_numeric_leaf(&[0x03, 0x80, 0x78], 0), None);
+        assert_eq!(read_numeric_leaf(&[0x05, 0x80], 0), None);
+    }
+
+    #[test]
+    fn type_records() {
+        // A structure whose size is a 2-byte numeric leaf
+        let mut structure = vec![0u8; 16];
+        structure.extend_from_slice(&[0x02, 0x80, 0x00, 0x01]);
+        structure.extend_from_slice(b"Point\0");
+        let mut data = words(&[CV_SIGNATURE_C13]);
+        data.extend(record(TypeLeafKind::Structure as u16, &structure));
+        data.extend(record(TypeLeafKind::StringId as u16, b"\0\0\0\0a.c\0"));
+
+        let records = parse_type_records(&data).unwrap();
+        let names: Vec<(u32, Option<String>)> = records.iter().map(|record| (record.index, record.get_name())).collect();
+        assert_eq!(names, [(FIRST_TYPE_INDEX, Some(String::from("Point"))), (FIRST_TYPE_INDEX + 1, Some(String::from("a.c")))]);
+    }
+}
@@ -29,33 +29,125 @@ pub struct CoffFileHeader {
 
 impl fmt::Display for CoffFileHeader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let machine_type = self.get_machine_type()
-            .expect("Failed to get machine type");
-        let characteristics = self.get_characteristics()
-            .expect("Failed to get characteristics");
-        #[cfg(feature = "chrono")]
-        let time = self.get_time_date_stamp()
-            .expect("Failed to get time date stamp");
-        #[cfg(not(feature = "chrono"))]
-        let time = self.time_date_stamp;
-
         writeln!(f, "COFF Header")?;
         writeln!(f, "-----------")?;
-        writeln!(f, "Machine Type:            {:?}", machine_type)?;
+        match self.get_machine_type() {
+            Some(machine_type) => writeln!(f, "Machine Type:            {:?}", machine_type)?,
+            None => writeln!(f, "Machine Type:            {:#06x}", self.machine)?,
+        }
         writeln!(f, "Number of Sections:      {}", self.number_of_sections)?;
-        writeln!(f, "Time Date Stamp:         {}", time)?;
+        #[cfg(feature = "chrono")]
+        match self.get_time_date_stamp() {
+            Some(time) => writeln!(f, "Time Date Stamp:         {}", time)?,
+            None => writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?,
+        }
+        #[cfg(not(feature = "chrono"))]
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
         writeln!(f, "Pointer of Symbol Table: {:#010x}", self.pointer_to_symbol_table)?;
         writeln!(f, "Number of Symbols:       {}", self.number_of_symbols)?;
         writeln!(f, "Size of Optional Header: {}", self.size_of_optional_header)?;
-        writeln!(f, "Characteristics:         {}", characteristics)?;
+        match self.get_characteristics() {
+            Some(characteristics) => writeln!(f, "Characteristics:         {}", characteristics)?,
+            None => writeln!(f, "Characteristics:         {:#06x}", self.characteristics)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Class ID identifying an `ANON_OBJECT_HEADER_BIGOBJ`,
+/// {D1BAA1C7-BAEE-4BA9-AF20-FAF66AA4DCB8} in its in-file byte order.
+pub const BIGOBJ_CLASS_ID: [u8; 16] = [
+    0xc7, 0xa1, 0xba, 0xd1, 0xee, 0xba, 0xa9, 0x4b,
+    0xaf, 0x20, 0xfa, 0xf6, 0x6a, 0xa4, 0xdc, 0xb8
+];
+
+/// Big Object File Header (Object Only)
+/// Emitted instead of the COFF file header by MSVC `/bigobj`,
+/// allowing 32-bit section counts and section numbers.
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct BigObjHeader {
+    /// Must be `IMAGE_FILE_MACHINE_UNKNOWN` (0).
+    pub sig1: u16,
+    /// Must be 0xffff.
+    pub sig2: u16,
+    /// Header version, 2 or greater.
+    pub version: u16,
+    /// The number that identifies the type of target machine.
+    pub machine: u16,
+    /// The low 32 bits of the number of seconds since 00:00 January 1, 1970, which indicates when the file was created.
+    pub time_date_stamp: u32,
+    /// Must be `BIGOBJ_CLASS_ID`.
+    pub class_id: [u8; 16],
+    /// Size of data that follows the header.
+    pub size_of_data: u32,
+    /// Reserved flags, must be 0.
+    pub flags: u32,
+    /// Size of CLR metadata.
+    pub meta_data_size: u32,
+    /// File offset of CLR metadata.
+    pub meta_data_offset: u32,
+    /// The number of sections.
+    pub number_of_sections: u32,
+    /// The file offset of the symbol table.
+    pub pointer_to_symbol_table: u32,
+    /// The number of 20-byte records in the symbol table, including auxiliary records.
+    pub number_of_symbols: u32
+}
+
+impl fmt::Display for BigObjHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Big Object Header")?;
+        writeln!(f, "-----------------")?;
+        writeln!(f, "Version:                 {}", self.version)?;
+        match self.get_machine_type() {
+            Some(machine_type) => writeln!(f, "Machine Type:            {:?}", machine_type)?,
+            None => writeln!(f, "Machine Type:            {:#06x}", self.machine)?,
+        }
+        #[cfg(feature = "chrono")]
+        match self.get_time_date_stamp() {
+            Some(time) => writeln!(f, "Time Date Stamp:         {}", time)?,
+            None => writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?,
+        }
+        #[cfg(not(feature = "chrono"))]
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
+        writeln!(f, "Number of Sections:      {}", self.number_of_sections)?;
+        writeln!(f, "Pointer of Symbol Table: {:#010x}", self.pointer_to_symbol_table)?;
+        writeln!(f, "Number of Symbols:       {}", self.number_of_symbols)?;
 
         Ok(())
     }
 }
 
+impl BigObjHeader {
+    /// Returns the machine type as an enum
+    pub fn get_machine_type(&self) -> Option<MachineTypes> {
+        MachineTypes::from_u16(self.machine)
+    }
+
+    /// Returns the Unix epoch timestamp as a `DateTime<Utc>`
+    #[cfg(feature = "chrono")]
+    pub fn get_time_date_stamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.time_date_stamp.into(), 0)
+    }
+}
+
+/// Returns true if the binary starts with a big object header
+pub fn is_big_object(binary: &[u8]) -> bool {
+    match binary.get(0..size_of::<BigObjHeader>()) {
+        Some(slice) => {
+            slice[0..4] == [0x00, 0x00, 0xff, 0xff]
+                && u16::from_le_bytes([slice[4], slice[5]]) >= 2
+                && slice[12..28] == BIGOBJ_CLASS_ID
+        }
+        None => false,
+    }
+}
+
 /// The Machine field has one of the following values, which specify the CPU type. 
 /// An image file can be run only on the specified machine or on a system that emulates the specified machine.
-#[derive(FromPrimitive, Debug, PartialEq)]
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
 #[repr(u16)]
 pub enum MachineTypes {
     /// The content of this field is assumed to be applicable to any machine type
@@ -72,8 +164,14 @@ pub enum MachineTypes {
     ARM = 0x1c0,
     /// ARM64 little endian
     ARM64 = 0xaa64,
+    /// ARM64EC, ARM64 code that interoperates with x64 code
+    ARM64EC = 0xa641,
+    /// ARM64X, a hybrid image containing both ARM64 and ARM64EC code
+    ARM64X = 0xa64e,
     /// ARM Thumb-2 little endian
     ARMNT = 0x1c4,
+    /// x86 image with ARM64 code (compiled hybrid portable executable)
+    CHPEX86 = 0x3a64,
     /// EFI byte code
     EBC = 0xebc,
     /// Intel 386 or later processors and compatible processors
@@ -0,0 +1,203 @@
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use core::fmt;
+use crate::{prelude::*, section::{SectionHeader, SectionFlags}, string_table::StringTable, symbol::{AuxSymbol, StorageClass, Symbol}};
+
+/// Match every COMDAT section (`IMAGE_SCN_LNK_COMDAT`) with its
+/// section definition and COMDAT symbol.
+/// The first symbol that names a COMDAT section is its section symbol,
+/// whose auxiliary record holds the selection;
+/// the next symbol defined in the same section is the COMDAT symbol,
+/// except for associative sections, which have none.
+pub fn parse_comdats(section_table: &[SectionHeader], symbol_table: &[Symbol], string_table: Option<&StringTable>) -> Vec<Comdat> {
+    let mut comdats: Vec<Comdat> = Vec::new();
+
+    for (position, symbol) in symbol_table.iter().enumerate() {
+        let Some(AuxSymbol::SectionDefinition(definition)) = symbol.aux_symbols.first() else {
+            continue;
+        };
+
+        if symbol.section_number <= 0 || comdats.iter().any(|comdat| comdat.section_number as i32 == symbol.section_number) {
+            continue;
+        }
+
+        let is_comdat = section_table.get(symbol.section_number as usize - 1)
+            .is_some_and(|section| section.characteristics & SectionFlags::IMAGE_SCN_LNK_COMDAT.bits() != 0);
+        if !is_comdat {
+            continue;
+        }
+
+        // Associative sections follow their parent and have no COMDAT symbol of their own
+        let selection = definition.selection;
+        let (comdat_symbol, associated_section) = if ComdatSelection::from_u8(selection) == Some(ComdatSelection::Associative) {
+            (None, Some(definition.get_number()))
+        } else {
+            let comdat_symbol = symbol_table[position+1..].iter()
+                .find(|candidate| candidate.section_number == symbol.section_number
+                    && matches!(candidate.get_storage_class(), Some(StorageClass::External) | Some(StorageClass::Static)));
+            (comdat_symbol, None)
+        };
+
+        comdats.push(Comdat {
+            section_number: symbol.section_number as u32,
+            section_symbol_index: symbol.index,
+            comdat_symbol_index: comdat_symbol.map(|comdat_symbol| comdat_symbol.index),
+            comdat_symbol_name: comdat_symbol.and_then(|comdat_symbol| comdat_symbol.get_name(string_table)),
+            selection,
+            associated_section,
+            length: definition.length,
+            checksum: definition.checksum,
+        });
+    }
+
+    comdats
+}
+
+/// Follow the associative chain of `section_number` up to the
+/// COMDAT section that decides whether it is kept.
+/// Returns `None` if the section is not a COMDAT or the chain is broken or cyclic.
+pub fn get_associative_root(comdats: &[Comdat], section_number: u32) -> Option<&Comdat> {
+    let mut comdat = comdats.iter().find(|comdat| comdat.section_number == section_number)?;
+
+    for _ in 0..comdats.len() {
+        match comdat.associated_section {
+            Some(parent) => {
+                comdat = comdats.iter().find(|comdat| comdat.section_number == parent)?;
+            }
+            None => {
+                return Some(comdat);
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the section numbers of every COMDAT section that is,
+/// directly or through a chain, associated with `section_number`.
+/// These sections are kept or discarded together with it.
+pub fn get_associated_sections(comdats: &[Comdat], section_number: u32) -> Vec<u32> {
+    let mut sections: Vec<u32> = Vec::new();
+
+    for comdat in comdats.iter() {
+        let mut parent = comdat.associated_section;
+
+        // Bound the walk so a cyclic chain cannot loop forever
+        for _ in 0..comdats.len() {
+            match parent {
+                Some(number) if number == section_number => {
+                    sections.push(comdat.section_number);
+                    break;
+                }
+                Some(number) => {
+                    parent = comdats.iter()
+                        .find(|comdat| comdat.section_number == number)
+                        .and_then(|comdat| comdat.associated_section);
+                }
+                None => break,
+            }
+        }
+    }
+
+    sections
+}
+
+/// A COMDAT section together with its defining symbols
+#[derive(Clone)]
+pub struct Comdat {
+    /// One-based index of the COMDAT section in the section table.
+    pub section_number: u32,
+    /// Symbol table index of the section symbol, which carries the section definition.
+    pub section_symbol_index: u32,
+    /// Symbol table index of the COMDAT symbol.
+    /// Always `None` for associative COMDATs.
+    pub comdat_symbol_index: Option<u32>,
+    /// Name of the COMDAT symbol, which identifies the COMDAT across object files.
+    pub comdat_symbol_name: Option<String>,
+    /// The raw COMDAT selection number.
+    pub selection: u8,
+    /// One-based index of the section this one is associated with,
+    /// for `ComdatSelection::Associative` only.
+    pub associated_section: Option<u32>,
+    /// The size of the section data.
+    pub length: u32,
+    /// The checksum of the section data.
+    pub checksum: u32
+}
+
+impl fmt::Display for Comdat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "COMDAT")?;
+        writeln!(f, "------")?;
+        writeln!(f, "Section Number:          {}", self.section_number)?;
+        writeln!(f, "Section Symbol Index:    {}", self.section_symbol_index)?;
+        match self.comdat_symbol_index {
+            Some(index) => writeln!(f, "COMDAT Symbol Index:     {}", index)?,
+            None => writeln!(f, "COMDAT Symbol Index:     None")?,
+        }
+        match &self.comdat_symbol_name {
+            Some(name) => writeln!(f, "COMDAT Symbol Name:      {:?}", name)?,
+            None => writeln!(f, "COMDAT Symbol Name:      None")?,
+        }
+        match self.get_selection() {
+            Some(selection) => writeln!(f, "Selection:               {:?}", selection)?,
+            None => writeln!(f, "Selection:               {}", self.selection)?,
+        }
+        if let Some(section) = self.associated_section {
+            writeln!(f, "Associated Section:      {}", section)?;
+        }
+        writeln!(f, "Length:                  {}", self.length)?;
+        writeln!(f, "Checksum:                {:#010x}", self.checksum)?;
+
+        Ok(())
+    }
+}
+
+impl Comdat {
+    /// Returns the selection as an enum
+    pub fn get_selection(&self) -> Option<ComdatSelection> {
+        ComdatSelection::from_u8(self.selection)
+    }
+
+    /// Returns true if a linker could keep a single copy of this COMDAT
+    /// and `other`, a COMDAT with the same symbol name from another object,
+    /// without reporting a duplicate definition.
+    /// Associative COMDATs are never folded on their own; check their root instead.
+    pub fn can_fold_with(&self, other: &Comdat) -> bool {
+        if self.comdat_symbol_name.is_none() || self.comdat_symbol_name != other.comdat_symbol_name {
+            return false;
+        }
+
+        match (self.get_selection(), other.get_selection()) {
+            (Some(ComdatSelection::Any), Some(ComdatSelection::Any)) => true,
+            (Some(ComdatSelection::Largest), Some(ComdatSelection::Largest)) => true,
+            (Some(ComdatSelection::SameSize), Some(ComdatSelection::SameSize)) => self.length == other.length,
+            (Some(ComdatSelection::ExactMatch), Some(ComdatSelection::ExactMatch)) => {
+                self.length == other.length && self.checksum == other.checksum
+            }
+            _ => false,
+        }
+    }
+}
+
+/// How the linker chooses between multiple definitions of a COMDAT
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum ComdatSelection {
+    /// If this symbol is already defined, the linker issues a "multiply defined symbol" error.
+    NoDuplicates = 1,
+    /// Any section that defines the same COMDAT symbol can be linked; the rest are removed.
+    Any = 2,
+    /// The linker chooses an arbitrary section among the definitions for this symbol.
+    /// If all definitions are not the same size, a "multiply defined symbol" error is issued.
+    SameSize = 3,
+    /// The linker chooses an arbitrary section among the definitions for this symbol.
+    /// If all definitions do not match exactly, a "multiply defined symbol" error is issued.
+    ExactMatch = 4,
+    /// The section is linked if a certain other COMDAT section is linked.
+    /// This other section is indicated by the `number` field of the section definition.
+    Associative = 5,
+    /// The linker chooses the largest definition from among all of the definitions for this symbol.
+    /// If multiple definitions have this size, the choice between them is arbitrary.
+    Largest = 6,
+}
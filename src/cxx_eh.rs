@@ -0,0 +1,752 @@
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable};
+use core::{fmt, str};
+use crate::{debug::parse_records, exception::{RuntimeFunction, UnwindInfo}, load_config::read_versioned, pe::{PortableExecutable, read_c_string, read_u32}, prelude::*, rtti::parse_type_descriptor, Error};
+
+/// Mask of the magic number in the first field of an FH3 `FuncInfo`;
+/// the remaining bits hold the BBT flags
+pub const FUNC_INFO_MAGIC_MASK: u32 = 0x1fffffff;
+/// `FuncInfo` magic number of Visual C++ 6 and earlier
+pub const EH_MAGIC_NUMBER1: u32 = 0x19930520;
+/// `FuncInfo` magic number adding the exception specification type list
+pub const EH_MAGIC_NUMBER2: u32 = 0x19930521;
+/// `FuncInfo` magic number adding the EH flags
+pub const EH_MAGIC_NUMBER3: u32 = 0x19930522;
+
+/// The MSVC C++ frame handler a function's unwind info names
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CxxFrameHandler {
+    /// `__CxxFrameHandler3` and friends, with an uncompressed `FuncInfo`.
+    FrameHandler3,
+    /// `__CxxFrameHandler4` and friends, with a compressed `FuncInfo4`.
+    FrameHandler4,
+}
+
+impl CxxFrameHandler {
+    /// Identify a frame handler from its name
+    pub fn from_name(name: &str) -> Option<CxxFrameHandler> {
+        match name {
+            "__CxxFrameHandler" | "__CxxFrameHandler2" | "__CxxFrameHandler3" | "__GSHandlerCheck_EH" => Some(CxxFrameHandler::FrameHandler3),
+            "__CxxFrameHandler4" | "__GSHandlerCheck_EH4" => Some(CxxFrameHandler::FrameHandler4),
+            _ => None,
+        }
+    }
+}
+
+/// Uncompressed x64 `FuncInfo`, used by `__CxxFrameHandler3`. Addresses are RVAs.
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct FuncInfo {
+    /// The magic number in the low 29 bits, and BBT flags in the high 3 bits.
+    pub magic_number: u32,
+    /// The number of entries in the unwind map.
+    pub max_state: i32,
+    /// The RVA of the unwind map.
+    pub unwind_map: u32,
+    /// The number of entries in the try block map.
+    pub try_block_count: u32,
+    /// The RVA of the try block map.
+    pub try_block_map: u32,
+    /// The number of entries in the IP-to-state map.
+    pub ip_map_count: u32,
+    /// The RVA of the IP-to-state map.
+    pub ip_to_state_map: u32,
+    /// The frame offset of the unwind help slot.
+    pub unwind_help: i32,
+    /// The RVA of the exception specification type list (`EH_MAGIC_NUMBER2`).
+    pub es_type_list: u32,
+    /// EH flags (`EH_MAGIC_NUMBER3`).
+    pub eh_flags: u32,
+}
+
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+struct UnwindMapEntry {
+    to_state: i32,
+    action: u32,
+}
+
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+struct TryBlockMapEntry {
+    try_low: i32,
+    try_high: i32,
+    catch_high: i32,
+    catches: i32,
+    handler_array: u32,
+}
+
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+struct HandlerType {
+    adjectives: u32,
+    type_descriptor: u32,
+    catch_object: i32,
+    handler: u32,
+    frame: u32,
+}
+
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+struct IpToStateMapEntry {
+    ip: u32,
+    state: i32,
+}
+
+bitflags! {
+    /// Header flags of a compressed FH4 `FuncInfo4`
+    pub struct FuncInfo4Flags: u8 {
+        /// The function is a catch funclet.
+        const IS_CATCH = 0x01;
+        /// The function has separated code segments, each with its own IP-to-state map.
+        const IS_SEPARATED = 0x02;
+        /// BBT flags are present.
+        const BBT = 0x04;
+        /// An unwind map is present.
+        const UNWIND_MAP = 0x08;
+        /// A try block map is present.
+        const TRY_BLOCK_MAP = 0x10;
+        /// The function was compiled with `/EHs`.
+        const EHS = 0x20;
+        /// The function is `noexcept`.
+        const NO_EXCEPT = 0x40;
+    }
+}
+
+// Allow FuncInfo4 flags to be easily printed
+impl fmt::Debug for FuncInfo4Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for FuncInfo4Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for FuncInfo4Flags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+bitflags! {
+    /// Adjectives of a catch handler's caught type
+    pub struct HandlerAdjectives: u32 {
+        /// The type is `const`.
+        const HT_IS_CONST = 0x00000001;
+        /// The type is `volatile`.
+        const HT_IS_VOLATILE = 0x00000002;
+        /// The type is `__unaligned`.
+        const HT_IS_UNALIGNED = 0x00000004;
+        /// The type is caught by reference.
+        const HT_IS_REFERENCE = 0x00000008;
+        /// The handler may resume execution.
+        const HT_IS_RESUMABLE = 0x00000010;
+        /// The handler is `catch (...)` compiled with `/EHs`.
+        const HT_IS_STD_DOT_DOT = 0x00000040;
+        /// The handler catches `std::bad_alloc` for compatibility.
+        const HT_IS_BAD_ALLOC_COMPAT = 0x00000080;
+        /// The handler is for a managed (C++/CLI) exception.
+        const HT_IS_COMPLUS_EH = 0x80000000;
+    }
+}
+
+// Allow Handler adjectives to be easily printed
+impl fmt::Debug for HandlerAdjectives {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for HandlerAdjectives {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for HandlerAdjectives {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// What an unwind map entry does when its state is unwound
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CxxUnwindType {
+    /// Nothing.
+    NoUnwind,
+    /// Call a destructor on an object in the frame (FH4).
+    DestructorWithObject,
+    /// Call a destructor on an object a frame slot points to (FH4).
+    DestructorWithPointerToObject,
+    /// Call an unwind funclet.
+    Funclet,
+}
+
+/// A decoded unwind map entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CxxUnwindMapEntry {
+    /// The state to continue unwinding at, or -1.
+    pub to_state: i32,
+    /// What unwinding this state does.
+    pub unwind_type: CxxUnwindType,
+    /// The RVA of the funclet or destructor, or 0.
+    pub action: u32,
+    /// The frame offset of the object to destroy (FH4 destructor entries).
+    pub object: u32
+}
+
+/// A decoded catch handler
+#[derive(Clone, Debug, PartialEq)]
+pub struct CxxHandler {
+    /// Adjectives of the caught type; see `HandlerAdjectives`.
+    pub adjectives: u32,
+    /// The RVA of the caught type's `TypeDescriptor`, or 0 for `catch (...)`.
+    pub type_descriptor: u32,
+    /// The frame offset of the catch object, or 0 if it is unnamed.
+    pub catch_object: i32,
+    /// The RVA of the catch funclet.
+    pub handler: u32,
+    /// The establisher frame offset (FH3).
+    pub frame: u32,
+    /// The RVAs execution continues at after the catch funclet (FH4).
+    pub continuations: Vec<u32>
+}
+
+/// A decoded try block
+#[derive(Clone, Debug, PartialEq)]
+pub struct CxxTryBlock {
+    /// The lowest state of the try block.
+    pub try_low: i32,
+    /// The highest state of the try block.
+    pub try_high: i32,
+    /// The highest state of its catch blocks.
+    pub catch_high: i32,
+    /// The catch handlers, in the order they are tested.
+    pub handlers: Vec<CxxHandler>
+}
+
+/// A decoded IP-to-state map entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CxxIpToState {
+    /// The RVA from which the state applies.
+    pub ip: u32,
+    /// The state, or -1 outside of any state.
+    pub state: i32
+}
+
+/// C++ exception handling data of a function, decoded from an FH3 `FuncInfo`
+/// or a compressed FH4 `FuncInfo4`
+#[derive(Clone, Debug)]
+pub struct CxxFuncInfo {
+    /// The frame handler version the data was decoded for.
+    pub frame_handler: CxxFrameHandler,
+    /// The FH3 magic number, or 0 for FH4.
+    pub magic_number: u32,
+    /// FH4 header flags; see `FuncInfo4Flags`.
+    pub flags: u8,
+    /// BBT flags.
+    pub bbt_flags: u32,
+    /// The unwind map, indexed by state.
+    pub unwind_map: Vec<CxxUnwindMapEntry>,
+    /// The try block map, innermost try blocks first.
+    pub try_blocks: Vec<CxxTryBlock>,
+    /// The IP-to-state map, sorted by address.
+    pub ip_to_state_map: Vec<CxxIpToState>,
+    /// The frame offset of the unwind help slot (FH3).
+    pub unwind_help: i32,
+    /// The RVA of the exception specification type list (FH3).
+    pub es_type_list: u32,
+    /// EH flags (FH3).
+    pub eh_flags: u32,
+    /// The establisher frame offset of a catch funclet (FH4).
+    pub frame: u32
+}
+
+/// Find the name of an imported function from the RVA of its
+/// `jmp [rip+disp32]` thunk, or of the IAT entry itself, as MSVC
+/// emits for handlers such as `__CxxFrameHandler3`
+pub fn get_import_thunk_name(binary: &[u8], pe: &PortableExecutable, rva: u32) -> Option<String> {
+    let offset = pe.rva_to_offset(rva)?;
+    let iat_entry = match binary.get(offset..offset + 6)? {
+        [0xff, 0x25, displacement @ ..] => rva.checked_add(6)?.checked_add_signed(i32::from_le_bytes(displacement.try_into().ok()?))?,
+        _ => rva,
+    };
+
+    // On disk, an IAT entry holds the RVA of its hint/name entry unless it imports by ordinal
+    let iat = pe.get_data_directories()?.import_address_table;
+    if iat_entry < iat.virtual_address || iat_entry - iat.virtual_address >= iat.size {
+        return None;
+    }
+
+    let hint_name = read_u32(binary, pe.rva_to_offset(iat_entry)?).ok()?;
+    if hint_name & 0x80000000 != 0 {
+        return None;
+    }
+    read_c_string(binary, pe.rva_to_offset(hint_name.checked_add(2)?)?).ok()
+}
+
+/// Parse the C++ exception handling data of a function.
+/// The handler data starts with the RVA of the `FuncInfo`.
+///
+/// If `frame_handler` is `None`, the handler is identified by its import name.
+/// A statically linked handler (`/MT`) has no import name, so its data is
+/// only parsed if it starts with an FH3 magic number; callers that know the
+/// handler, for example from symbols, should pass it explicitly.
+pub fn parse_cxx_eh_data(binary: &[u8], pe: &PortableExecutable, function: &RuntimeFunction, unwind_info: &UnwindInfo, frame_handler: Option<CxxFrameHandler>) -> Result<Option<CxxFuncInfo>, Error> {
+    let (handler, handler_data) = match (unwind_info.exception_handler, unwind_info.handler_data) {
+        (Some(handler), Some(handler_data)) => (handler, handler_data),
+        _ => {
+            return Ok(None);
+        }
+    };
+
+    let func_info = match pe.rva_to_offset(handler_data) {
+        Some(offset) => read_u32(binary, offset)?,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let frame_handler = match frame_handler.or_else(|| get_import_thunk_name(binary, pe, handler).as_deref().and_then(CxxFrameHandler::from_name)) {
+        Some(frame_handler) => frame_handler,
+        None if has_func_info_magic(binary, pe, func_info) => CxxFrameHandler::FrameHandler3,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    match frame_handler {
+        CxxFrameHandler::FrameHandler3 => Ok(Some(parse_func_info(binary, pe, func_info)?)),
+        CxxFrameHandler::FrameHandler4 => Ok(Some(parse_func_info4(binary, pe, func_info, function.begin_address)?)),
+    }
+}
+
+/// Returns true if an FH3 `FuncInfo` magic number is found at an RVA
+fn has_func_info_magic(binary: &[u8], pe: &PortableExecutable, rva: u32) -> bool {
+    match pe.rva_to_offset(rva).map(|offset| read_u32(binary, offset)) {
+        Some(Ok(magic_number)) => matches!(magic_number & FUNC_INFO_MAGIC_MASK, EH_MAGIC_NUMBER1 | EH_MAGIC_NUMBER2 | EH_MAGIC_NUMBER3),
+        _ => false,
+    }
+}
+
+/// Parse an uncompressed x64 `FuncInfo` and the tables it points to
+pub fn parse_func_info(binary: &[u8], pe: &PortableExecutable, rva: u32) -> Result<CxxFuncInfo, Error> {
+    let data = get_data(binary, pe, rva)?;
+    let magic_number = read_u32(data, 0)? & FUNC_INFO_MAGIC_MASK;
+    let size = match magic_number {
+        EH_MAGIC_NUMBER1 => 32,
+        EH_MAGIC_NUMBER2 => 36,
+        EH_MAGIC_NUMBER3 => size_of::<FuncInfo>(),
+        _ => {
+            return Err(Error::BadFuncInfo);
+        }
+    };
+    let header = read_versioned::<FuncInfo>(data, size)?;
+
+    let mut func_info = CxxFuncInfo {
+        frame_handler: CxxFrameHandler::FrameHandler3,
+        magic_number,
+        flags: 0,
+        bbt_flags: header.magic_number >> 29,
+        unwind_map: Vec::new(),
+        try_blocks: Vec::new(),
+        ip_to_state_map: Vec::new(),
+        unwind_help: header.unwind_help,
+        es_type_list: header.es_type_list,
+        eh_flags: header.eh_flags,
+        frame: 0,
+    };
+
+    for entry in read_table::<UnwindMapEntry>(binary, pe, header.unwind_map, header.max_state.max(0) as u32)? {
+        func_info.unwind_map.push(CxxUnwindMapEntry {
+            to_state: entry.to_state,
+            unwind_type: if entry.action == 0 { CxxUnwindType::NoUnwind } else { CxxUnwindType::Funclet },
+            action: entry.action,
+            object: 0,
+        });
+    }
+
+    for entry in read_table::<TryBlockMapEntry>(binary, pe, header.try_block_map, header.try_block_count)? {
+        let handlers = read_table::<HandlerType>(binary, pe, entry.handler_array, entry.catches.max(0) as u32)?;
+        func_info.try_blocks.push(CxxTryBlock {
+            try_low: entry.try_low,
+            try_high: entry.try_high,
+            catch_high: entry.catch_high,
+            handlers: handlers.iter().map(|handler| CxxHandler {
+                adjectives: handler.adjectives,
+                type_descriptor: handler.type_descriptor,
+                catch_object: handler.catch_object,
+                handler: handler.handler,
+                frame: handler.frame,
+                continuations: Vec::new(),
+            }).collect(),
+        });
+    }
+
+    for entry in read_table::<IpToStateMapEntry>(binary, pe, header.ip_to_state_map, header.ip_map_count)? {
+        func_info.ip_to_state_map.push(CxxIpToState { ip: entry.ip, state: entry.state });
+    }
+
+    Ok(func_info)
+}
+
+/// Parse a compressed FH4 `FuncInfo4` and the tables it points to.
+/// IP-to-state offsets and some continuation addresses are relative to the
+/// start of the function, or of the function's segment for separated code.
+pub fn parse_func_info4(binary: &[u8], pe: &PortableExecutable, rva: u32, function_start: u32) -> Result<CxxFuncInfo, Error> {
+    let mut reader = CompressedReader::new(get_data(binary, pe, rva)?);
+    let flags = reader.read_u8()?;
+    let header = FuncInfo4Flags::from_bits_retain(flags);
+
+    let mut func_info = CxxFuncInfo {
+        frame_handler: CxxFrameHandler::FrameHandler4,
+        magic_number: 0,
+        flags,
+        bbt_flags: 0,
+        unwind_map: Vec::new(),
+        try_blocks: Vec::new(),
+        ip_to_state_map: Vec::new(),
+        unwind_help: 0,
+        es_type_list: 0,
+        eh_flags: 0,
+        frame: 0,
+    };
+
+    if header.contains(FuncInfo4Flags::BBT) {
+        func_info.bbt_flags = reader.read_unsigned()?;
+    }
+    let unwind_map = if header.contains(FuncInfo4Flags::UNWIND_MAP) { reader.read_int()? } else { 0 };
+    let try_block_map = if header.contains(FuncInfo4Flags::TRY_BLOCK_MAP) { reader.read_int()? } else { 0 };
+
+    let mut ip_to_state_map = 0;
+    if header.contains(FuncInfo4Flags::IS_SEPARATED) {
+        // Each segment of a separated function has its own map
+        let mut segments = CompressedReader::new(get_data(binary, pe, reader.read_int()?)?);
+        for _ in 0..segments.read_unsigned()? {
+            let segment = segments.read_int()?;
+            let map = segments.read_int()?;
+            if segment == function_start {
+                ip_to_state_map = map;
+                break;
+            }
+        }
+    } else {
+        ip_to_state_map = reader.read_int()?;
+    }
+
+    if header.contains(FuncInfo4Flags::IS_CATCH) {
+        func_info.frame = reader.read_unsigned()?;
+    }
+
+    if unwind_map != 0 {
+        func_info.unwind_map = parse_unwind_map4(get_data(binary, pe, unwind_map)?)?;
+    }
+
+    if try_block_map != 0 {
+        let mut reader = CompressedReader::new(get_data(binary, pe, try_block_map)?);
+        for _ in 0..reader.read_unsigned()? {
+            let try_low = reader.read_unsigned()? as i32;
+            let try_high = reader.read_unsigned()? as i32;
+            let catch_high = reader.read_unsigned()? as i32;
+            let handler_array = reader.read_int()?;
+            func_info.try_blocks.push(CxxTryBlock {
+                try_low,
+                try_high,
+                catch_high,
+                handlers: parse_handler_map4(get_data(binary, pe, handler_array)?, function_start)?,
+            });
+        }
+    }
+
+    if ip_to_state_map != 0 {
+        let mut reader = CompressedReader::new(get_data(binary, pe, ip_to_state_map)?);
+        let mut ip = function_start;
+        for _ in 0..reader.read_unsigned()? {
+            ip = ip.wrapping_add(reader.read_unsigned()?);
+            // States are stored plus one, so that -1 encodes as 0
+            let state = reader.read_unsigned()? as i32 - 1;
+            func_info.ip_to_state_map.push(CxxIpToState { ip, state });
+        }
+    }
+
+    Ok(func_info)
+}
+
+fn parse_unwind_map4(data: &[u8]) -> Result<Vec<CxxUnwindMapEntry>, Error> {
+    let mut reader = CompressedReader::new(data);
+    let count = reader.read_unsigned()?;
+
+    // Entries link to the next state by a backwards byte offset from their start
+    let mut starts: Vec<usize> = Vec::new();
+    let mut next_offsets: Vec<usize> = Vec::new();
+    let mut entries: Vec<CxxUnwindMapEntry> = Vec::new();
+    for _ in 0..count {
+        starts.push(reader.position);
+        let next_offset_and_type = reader.read_unsigned()?;
+        let (unwind_type, action, object) = match next_offset_and_type & 3 {
+            1 => (CxxUnwindType::DestructorWithObject, reader.read_int()?, reader.read_unsigned()?),
+            2 => (CxxUnwindType::DestructorWithPointerToObject, reader.read_int()?, reader.read_unsigned()?),
+            3 => (CxxUnwindType::Funclet, reader.read_int()?, 0),
+            _ => (CxxUnwindType::NoUnwind, 0, 0),
+        };
+
+        next_offsets.push((next_offset_and_type >> 2) as usize);
+        entries.push(CxxUnwindMapEntry { to_state: -1, unwind_type, action, object });
+    }
+
+    for (index, entry) in entries.iter_mut().enumerate() {
+        if next_offsets[index] != 0 {
+            let target = starts[index].checked_sub(next_offsets[index]);
+            entry.to_state = match target.and_then(|target| starts.iter().position(|&start| start == target)) {
+                Some(state) => state as i32,
+                None => {
+                    return Err(Error::BadFuncInfo);
+                }
+            };
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_handler_map4(data: &[u8], function_start: u32) -> Result<Vec<CxxHandler>, Error> {
+    let mut reader = CompressedReader::new(data);
+    let mut handlers: Vec<CxxHandler> = Vec::new();
+
+    for _ in 0..reader.read_unsigned()? {
+        // Bit 0: adjectives, 1: type, 2: catch object, 3: RVA continuations, 4-5: continuation count
+        let header = reader.read_u8()?;
+        let mut handler = CxxHandler {
+            adjectives: if header & 0x01 != 0 { reader.read_unsigned()? } else { 0 },
+            type_descriptor: if header & 0x02 != 0 { reader.read_int()? } else { 0 },
+            catch_object: if header & 0x04 != 0 { reader.read_unsigned()? as i32 } else { 0 },
+            handler: reader.read_int()?,
+            frame: 0,
+            continuations: Vec::new(),
+        };
+
+        for _ in 0..(header >> 4) & 3 {
+            let continuation = if header & 0x08 != 0 {
+                reader.read_int()?
+            } else {
+                function_start.wrapping_add(reader.read_unsigned()?)
+            };
+            handler.continuations.push(continuation);
+        }
+
+        handlers.push(handler);
+    }
+
+    Ok(handlers)
+}
+
+/// Reads the compressed integers of FH4 metadata
+struct CompressedReader<'a> {
+    data: &'a [u8],
+    position: usize
+}
+
+impl<'a> CompressedReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        CompressedReader { data, position: 0 }
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        match self.data.get(self.position) {
+            Some(&value) => {
+                self.position += 1;
+                Ok(value)
+            }
+            None => Err(Error::BadFuncInfo),
+        }
+    }
+
+    fn read_int(&mut self) -> Result<u32, Error> {
+        let value = read_u32(self.data, self.position).map_err(|_| Error::BadFuncInfo)?;
+        self.position += 4;
+        Ok(value)
+    }
+
+    /// The low bits of the first byte give the length: `x0` is 1 byte,
+    /// `01` is 2, `011` is 3, `0111` is 4 and `1111` is 4 bytes following the first
+    fn read_unsigned(&mut self) -> Result<u32, Error> {
+        const LENGTHS: [usize; 16] = [1, 2, 1, 3, 1, 2, 1, 4, 1, 2, 1, 3, 1, 2, 1, 5];
+
+        let first = match self.data.get(self.position) {
+            Some(&first) => first,
+            None => {
+                return Err(Error::BadFuncInfo);
+            }
+        };
+
+        let length = LENGTHS[(first & 0xf) as usize];
+        let bytes = match self.data.get(self.position..self.position + length) {
+            Some(bytes) => bytes,
+            None => {
+                return Err(Error::BadFuncInfo);
+            }
+        };
+
+        let value = if length == 5 {
+            u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]])
+        } else {
+            (bytes.iter().rev().fold(0u32, |value, &byte| value << 8 | u32::from(byte))) >> length
+        };
+
+        self.position += length;
+        Ok(value)
+    }
+}
+
+fn get_data<'a>(binary: &'a [u8], pe: &PortableExecutable, rva: u32) -> Result<&'a [u8], Error> {
+    match pe.rva_to_offset(rva).and_then(|offset| binary.get(offset..)) {
+        Some(data) => Ok(data),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+fn read_table<T: Pod>(binary: &[u8], pe: &PortableExecutable, rva: u32, count: u32) -> Result<Vec<T>, Error> {
+    if rva == 0 || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let data = get_data(binary, pe, rva)?;
+    match (count as usize).checked_mul(size_of::<T>()).and_then(|size| data.get(..size)) {
+        Some(table) => parse_records(table),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Get the decorated name of a caught type, such as `.?AVexception@std@@`,
+/// from its x64 `TypeDescriptor`
+pub fn get_type_name(binary: &[u8], pe: &PortableExecutable, type_descriptor: u32) -> Result<String, Error> {
+    Ok(parse_type_descriptor(binary, pe, type_descriptor)?.name)
+}
+
+impl CxxFuncInfo {
+    /// Returns the FH4 header flags as bitflags
+    pub fn get_flags(&self) -> FuncInfo4Flags {
+        FuncInfo4Flags::from_bits_retain(self.flags)
+    }
+
+    /// Returns the state at an RVA, or -1 if it is outside of any state
+    pub fn get_state(&self, rva: u32) -> i32 {
+        let index = self.ip_to_state_map.partition_point(|entry| entry.ip <= rva);
+        match index.checked_sub(1) {
+            Some(index) => self.ip_to_state_map[index].state,
+            None => -1,
+        }
+    }
+
+    /// Find the catch handlers that cover an RVA, in the order they are
+    /// tested: innermost try block first, then each block's handlers in order
+    pub fn find_catch_handlers(&self, rva: u32) -> Vec<&CxxHandler> {
+        let state = self.get_state(rva);
+        self.try_blocks.iter()
+            .filter(|try_block| state >= try_block.try_low && state <= try_block.try_high)
+            .flat_map(|try_block| try_block.handlers.iter())
+            .collect()
+    }
+}
+
+impl CxxHandler {
+    /// Returns the adjectives as bitflags
+    pub fn get_adjectives(&self) -> HandlerAdjectives {
+        HandlerAdjectives::from_bits_retain(self.adjectives)
+    }
+}
+
+impl fmt::Display for CxxFuncInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "C++ Exception Handling")?;
+        writeln!(f, "----------------------")?;
+        writeln!(f, "Frame Handler:           {:?}", self.frame_handler)?;
+        match self.frame_handler {
+            CxxFrameHandler::FrameHandler3 => {
+                writeln!(f, "Magic Number:            {:#010x}", self.magic_number)?;
+                writeln!(f, "Unwind Help:             {:#x}", self.unwind_help)?;
+                writeln!(f, "ES Type List:            {:#010x}", self.es_type_list)?;
+                writeln!(f, "EH Flags:                {:#x}", self.eh_flags)?;
+            }
+            CxxFrameHandler::FrameHandler4 => {
+                writeln!(f, "Flags:                   {}", self.get_flags())?;
+                writeln!(f, "Frame:                   {:#x}", self.frame)?;
+            }
+        }
+        writeln!(f, "BBT Flags:               {:#x}", self.bbt_flags)?;
+
+        for (state, entry) in self.unwind_map.iter().enumerate() {
+            writeln!(f, "Unwind Map Entry:        {} -> {} {:?} {:#010x}", state, entry.to_state, entry.unwind_type, entry.action)?;
+        }
+        for try_block in self.try_blocks.iter() {
+            writeln!(f, "Try Block:               states {}-{}, catch {}", try_block.try_low, try_block.try_high, try_block.catch_high)?;
+            for handler in try_block.handlers.iter() {
+                writeln!(f, "Catch:                   type {:#010x} handler {:#010x} ({})", handler.type_descriptor, handler.handler, handler.get_adjectives())?;
+            }
+        }
+        for entry in self.ip_to_state_map.iter() {
+            writeln!(f, "IP to State:             {:#010x} -> {}", entry.ip, entry.state)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_unsigned(data: &[u8]) -> (u32, usize) {
+        let mut reader = CompressedReader::new(data);
+        let value = reader.read_unsigned().unwrap();
+        (value, reader.position)
+    }
+
+    #[test]
+    fn read_unsigned_lengths() {
+        assert_eq!(read_unsigned(&[0x7f << 1]), (0x7f, 1));
+        assert_eq!(read_unsigned(&(0x3fff_u16 << 2 | 0x1).to_le_bytes()), (0x3fff, 2));
+        assert_eq!(read_unsigned(&(0x1f_ffff_u32 << 3 | 0x3).to_le_bytes()[..3]), (0x1f_ffff, 3));
+        assert_eq!(read_unsigned(&(0x0fff_ffff_u32 << 4 | 0x7).to_le_bytes()), (0x0fff_ffff, 4));
+        assert_eq!(read_unsigned(&[0x0f, 0x78, 0x56, 0x34, 0x12]), (0x1234_5678, 5));
+    }
+
+    #[test]
+    fn read_unsigned_truncated() {
+        assert!(CompressedReader::new(&[0x0f, 0x78, 0x56]).read_unsigned().is_err());
+    }
+
+    #[test]
+    fn unwind_map4_next_offsets() {
+        let data = [
+            3 << 1,
+            // State 0 at offset 1: no unwind, no next state
+            0x00,
+            // State 1 at offset 2: funclet, next state 1 byte back
+            ((1 << 2) | 3) << 1, 0x00, 0x10, 0x00, 0x00,
+            // State 2 at offset 7: destructor with object, next state 5 bytes back
+            ((5 << 2) | 1) << 1, 0x00, 0x20, 0x00, 0x00, 0x10 << 1,
+        ];
+
+        assert_eq!(parse_unwind_map4(&data).unwrap(), [
+            CxxUnwindMapEntry { to_state: -1, unwind_type: CxxUnwindType::NoUnwind, action: 0, object: 0 },
+            CxxUnwindMapEntry { to_state: 0, unwind_type: CxxUnwindType::Funclet, action: 0x1000, object: 0 },
+            CxxUnwindMapEntry { to_state: 1, unwind_type: CxxUnwindType::DestructorWithObject, action: 0x2000, object: 0x10 },
+        ]);
+    }
+
+    #[test]
+    fn unwind_map4_next_offset_between_entries() {
+        // State 1 points 2 bytes back, at the entry count rather than an entry
+        let data = [2 << 1, 0x00, (2 << 2) << 1];
+        assert!(matches!(parse_unwind_map4(&data), Err(Error::BadFuncInfo)));
+    }
+}
@@ -0,0 +1,796 @@
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::{fmt, str};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use crate::{metadata::{MetadataRoot, PORTABLE_PDB_ID_SIZE}, pe::{PortableExecutable, read_c_string, read_u32}, prelude::*, Error};
+
+/// Signature of a PDB 7.0 CodeView record ("RSDS")
+pub const CV_SIGNATURE_RSDS: [u8; 4] = *b"RSDS";
+/// Signature of an embedded portable PDB ("MPDB")
+pub const EMBEDDED_PDB_SIGNATURE: [u8; 4] = *b"MPDB";
+
+/// Debug Directory entry
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct DebugDirectory {
+    /// Reserved, must be zero.
+    pub characteristics: u32,
+    /// The time and date that the debug data was created.
+    pub time_date_stamp: u32,
+    /// The major version number of the debug data format.
+    pub major_version: u16,
+    /// The minor version number of the debug data format.
+    pub minor_version: u16,
+    /// The format of debugging information.
+    pub debug_type: u32,
+    /// The size of the debug data (not including the debug directory itself).
+    pub size_of_data: u32,
+    /// The address of the debug data when loaded, relative to the image base.
+    pub address_of_raw_data: u32,
+    /// The file pointer to the debug data.
+    pub pointer_to_raw_data: u32
+}
+
+impl fmt::Display for DebugDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Debug Directory")?;
+        writeln!(f, "---------------")?;
+        match self.get_debug_type() {
+            Some(debug_type) => writeln!(f, "Type:                    {:?}", debug_type)?,
+            None => writeln!(f, "Type:                    {}", self.debug_type)?,
+        }
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
+        writeln!(f, "Version:                 {}.{}", self.major_version, self.minor_version)?;
+        writeln!(f, "Size of Data:            {}", self.size_of_data)?;
+        writeln!(f, "Address of Raw Data:     {:#010x}", self.address_of_raw_data)?;
+        writeln!(f, "Pointer to Raw Data:     {:#010x}", self.pointer_to_raw_data)?;
+
+        Ok(())
+    }
+}
+
+impl DebugDirectory {
+    /// Returns the debug type as an enum
+    pub fn get_debug_type(&self) -> Option<DebugType> {
+        DebugType::from_u32(self.debug_type)
+    }
+
+    /// Get the debug data this entry describes
+    pub fn get_data<'a>(&self, binary: &'a [u8]) -> Option<&'a [u8]> {
+        let start = self.pointer_to_raw_data as usize;
+        binary.get(start..start.checked_add(self.size_of_data as usize)?)
+    }
+}
+
+/// The format of debugging information
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum DebugType {
+    /// An unknown value that is ignored by all tools.
+    Unknown = 0,
+    /// The COFF debug information (line numbers, symbol table, and string table).
+    Coff = 1,
+    /// The Visual C++ debug information.
+    CodeView = 2,
+    /// The frame pointer omission (FPO) information.
+    Fpo = 3,
+    /// The location of DBG file.
+    Misc = 4,
+    /// A copy of .pdata section.
+    Exception = 5,
+    /// Reserved.
+    Fixup = 6,
+    /// The mapping from an RVA in image to an RVA in source image.
+    OmapToSrc = 7,
+    /// The mapping from an RVA in source image to an RVA in image.
+    OmapFromSrc = 8,
+    /// Reserved for Borland.
+    Borland = 9,
+    /// Reserved.
+    Reserved10 = 10,
+    /// Reserved.
+    Clsid = 11,
+    /// Visual C++ feature counts.
+    VcFeature = 12,
+    /// Profile guided optimization layout.
+    Pogo = 13,
+    /// Incremental link-time code generation.
+    Iltcg = 14,
+    /// Intel MPX.
+    Mpx = 15,
+    /// PE determinism or reproducibility.
+    Repro = 16,
+    /// Debugging information is embedded in the PE file at location specified by PointerToRawData.
+    EmbeddedPortablePdb = 17,
+    /// Stores crypto hash for the content of the symbol file used to build the PE/COFF file.
+    PdbChecksum = 19,
+    /// Extended DLL characteristics bits.
+    ExDllCharacteristics = 20,
+}
+
+/// Parse the debug directory of an image.
+/// Returns an empty list if the image has no debug directory.
+pub fn parse_debug_directories(binary: &[u8], pe: &PortableExecutable) -> Result<Vec<DebugDirectory>, Error> {
+    let data_directory = match pe.get_data_directories() {
+        Some(data_directories) => data_directories.debug,
+        None => {
+            return Ok(Vec::new());
+        }
+    };
+
+    if data_directory.virtual_address == 0 || data_directory.size == 0 {
+        return Ok(Vec::new());
+    }
+
+    let offset = match pe.rva_to_offset(data_directory.virtual_address) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let entry_size = size_of::<DebugDirectory>();
+    let mut directories: Vec<DebugDirectory> = Vec::new();
+    for i in 0..data_directory.size as usize / entry_size {
+        let start = offset + i * entry_size;
+        let slice = match binary.get(start..start+entry_size) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        match try_pod_read_unaligned::<DebugDirectory>(slice) {
+            Ok(directory) => directories.push(directory),
+            Err(_) => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    }
+
+    Ok(directories)
+}
+
+/// PDB 7.0 CodeView record, identifying the PDB that matches an image
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeViewPdb70 {
+    /// The PDB's GUID, as stored (the first three fields are little-endian).
+    pub guid: [u8; 16],
+    /// The PDB's age, incremented each time the PDB is updated.
+    pub age: u32,
+    /// The path of the PDB when the image was linked.
+    pub path: String
+}
+
+/// Parse the CodeView record a debug directory entry points to
+pub fn parse_codeview(binary: &[u8], directory: &DebugDirectory) -> Result<CodeViewPdb70, Error> {
+    let data = match directory.get_data(binary) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if data.get(0..4) != Some(&CV_SIGNATURE_RSDS[..]) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let guid = match data.get(4..20) {
+        Some(guid) => guid,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut record = CodeViewPdb70 {
+        guid: [0; 16],
+        age: read_u32(data, 20)?,
+        path: read_c_string(data, 24)?,
+    };
+    record.guid.copy_from_slice(guid);
+
+    Ok(record)
+}
+
+/// Find and parse the CodeView record of an image, if it has one
+pub fn get_codeview(binary: &[u8], pe: &PortableExecutable) -> Result<Option<CodeViewPdb70>, Error> {
+    for directory in parse_debug_directories(binary, pe)? {
+        if directory.get_debug_type() == Some(DebugType::CodeView) {
+            return Ok(Some(parse_codeview(binary, &directory)?));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Format a GUID stored in its in-memory layout as
+/// `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX`
+pub fn format_guid(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8], guid[9], guid[10], guid[11], guid[12], guid[13], guid[14], guid[15]
+    )
+}
+
+impl CodeViewPdb70 {
+    /// Returns the key symbol servers index the PDB under:
+    /// the GUID without dashes followed by the age in hexadecimal
+    pub fn get_symbol_server_key(&self) -> String {
+        format!("{}{:X}", format_guid(&self.guid).replace('-', ""), self.age)
+    }
+}
+
+impl fmt::Display for CodeViewPdb70 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CodeView PDB 7.0")?;
+        writeln!(f, "----------------")?;
+        writeln!(f, "GUID:                    {{{}}}", format_guid(&self.guid))?;
+        writeln!(f, "Age:                     {}", self.age)?;
+        writeln!(f, "Path:                    {}", self.path)?;
+
+        Ok(())
+    }
+}
+
+/// A portable PDB embedded in the image, decompressed
+#[derive(Clone)]
+pub struct EmbeddedPortablePdb {
+    /// The decompressed portable PDB.
+    pub data: Vec<u8>,
+    /// The metadata root at the start of the portable PDB.
+    pub metadata_root: MetadataRoot
+}
+
+/// Decompress the portable PDB an `EmbeddedPortablePdb` debug directory entry points to.
+/// The data is the "MPDB" signature, the uncompressed size and a raw deflate stream.
+#[cfg(feature = "miniz_oxide")]
+pub fn parse_embedded_portable_pdb(binary: &[u8], directory: &DebugDirectory) -> Result<EmbeddedPortablePdb, Error> {
+    use crate::metadata::parse_metadata_root;
+
+    let data = match directory.get_data(binary) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if data.get(0..4) != Some(&EMBEDDED_PDB_SIGNATURE[..]) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let size = read_u32(data, 4)? as usize;
+    let pdb = match miniz_oxide::inflate::decompress_to_vec_with_limit(&data[8..], size) {
+        Ok(pdb) if pdb.len() == size => pdb,
+        _ => {
+            return Err(Error::BadCompressedData);
+        }
+    };
+
+    Ok(EmbeddedPortablePdb {
+        metadata_root: parse_metadata_root(&pdb)?,
+        data: pdb,
+    })
+}
+
+impl EmbeddedPortablePdb {
+    /// Returns the portable PDB id from the `#Pdb` stream
+    pub fn get_id(&self) -> Option<[u8; PORTABLE_PDB_ID_SIZE]> {
+        self.metadata_root.get_portable_pdb_id(&self.data)
+    }
+}
+
+impl fmt::Display for EmbeddedPortablePdb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Embedded Portable PDB")?;
+        writeln!(f, "---------------------")?;
+        writeln!(f, "Size:                    {}", self.data.len())?;
+        if let Some(id) = self.get_id() {
+            let mut guid = [0u8; 16];
+            guid.copy_from_slice(&id[..16]);
+            writeln!(f, "GUID:                    {{{}}}", format_guid(&guid))?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.metadata_root)?;
+
+        Ok(())
+    }
+}
+
+/// A hash of the PDB file matching the image, from a `PdbChecksum` entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdbChecksum {
+    /// The hash algorithm name, such as `SHA256`.
+    pub algorithm: String,
+    /// The hash of the PDB file.
+    pub checksum: Vec<u8>
+}
+
+/// Parse the PDB checksum a `PdbChecksum` debug directory entry points to:
+/// a null-terminated algorithm name followed by the hash
+pub fn parse_pdb_checksum(binary: &[u8], directory: &DebugDirectory) -> Result<PdbChecksum, Error> {
+    let data = match directory.get_data(binary) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let algorithm = read_c_string(data, 0)?;
+    Ok(PdbChecksum {
+        checksum: data[algorithm.len() + 1..].to_vec(),
+        algorithm,
+    })
+}
+
+/// Find and parse every PDB checksum of an image
+pub fn get_pdb_checksums(binary: &[u8], pe: &PortableExecutable) -> Result<Vec<PdbChecksum>, Error> {
+    let mut checksums: Vec<PdbChecksum> = Vec::new();
+
+    for directory in parse_debug_directories(binary, pe)? {
+        if directory.get_debug_type() == Some(DebugType::PdbChecksum) {
+            checksums.push(parse_pdb_checksum(binary, &directory)?);
+        }
+    }
+
+    Ok(checksums)
+}
+
+impl PdbChecksum {
+    /// Hash a PDB file with this checksum's algorithm and compare.
+    /// Portable PDBs are hashed with their id zeroed, as the id itself
+    /// is derived from the hash. Returns `None` for unsupported algorithms.
+    #[cfg(feature = "sha2")]
+    pub fn verify(&self, pdb: &[u8]) -> Option<bool> {
+        use crate::metadata::{PDB_STREAM_NAME, is_metadata_root, parse_metadata_root};
+        use sha2::Digest;
+
+        let mut data = pdb.to_vec();
+        if is_metadata_root(pdb) && let Ok(root) = parse_metadata_root(pdb)
+            && let Some(stream) = root.get_stream_header(PDB_STREAM_NAME)
+            && let Some(id) = data.get_mut(stream.offset as usize..stream.offset as usize + PORTABLE_PDB_ID_SIZE) {
+            id.fill(0);
+        }
+
+        let hash = match self.algorithm.as_str() {
+            "SHA256" => sha2::Sha256::digest(&data).to_vec(),
+            "SHA384" => sha2::Sha384::digest(&data).to_vec(),
+            "SHA512" => sha2::Sha512::digest(&data).to_vec(),
+            _ => {
+                return None;
+            }
+        };
+
+        Some(hash == self.checksum)
+    }
+}
+
+impl fmt::Display for PdbChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "PDB Checksum")?;
+        writeln!(f, "------------")?;
+        writeln!(f, "Algorithm:               {}", self.algorithm)?;
+        write!(f, "Checksum:                ")?;
+        for byte in self.checksum.iter() {
+            write!(f, "{:02X}", byte)?;
+        }
+        writeln!(f)?;
+
+        Ok(())
+    }
+}
+
+/// Frame Pointer Omission data for a function (`FPO_DATA`)
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct FpoData {
+    /// The offset of the first byte of the function code.
+    pub offset_start: u32,
+    /// The number of bytes in the function.
+    pub procedure_size: u32,
+    /// The number of local variables, in dwords.
+    pub locals: u32,
+    /// The size of the parameters, in dwords.
+    pub parameters: u16,
+    /// The prolog size (bits 0-7), the number of saved registers (bits 8-10),
+    /// the SEH (bit 11) and EBP (bit 12) flags, and the frame type (bits 14-15).
+    pub attributes: u16
+}
+
+/// The kind of frame an FPO record describes
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum FrameType {
+    /// A frame without a frame pointer.
+    Fpo = 0,
+    /// A trap frame.
+    Trap = 1,
+    /// A task state segment frame.
+    Tss = 2,
+    /// A frame with a frame pointer.
+    NonFpo = 3,
+}
+
+impl FpoData {
+    /// Returns the number of bytes in the function prolog
+    pub fn get_prolog_size(&self) -> u8 {
+        (self.attributes & 0xff) as u8
+    }
+
+    /// Returns the number of registers the function saves
+    pub fn get_saved_registers(&self) -> u8 {
+        ((self.attributes >> 8) & 0x7) as u8
+    }
+
+    /// Returns true if the function uses structured exception handling
+    pub fn has_seh(&self) -> bool {
+        self.attributes & 0x0800 != 0
+    }
+
+    /// Returns true if EBP has been allocated
+    pub fn uses_bp(&self) -> bool {
+        self.attributes & 0x1000 != 0
+    }
+
+    /// Returns the frame type as an enum
+    pub fn get_frame_type(&self) -> Option<FrameType> {
+        FrameType::from_u16(self.attributes >> 14)
+    }
+
+    /// Returns true if the record covers the offset
+    pub fn contains(&self, offset: u32) -> bool {
+        offset >= self.offset_start && offset - self.offset_start < self.procedure_size
+    }
+}
+
+impl fmt::Display for FpoData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FPO Data")?;
+        writeln!(f, "--------")?;
+        writeln!(f, "Offset Start:            {:#010x}", self.offset_start)?;
+        writeln!(f, "Procedure Size:          {}", self.procedure_size)?;
+        writeln!(f, "Locals:                  {}", self.locals)?;
+        writeln!(f, "Parameters:              {}", self.parameters)?;
+        writeln!(f, "Prolog Size:             {}", self.get_prolog_size())?;
+        writeln!(f, "Saved Registers:         {}", self.get_saved_registers())?;
+        writeln!(f, "Has SEH:                 {}", self.has_seh())?;
+        writeln!(f, "Uses BP:                 {}", self.uses_bp())?;
+        match self.get_frame_type() {
+            Some(frame_type) => writeln!(f, "Frame Type:              {:?}", frame_type)?,
+            None => writeln!(f, "Frame Type:              {}", self.attributes >> 14)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// An OMAP entry, mapping an address range to another layout.
+/// Entries are sorted by `rva`; each covers up to the next entry.
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct OmapEntry {
+    /// The start of the range in the layout being translated from.
+    pub rva: u32,
+    /// The start of the range in the layout being translated to, or 0 if it was removed.
+    pub rva_to: u32
+}
+
+/// Parse a table of fixed-size records, ignoring any trailing partial record
+pub(crate) fn parse_records<T: Pod>(data: &[u8]) -> Result<Vec<T>, Error> {
+    let mut records: Vec<T> = Vec::new();
+
+    for record in data.chunks_exact(size_of::<T>()) {
+        match try_pod_read_unaligned::<T>(record) {
+            Ok(record) => records.push(record),
+            Err(_) => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Parse the FPO records an `Fpo` debug directory entry points to
+pub fn parse_fpo_data(binary: &[u8], directory: &DebugDirectory) -> Result<Vec<FpoData>, Error> {
+    match directory.get_data(binary) {
+        Some(data) => parse_records(data),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Parse the OMAP an `OmapToSrc` or `OmapFromSrc` debug directory entry points to
+pub fn parse_omap(binary: &[u8], directory: &DebugDirectory) -> Result<Vec<OmapEntry>, Error> {
+    match directory.get_data(binary) {
+        Some(data) => parse_records(data),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Find and parse the first debug directory entry of a type with a table of records,
+/// such as `Fpo`, `OmapToSrc` or `OmapFromSrc`.
+/// Returns an empty list if the image has no such entry.
+pub fn get_debug_records<T: Pod>(binary: &[u8], pe: &PortableExecutable, debug_type: DebugType) -> Result<Vec<T>, Error> {
+    for directory in parse_debug_directories(binary, pe)? {
+        if directory.get_debug_type() == Some(debug_type) {
+            return match directory.get_data(binary) {
+                Some(data) => parse_records(data),
+                None => Err(Error::OffsetOutOfRange),
+            };
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Translate an address with an OMAP table.
+/// Use `OmapToSrc` to go from the rearranged image to the original layout
+/// the PDB describes, and `OmapFromSrc` for the reverse.
+/// Returns `None` if the address precedes the table or its code was removed.
+pub fn translate_omap(omap: &[OmapEntry], rva: u32) -> Option<u32> {
+    let index = omap.partition_point(|entry| entry.rva <= rva).checked_sub(1)?;
+    let entry = omap[index];
+    if entry.rva_to == 0 {
+        return None;
+    }
+
+    entry.rva_to.checked_add(rva - entry.rva)
+}
+
+/// Find the FPO record covering an offset
+pub fn find_fpo_data(fpo: &[FpoData], offset: u32) -> Option<&FpoData> {
+    let index = fpo.partition_point(|record| record.offset_start <= offset).checked_sub(1)?;
+    fpo.get(index).filter(|record| record.contains(offset))
+}
+
+/// Find the first debug directory entry of a type
+pub fn find_debug_directory(binary: &[u8], pe: &PortableExecutable, debug_type: DebugType) -> Result<Option<DebugDirectory>, Error> {
+    Ok(parse_debug_directories(binary, pe)?.into_iter().find(|directory| directory.get_debug_type() == Some(debug_type)))
+}
+
+/// Returns true if the image was linked with incremental link-time code generation.
+/// `Iltcg` entries carry no data; their presence is the information.
+pub fn is_iltcg(binary: &[u8], pe: &PortableExecutable) -> Result<bool, Error> {
+    Ok(find_debug_directory(binary, pe, DebugType::Iltcg)?.is_some())
+}
+
+/// Returns true if the image was built to be reproducible (`/Brepro`),
+/// in which case its timestamps are hashes rather than dates
+pub fn is_deterministic(binary: &[u8], pe: &PortableExecutable) -> Result<bool, Error> {
+    Ok(find_debug_directory(binary, pe, DebugType::Repro)?.is_some())
+}
+
+/// Counts of objects built with security features, from a `VcFeature` entry
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct VcFeature {
+    /// Objects built by compilers predating Visual C++ 11.0.
+    pub pre_vc11: u32,
+    /// Objects built with C or C++.
+    pub c_cpp: u32,
+    /// Objects built with buffer security checks (`/GS`).
+    pub gs: u32,
+    /// Objects built with additional security checks (`/sdl`).
+    pub sdl: u32,
+    /// Objects built with `/guardN`.
+    pub guard_n: u32
+}
+
+/// Parse the counts a `VcFeature` debug directory entry points to
+pub fn parse_vc_feature(binary: &[u8], directory: &DebugDirectory) -> Result<VcFeature, Error> {
+    let slice = match directory.get_data(binary).and_then(|data| data.get(0..size_of::<VcFeature>())) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    match try_pod_read_unaligned::<VcFeature>(slice) {
+        Ok(vc_feature) => Ok(vc_feature),
+        Err(_) => Err(Error::OffsetOutOfRange),
+    }
+}
+
+impl fmt::Display for VcFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "VC Feature")?;
+        writeln!(f, "----------")?;
+        writeln!(f, "Pre-VC++ 11.00:          {}", self.pre_vc11)?;
+        writeln!(f, "C/C++:                   {}", self.c_cpp)?;
+        writeln!(f, "/GS:                     {}", self.gs)?;
+        writeln!(f, "/sdl:                    {}", self.sdl)?;
+        writeln!(f, "guardN:                  {}", self.guard_n)?;
+
+        Ok(())
+    }
+}
+
+/// The kind of build a POGO entry describes
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum PogoSignature {
+    /// Link-time code generation without profile guided optimization.
+    Ltcg = 0x4c544347,
+    /// Profile guided optimization, instrumented build.
+    Pgi = 0x50474900,
+    /// Profile guided optimization, optimized build.
+    Pgo = 0x50474f00,
+    /// Profile guided optimization, update build.
+    Pgu = 0x50475500,
+}
+
+/// A named range of the image, usually a section or a grouped subsection
+/// such as `.text$mn` or `.rdata$zzzdbg`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PogoEntry {
+    /// The start of the range.
+    pub rva: u32,
+    /// The size of the range.
+    pub size: u32,
+    /// The name of the range.
+    pub name: String
+}
+
+/// Image layout from a `Pogo` debug directory entry
+#[derive(Clone, Debug)]
+pub struct Pogo {
+    /// The kind of build; see `PogoSignature`.
+    pub signature: u32,
+    /// Named ranges, in image order.
+    pub entries: Vec<PogoEntry>
+}
+
+/// Parse the layout a `Pogo` debug directory entry points to:
+/// a signature followed by 4-byte aligned RVA, size and name entries
+pub fn parse_pogo(binary: &[u8], directory: &DebugDirectory) -> Result<Pogo, Error> {
+    let data = match directory.get_data(binary) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut pogo = Pogo {
+        signature: read_u32(data, 0)?,
+        entries: Vec::new(),
+    };
+
+    let mut offset = 4;
+    while offset + 8 < data.len() {
+        let name = read_c_string(data, offset + 8)?;
+        pogo.entries.push(PogoEntry {
+            rva: read_u32(data, offset)?,
+            size: read_u32(data, offset + 4)?,
+            name: name.clone(),
+        });
+        offset += 8 + ((name.len() + 4) & !3);
+    }
+
+    Ok(pogo)
+}
+
+impl Pogo {
+    /// Returns the signature as an enum
+    pub fn get_signature(&self) -> Option<PogoSignature> {
+        PogoSignature::from_u32(self.signature)
+    }
+
+    /// Find the entry covering an RVA
+    pub fn find_entry(&self, rva: u32) -> Option<&PogoEntry> {
+        self.entries.iter().find(|entry| rva >= entry.rva && rva - entry.rva < entry.size)
+    }
+}
+
+impl fmt::Display for Pogo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "POGO")?;
+        writeln!(f, "----")?;
+        match self.get_signature() {
+            Some(signature) => writeln!(f, "Signature:               {:?}", signature)?,
+            None => writeln!(f, "Signature:               {:#010x}", self.signature)?,
+        }
+        for entry in self.entries.iter() {
+            writeln!(f, "{:#010x} {:#010x} {}", entry.rva, entry.size, entry.name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A deterministic build hash from a `Repro` debug directory entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct Repro {
+    /// The hash the image's timestamps were derived from.
+    /// Empty if the entry carries no data, as with older linkers.
+    pub hash: Vec<u8>
+}
+
+/// Parse the hash a `Repro` debug directory entry points to:
+/// its length followed by the hash
+pub fn parse_repro(binary: &[u8], directory: &DebugDirectory) -> Result<Repro, Error> {
+    if directory.size_of_data == 0 {
+        return Ok(Repro { hash: Vec::new() });
+    }
+
+    let data = match directory.get_data(binary) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let length = read_u32(data, 0)? as usize;
+    match length.checked_add(4).and_then(|end| data.get(4..end)) {
+        Some(hash) => Ok(Repro { hash: hash.to_vec() }),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+impl fmt::Display for Repro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Repro")?;
+        writeln!(f, "-----")?;
+        write!(f, "Hash:                    ")?;
+        for byte in self.hash.iter() {
+            write!(f, "{:02X}", byte)?;
+        }
+        writeln!(f)?;
+
+        Ok(())
+    }
+}
+
+bitflags! {
+    /// Extended DLL characteristics, from an `ExDllCharacteristics` entry
+    pub struct ExDllCharacteristics: u32 {
+        /// Image is compatible with CET shadow stacks.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT = 0x0001;
+        /// CET shadow stacks are enforced in strict mode.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT_STRICT_MODE = 0x0002;
+        /// Relaxed mode for context IP validation under CET is allowed.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_SET_CONTEXT_IP_VALIDATION_RELAXED_MODE = 0x0004;
+        /// Use of CET-related dynamic APIs is allowed in-process only.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_DYNAMIC_APIS_ALLOW_IN_PROC = 0x0008;
+        /// Reserved for CET.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_RESERVED_1 = 0x0010;
+        /// Reserved for CET.
+        const IMAGE_DLLCHARACTERISTICS_EX_CET_RESERVED_2 = 0x0020;
+        /// Image is compatible with forward-edge control flow integrity (XFG).
+        const IMAGE_DLLCHARACTERISTICS_EX_FORWARD_CFI_COMPAT = 0x0040;
+        /// Image is compatible with hotpatching.
+        const IMAGE_DLLCHARACTERISTICS_EX_HOTPATCH_COMPATIBLE = 0x0080;
+    }
+}
+
+// Allow extended DLL Characteristics flags to be easily printed
+impl fmt::Debug for ExDllCharacteristics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for ExDllCharacteristics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for ExDllCharacteristics {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// Parse the flags an `ExDllCharacteristics` debug directory entry points to.
+/// Unknown bits are retained.
+pub fn parse_ex_dll_characteristics(binary: &[u8], directory: &DebugDirectory) -> Result<ExDllCharacteristics, Error> {
+    match directory.get_data(binary) {
+        Some(data) => Ok(ExDllCharacteristics::from_bits_retain(read_u32(data, 0)?)),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
@@ -0,0 +1,126 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::fmt;
+use crate::{prelude::*, Error};
+
+/// MS-DOS executable signature ("MZ")
+pub const IMAGE_DOS_SIGNATURE: u16 = 0x5a4d;
+/// Size of the MS-DOS header, where the DOS stub begins
+pub const IMAGE_DOS_HEADER_SIZE: usize = 0x40;
+/// Size of a page of an MS-DOS executable, in bytes
+pub const DOS_PAGE_SIZE: usize = 512;
+/// Size of a paragraph of an MS-DOS executable, in bytes
+pub const DOS_PARAGRAPH_SIZE: usize = 16;
+
+/// MS-DOS header (`IMAGE_DOS_HEADER`) at the start of every image
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct DosHeader {
+    /// Must be `IMAGE_DOS_SIGNATURE` ("MZ").
+    pub e_magic: u16,
+    /// The number of bytes used in the last page of the DOS executable, or zero if it is full.
+    pub e_cblp: u16,
+    /// The number of pages in the DOS executable, including the last partial page.
+    pub e_cp: u16,
+    /// The number of entries in the DOS relocation table.
+    pub e_crlc: u16,
+    /// The size of the header in paragraphs, including the relocation table.
+    pub e_cparhdr: u16,
+    /// The minimum number of extra paragraphs the DOS program needs.
+    pub e_minalloc: u16,
+    /// The maximum number of extra paragraphs the DOS program requests.
+    pub e_maxalloc: u16,
+    /// The initial (relative) SS value.
+    pub e_ss: u16,
+    /// The initial SP value.
+    pub e_sp: u16,
+    /// The checksum of the DOS executable, usually zero.
+    pub e_csum: u16,
+    /// The initial IP value.
+    pub e_ip: u16,
+    /// The initial (relative) CS value.
+    pub e_cs: u16,
+    /// The file offset of the DOS relocation table.
+    pub e_lfarlc: u16,
+    /// The overlay number, zero for the main program.
+    pub e_ovno: u16,
+    /// Reserved.
+    pub e_res: [u16; 4],
+    /// The OEM identifier for `e_oeminfo`.
+    pub e_oemid: u16,
+    /// OEM specific information.
+    pub e_oeminfo: u16,
+    /// Reserved.
+    pub e_res2: [u16; 10],
+    /// The file offset of the new executable header ("PE\0\0").
+    pub e_lfanew: u32,
+}
+
+/// Parse the MS-DOS header at the start of an image
+pub fn parse_dos_header(binary: &[u8]) -> Result<DosHeader, Error> {
+    let slice = match binary.get(0..IMAGE_DOS_HEADER_SIZE) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let header = match try_pod_read_unaligned::<DosHeader>(slice) {
+        Ok(header) => header,
+        Err(_) => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if header.e_magic != IMAGE_DOS_SIGNATURE {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    Ok(header)
+}
+
+impl DosHeader {
+    /// Get the DOS stub between the MS-DOS header and the new executable header,
+    /// which holds the DOS program and the Rich header
+    pub fn get_stub<'a>(&self, binary: &'a [u8]) -> Option<&'a [u8]> {
+        binary.get(IMAGE_DOS_HEADER_SIZE..usize::try_from(self.e_lfanew).ok()?)
+    }
+
+    /// Returns the size of the DOS executable, from its page counts
+    pub fn get_dos_image_size(&self) -> usize {
+        match self.e_cblp {
+            0 => usize::from(self.e_cp) * DOS_PAGE_SIZE,
+            last => usize::from(self.e_cp).saturating_sub(1) * DOS_PAGE_SIZE + usize::from(last),
+        }
+    }
+
+    /// Returns the size of the DOS header including its relocation table, in bytes
+    pub fn get_header_size(&self) -> usize {
+        usize::from(self.e_cparhdr) * DOS_PARAGRAPH_SIZE
+    }
+}
+
+impl fmt::Display for DosHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DOS Header")?;
+        writeln!(f, "----------")?;
+        writeln!(f, "Magic:                   {:#06x}", self.e_magic)?;
+        writeln!(f, "Bytes on Last Page:      {}", self.e_cblp)?;
+        writeln!(f, "Pages in File:           {}", self.e_cp)?;
+        writeln!(f, "Relocations:             {}", self.e_crlc)?;
+        writeln!(f, "Header Paragraphs:       {}", self.e_cparhdr)?;
+        writeln!(f, "Min Extra Paragraphs:    {}", self.e_minalloc)?;
+        writeln!(f, "Max Extra Paragraphs:    {:#06x}", self.e_maxalloc)?;
+        writeln!(f, "Initial SS:SP:           {:04x}:{:04x}", self.e_ss, self.e_sp)?;
+        writeln!(f, "Checksum:                {:#06x}", self.e_csum)?;
+        writeln!(f, "Initial CS:IP:           {:04x}:{:04x}", self.e_cs, self.e_ip)?;
+        writeln!(f, "Relocation Table:        {:#06x}", self.e_lfarlc)?;
+        writeln!(f, "Overlay Number:          {}", self.e_ovno)?;
+        writeln!(f, "Reserved:                {:04x?}", self.e_res)?;
+        writeln!(f, "OEM ID:                  {:#06x}", self.e_oemid)?;
+        writeln!(f, "OEM Info:                {:#06x}", self.e_oeminfo)?;
+        writeln!(f, "Reserved 2:              {:04x?}", self.e_res2)?;
+        writeln!(f, "New Header Offset:       {:#010x}", self.e_lfanew)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use core::fmt;
+use crate::{module_definition::ModuleExport, prelude::*, section::{SectionFlags, SectionHeader}, Error};
+
+/// Name of the linker directive section
+pub const DRECTVE_SECTION_NAME: [u8; 8] = *b".drectve";
+
+/// UTF-8 byte order mark, which may precede the directives
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+
+/// A linker option embedded in an object file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Directive {
+    /// `/DEFAULTLIB:library` adds a library to search when resolving references.
+    DefaultLib(String),
+    /// `/NODEFAULTLIB[:library]` ignores one, or every, default library.
+    NoDefaultLib(Option<String>),
+    /// `/EXPORT:entryname[=internalname][,@ordinal[,NONAME]][,DATA][,PRIVATE]` exports a function or data.
+    Export(ModuleExport),
+    /// `/INCLUDE:symbol` forces a symbol reference.
+    Include(String),
+    /// `/MERGE:from=to` combines the `from` section into the `to` section.
+    Merge {
+        /// The section being merged.
+        from: String,
+        /// The section it is merged into.
+        to: String
+    },
+    /// `/ALTERNATENAME:from=to` resolves `from` to `to` if `from` is not otherwise defined.
+    AlternateName {
+        /// The symbol being given an alternate name.
+        from: String,
+        /// The symbol used in its place.
+        to: String
+    },
+    /// `/FAILIFMISMATCH:key=value` fails the link if another object uses a different value for the key.
+    FailIfMismatch {
+        /// The name of the setting, such as `_MSC_VER` or `RuntimeLibrary`.
+        key: String,
+        /// The value this object was built with.
+        value: String
+    },
+    /// `/MANIFESTDEPENDENCY:dependency` adds a dependency to the manifest.
+    ManifestDependency(String),
+    /// Any other option, such as `/GUARDSYM` or `/EDITANDCONTINUE`, without the leading slash.
+    Other(String),
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Directive::DefaultLib(library) => write!(f, "/DEFAULTLIB:{}", Quoted(library)),
+            Directive::NoDefaultLib(Some(library)) => write!(f, "/NODEFAULTLIB:{}", Quoted(library)),
+            Directive::NoDefaultLib(None) => write!(f, "/NODEFAULTLIB"),
+            Directive::Export(export) => {
+                write!(f, "/EXPORT:{}", export.name)?;
+                if let Some(internal_name) = &export.internal_name {
+                    write!(f, "={}", internal_name)?;
+                }
+                if let Some(ordinal) = export.ordinal {
+                    write!(f, ",@{}", ordinal)?;
+                    if export.noname {
+                        write!(f, ",NONAME")?;
+                    }
+                }
+                if export.data {
+                    write!(f, ",DATA")?;
+                }
+                if export.private {
+                    write!(f, ",PRIVATE")?;
+                }
+                Ok(())
+            }
+            Directive::Include(symbol) => write!(f, "/INCLUDE:{}", symbol),
+            Directive::Merge { from, to } => write!(f, "/MERGE:{}={}", from, to),
+            Directive::AlternateName { from, to } => write!(f, "/ALTERNATENAME:{}={}", from, to),
+            Directive::FailIfMismatch { key, value } => write!(f, "/FAILIFMISMATCH:{}={}", key, value),
+            Directive::ManifestDependency(dependency) => write!(f, "/MANIFESTDEPENDENCY:{}", Quoted(dependency)),
+            Directive::Other(option) => write!(f, "/{}", option),
+        }
+    }
+}
+
+/// Quotes a value if it contains whitespace
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.chars().any(char::is_whitespace) {
+            write!(f, "\"{}\"", self.0)
+        } else {
+            f.write_str(self.0)
+        }
+    }
+}
+
+/// Returns true if the section holds linker directives
+pub fn is_directive_section(section: &SectionHeader) -> bool {
+    section.name == DRECTVE_SECTION_NAME
+        && SectionFlags::from_bits_retain(section.characteristics).contains(SectionFlags::IMAGE_SCN_LNK_INFO)
+}
+
+/// Parse the linker directives stored in a `.drectve` section
+pub fn parse_directive_section(binary: &[u8], section: &SectionHeader) -> Result<Vec<Directive>, Error> {
+    let start = section.pointer_to_raw_data as usize;
+    let data = match start.checked_add(section.size_of_raw_data as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    // Directives are ANSI unless they start with a byte order mark
+    let data = data.strip_prefix(&UTF8_BOM[..]).unwrap_or(data);
+    let text = String::from_utf8_lossy(data);
+
+    Ok(parse_directives(text.trim_end_matches('\0')))
+}
+
+/// Parse a string of linker directives.
+/// Arguments that are not options (do not start with `/` or `-`) are skipped.
+pub fn parse_directives(text: &str) -> Vec<Directive> {
+    let mut directives: Vec<Directive> = Vec::new();
+
+    for argument in tokenize_command_line(text) {
+        let Some(option) = argument.strip_prefix(['/', '-']) else {
+            continue;
+        };
+
+        let (name, value) = match option.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (option, None),
+        };
+
+        let directive = match (name.to_ascii_uppercase().as_str(), value) {
+            ("DEFAULTLIB", Some(library)) => Directive::DefaultLib(String::from(library)),
+            ("NODEFAULTLIB", library) => Directive::NoDefaultLib(library.map(String::from)),
+            ("EXPORT", Some(export)) => match parse_export(export) {
+                Some(export) => Directive::Export(export),
+                None => Directive::Other(String::from(option)),
+            },
+            ("INCLUDE", Some(symbol)) => Directive::Include(String::from(symbol)),
+            ("MERGE", Some(value)) => match value.split_once('=') {
+                Some((from, to)) => Directive::Merge { from: String::from(from), to: String::from(to) },
+                None => Directive::Other(String::from(option)),
+            },
+            ("ALTERNATENAME", Some(value)) => match value.split_once('=') {
+                Some((from, to)) => Directive::AlternateName { from: String::from(from), to: String::from(to) },
+                None => Directive::Other(String::from(option)),
+            },
+            ("FAILIFMISMATCH", Some(value)) => match value.split_once('=') {
+                Some((key, value)) => Directive::FailIfMismatch { key: String::from(key), value: String::from(value) },
+                None => Directive::Other(String::from(option)),
+            },
+            ("MANIFESTDEPENDENCY", Some(dependency)) => Directive::ManifestDependency(String::from(dependency)),
+            _ => Directive::Other(String::from(option)),
+        };
+
+        directives.push(directive);
+    }
+
+    directives
+}
+
+/// Parse the value of an `/EXPORT` directive
+fn parse_export(value: &str) -> Option<ModuleExport> {
+    let mut parts = value.split(',');
+    let first = parts.next()?;
+    let (name, internal_name) = match first.split_once('=') {
+        Some((name, internal_name)) => (name, Some(String::from(internal_name))),
+        None => (first, None),
+    };
+
+    let mut export = ModuleExport {
+        name: String::from(name),
+        internal_name,
+        ..Default::default()
+    };
+
+    for part in parts {
+        match part.to_ascii_uppercase().as_str() {
+            "NONAME" => export.noname = true,
+            "DATA" => export.data = true,
+            "PRIVATE" => export.private = true,
+            ordinal => export.ordinal = Some(ordinal.strip_prefix('@')?.parse().ok()?),
+        }
+    }
+
+    Some(export)
+}
+
+/// Split a command line into arguments using the MSVC quoting rules:
+/// whitespace separates arguments unless inside double quotes,
+/// `2n` backslashes before a quote become `n` backslashes and the quote toggles quoting,
+/// `2n + 1` backslashes before a quote become `n` backslashes and a literal quote,
+/// and backslashes not followed by a quote are literal.
+pub fn tokenize_command_line(text: &str) -> Vec<String> {
+    let mut arguments: Vec<String> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut argument = String::new();
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() && !quoted {
+                break;
+            }
+            chars.next();
+
+            match c {
+                '\\' => {
+                    let mut backslashes = 1;
+                    while chars.next_if_eq(&'\\').is_some() {
+                        backslashes += 1;
+                    }
+
+                    if chars.next_if_eq(&'"').is_some() {
+                        argument.extend(core::iter::repeat_n('\\', backslashes / 2));
+                        if backslashes % 2 == 1 {
+                            argument.push('"');
+                        } else {
+                            quoted = !quoted;
+                        }
+                    } else {
+                        argument.extend(core::iter::repeat_n('\\', backslashes));
+                    }
+                }
+                '"' => {
+                    // A doubled quote inside quotes is a literal quote
+                    if quoted && chars.next_if_eq(&'"').is_some() {
+                        argument.push('"');
+                    } else {
+                        quoted = !quoted;
+                    }
+                }
+                c => argument.push(c),
+            }
+        }
+
+        arguments.push(argument);
+    }
+
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_quoted_paths() {
+        assert_eq!(tokenize_command_line(r#"  "C:\Program Files\a.lib"  /X  "#), [r"C:\Program Files\a.lib", "/X"]);
+        assert_eq!(tokenize_command_line(r#"/DEFAULTLIB:"a b.lib" /INCLUDE:c"#), ["/DEFAULTLIB:a b.lib", "/INCLUDE:c"]);
+        assert_eq!(tokenize_command_line(r#"a\\\"b "c\\" d\e"#), [r#"a\"b"#, r"c\", r"d\e"]);
+        assert_eq!(tokenize_command_line(r#""say ""hi""""#), [r#"say "hi""#]);
+        assert!(tokenize_command_line(" \t\r\n").is_empty());
+    }
+
+    #[test]
+    fn parse_known_directives() {
+        let directives = parse_directives(r#"/DEFAULTLIB:"a b.lib" -defaultlib:OLDNAMES /NODEFAULTLIB /ALTERNATENAME:x=y /FAILIFMISMATCH:k=v /MERGE:.a=.b /INCLUDE:sym not_an_option /GUARDSYM:foo,8"#);
+        assert_eq!(directives, [
+            Directive::DefaultLib(String::from("a b.lib")),
+            Directive::DefaultLib(String::from("OLDNAMES")),
+            Directive::NoDefaultLib(None),
+            Directive::AlternateName { from: String::from("x"), to: String::from("y") },
+            Directive::FailIfMismatch { key: String::from("k"), value: String::from("v") },
+            Directive::Merge { from: String::from(".a"), to: String::from(".b") },
+            Directive::Include(String::from("sym")),
+            Directive::Other(String::from("GUARDSYM:foo,8")),
+        ]);
+    }
+
+    #[test]
+    fn parse_exports() {
+        let directives = parse_directives("/EXPORT:foo=bar,@2,NONAME /EXPORT:baz,DATA,PRIVATE /EXPORT:bad,@x");
+        assert_eq!(directives, [
+            Directive::Export(ModuleExport {
+                name: String::from("foo"),
+                internal_name: Some(String::from("bar")),
+                ordinal: Some(2),
+                noname: true,
+                ..Default::default()
+            }),
+            Directive::Export(ModuleExport { name: String::from("baz"), data: true, private: true, ..Default::default() }),
+            Directive::Other(String::from("EXPORT:bad,@x")),
+        ]);
+    }
+}
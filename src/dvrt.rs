@@ -0,0 +1,555 @@
+use core::fmt;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use crate::{load_config::LoadConfigDirectory64, pe::{PortableExecutable, read_u16, read_u32}, prelude::*, Error};
+
+/// Dynamic relocation symbols, identifying the kind of fixups that follow
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u64)]
+pub enum DynamicRelocationSymbol {
+    /// Return flow guard prologue.
+    GuardRfPrologue = 1,
+    /// Return flow guard epilogue.
+    GuardRfEpilogue = 2,
+    /// Calls and jumps through the IAT, for import control transfer (retpoline).
+    GuardImportControlTransfer = 3,
+    /// Indirect calls and jumps, for indirect control transfer (retpoline).
+    GuardIndirControlTransfer = 4,
+    /// Switchable branches, for retpoline.
+    GuardSwitchtableBranch = 5,
+    /// Fixups applied when an ARM64X image is loaded as ARM64EC.
+    Arm64X = 6,
+    /// Function overrides.
+    FunctionOverride = 7,
+    /// ARM64 kernel import call transfer.
+    Arm64KernelImportCallTransfer = 8,
+}
+
+/// IAT index of an ARM64 kernel import call fixup without an IAT entry
+const ARM64_NO_IAT_INDEX: u16 = 0x7fff;
+
+/// Dynamic value relocation table, pointed to by the load configuration
+#[derive(Clone, Debug)]
+pub struct DynamicRelocationTable {
+    /// The table version, 1 or 2.
+    pub version: u32,
+    /// The size of the relocations following the header.
+    pub size: u32,
+    /// Dynamic relocations, one per symbol.
+    pub relocations: Vec<DynamicRelocation>
+}
+
+/// The fixups of one dynamic relocation symbol
+#[derive(Clone, Debug)]
+pub struct DynamicRelocation {
+    /// The symbol; see `DynamicRelocationSymbol`.
+    pub symbol: u64,
+    /// Symbol group (version 2 only).
+    pub symbol_group: u32,
+    /// Flags (version 2 only).
+    pub flags: u32,
+    /// Fixups, grouped by page.
+    /// Empty for symbols whose fixup records are not decoded.
+    pub blocks: Vec<DynamicRelocationBlock>,
+    /// The raw fixup records.
+    pub data: Vec<u8>
+}
+
+/// The fixups of one 4 KiB page
+#[derive(Clone, Debug)]
+pub struct DynamicRelocationBlock {
+    /// The RVA of the page.
+    pub virtual_address: u32,
+    /// Fixups within the page.
+    pub fixups: Vec<DynamicFixup>
+}
+
+/// A decoded dynamic relocation fixup.
+/// Offsets are relative to the block's page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DynamicFixup {
+    /// A call or jump through the IAT.
+    ImportControlTransfer {
+        /// Offset of the instruction in the page.
+        offset: u16,
+        /// True for a call, false for a jump.
+        indirect_call: bool,
+        /// Index of the IAT entry.
+        iat_index: u32,
+    },
+    /// An indirect call or jump through a register.
+    IndirectControlTransfer {
+        /// Offset of the instruction in the page.
+        offset: u16,
+        /// True for a call, false for a jump.
+        indirect_call: bool,
+        /// True if the instruction has a REX.W prefix.
+        rex_w_prefix: bool,
+        /// True if the target is checked by CFG.
+        cfg_check: bool,
+    },
+    /// A switchable branch.
+    SwitchableBranch {
+        /// Offset of the instruction in the page.
+        offset: u16,
+        /// The register holding the target.
+        register_number: u8,
+    },
+    /// Zero the bytes at the offset.
+    Arm64XZeroFill {
+        /// Offset of the bytes in the page.
+        offset: u16,
+        /// Number of bytes: 2, 4 or 8.
+        size: u8,
+    },
+    /// Overwrite the bytes at the offset with a value.
+    Arm64XValue {
+        /// Offset of the bytes in the page.
+        offset: u16,
+        /// Number of bytes: 2, 4 or 8.
+        size: u8,
+        /// The value to store.
+        value: u64,
+    },
+    /// Add a delta to the 8-byte value at the offset.
+    Arm64XDelta {
+        /// Offset of the value in the page.
+        offset: u16,
+        /// The delta to add.
+        delta: i64,
+    },
+    /// An ARM64 kernel call through the IAT.
+    Arm64KernelImportCallTransfer {
+        /// Offset of the instruction in the page.
+        offset: u16,
+        /// True for a call, false for a branch.
+        indirect_call: bool,
+        /// The register holding the target.
+        register_index: u8,
+        /// The kind of import.
+        import_type: u8,
+        /// Index of the IAT entry, if the fixup has one.
+        iat_index: Option<u16>,
+    },
+}
+
+/// Parse the dynamic value relocation table of an image.
+/// The table is located through the section and offset in the
+/// load configuration, or its virtual address if those are unset.
+/// Returns `None` if the image has no table.
+pub fn parse_dynamic_relocation_table(binary: &[u8], pe: &PortableExecutable, config: &LoadConfigDirectory64) -> Result<Option<DynamicRelocationTable>, Error> {
+    let offset = if config.dynamic_value_reloc_table_section != 0 {
+        match pe.section_table.get(config.dynamic_value_reloc_table_section as usize - 1) {
+            Some(section) => section.pointer_to_raw_data as usize + config.dynamic_value_reloc_table_offset as usize,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    } else if config.dynamic_value_reloc_table != 0 {
+        match pe.va_to_rva(config.dynamic_value_reloc_table).and_then(|rva| pe.rva_to_offset(rva)) {
+            Some(offset) => offset,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    } else {
+        return Ok(None);
+    };
+
+    let mut table = DynamicRelocationTable {
+        version: read_u32(binary, offset)?,
+        size: read_u32(binary, offset + 4)?,
+        relocations: Vec::new(),
+    };
+
+    let start = offset + 8;
+    let data = match start.checked_add(table.size as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let pointer_size = if pe.is_64_bit() { 8 } else { 4 };
+    let mut offset = 0;
+    while offset < data.len() {
+        let (relocation, size) = match table.version {
+            1 => parse_dynamic_relocation_v1(data, offset, pointer_size)?,
+            2 => parse_dynamic_relocation_v2(data, offset, pointer_size)?,
+            _ => {
+                return Err(Error::BadDynamicRelocation);
+            }
+        };
+
+        table.relocations.push(relocation);
+        offset += size;
+    }
+
+    Ok(Some(table))
+}
+
+fn read_pointer(data: &[u8], offset: usize, pointer_size: usize) -> Result<u64, Error> {
+    if pointer_size == 8 {
+        Ok(read_u32(data, offset)? as u64 | (read_u32(data, offset + 4)? as u64) << 32)
+    } else {
+        Ok(read_u32(data, offset)?.into())
+    }
+}
+
+fn get_slice(data: &[u8], start: usize, size: usize) -> Result<&[u8], Error> {
+    match start.checked_add(size).and_then(|end| data.get(start..end)) {
+        Some(slice) => Ok(slice),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Version 1: the symbol and the size of the base relocation blocks that follow
+fn parse_dynamic_relocation_v1(data: &[u8], offset: usize, pointer_size: usize) -> Result<(DynamicRelocation, usize), Error> {
+    let symbol = read_pointer(data, offset, pointer_size)?;
+    let size = read_u32(data, offset + pointer_size)? as usize;
+    let header_size = pointer_size + 4;
+    let fixups = get_slice(data, offset + header_size, size)?;
+
+    let relocation = DynamicRelocation {
+        symbol,
+        symbol_group: 0,
+        flags: 0,
+        blocks: parse_dynamic_relocation_blocks(symbol, fixups)?,
+        data: fixups.to_vec(),
+    };
+
+    Ok((relocation, header_size + size))
+}
+
+/// Version 2: a header with its own size, followed by the fixup info
+fn parse_dynamic_relocation_v2(data: &[u8], offset: usize, pointer_size: usize) -> Result<(DynamicRelocation, usize), Error> {
+    let header_size = read_u32(data, offset)? as usize;
+    let size = read_u32(data, offset + 4)? as usize;
+    let symbol = read_pointer(data, offset + 8, pointer_size)?;
+
+    let relocation = DynamicRelocation {
+        symbol,
+        symbol_group: read_u32(data, offset + 8 + pointer_size)?,
+        flags: read_u32(data, offset + 12 + pointer_size)?,
+        blocks: Vec::new(),
+        data: get_slice(data, offset + header_size, size)?.to_vec(),
+    };
+
+    match header_size.checked_add(size) {
+        Some(size) if size > 0 => Ok((relocation, size)),
+        _ => Err(Error::BadDynamicRelocation),
+    }
+}
+
+/// Parse base relocation blocks, decoding the fixups of known symbols
+fn parse_dynamic_relocation_blocks(symbol: u64, data: &[u8]) -> Result<Vec<DynamicRelocationBlock>, Error> {
+    let mut blocks: Vec<DynamicRelocationBlock> = Vec::new();
+
+    let kind = match DynamicRelocationSymbol::from_u64(symbol) {
+        Some(kind @ (DynamicRelocationSymbol::GuardImportControlTransfer
+            | DynamicRelocationSymbol::GuardIndirControlTransfer
+            | DynamicRelocationSymbol::GuardSwitchtableBranch
+            | DynamicRelocationSymbol::Arm64X
+            | DynamicRelocationSymbol::Arm64KernelImportCallTransfer)) => kind,
+        _ => {
+            return Ok(blocks);
+        }
+    };
+
+    let mut offset = 0;
+    while offset + 8 <= data.len() {
+        let virtual_address = read_u32(data, offset)?;
+        let size = read_u32(data, offset + 4)? as usize;
+        if size < 8 {
+            return Err(Error::BadDynamicRelocation);
+        }
+
+        let entries = get_slice(data, offset + 8, size - 8)?;
+        blocks.push(DynamicRelocationBlock {
+            virtual_address,
+            fixups: parse_fixups(kind, entries)?,
+        });
+        offset += size;
+    }
+
+    Ok(blocks)
+}
+
+fn parse_fixups(kind: DynamicRelocationSymbol, data: &[u8]) -> Result<Vec<DynamicFixup>, Error> {
+    let mut fixups: Vec<DynamicFixup> = Vec::new();
+
+    // Blocks of 16-bit entries are padded to 4 bytes with an empty entry
+    let entries = match data.strip_suffix(&[0, 0]) {
+        Some(entries) if data.len().is_multiple_of(4) => entries,
+        _ => data,
+    };
+
+    match kind {
+        DynamicRelocationSymbol::GuardImportControlTransfer => {
+            for entry in data.chunks_exact(4) {
+                let entry = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                fixups.push(DynamicFixup::ImportControlTransfer {
+                    offset: (entry & 0xfff) as u16,
+                    indirect_call: entry & 0x1000 != 0,
+                    iat_index: entry >> 13,
+                });
+            }
+        }
+        DynamicRelocationSymbol::GuardIndirControlTransfer => {
+            for entry in entries.chunks_exact(2) {
+                let entry = u16::from_le_bytes([entry[0], entry[1]]);
+                fixups.push(DynamicFixup::IndirectControlTransfer {
+                    offset: entry & 0xfff,
+                    indirect_call: entry & 0x1000 != 0,
+                    rex_w_prefix: entry & 0x2000 != 0,
+                    cfg_check: entry & 0x4000 != 0,
+                });
+            }
+        }
+        DynamicRelocationSymbol::GuardSwitchtableBranch => {
+            for entry in entries.chunks_exact(2) {
+                let entry = u16::from_le_bytes([entry[0], entry[1]]);
+                fixups.push(DynamicFixup::SwitchableBranch {
+                    offset: entry & 0xfff,
+                    register_number: (entry >> 12) as u8,
+                });
+            }
+        }
+        DynamicRelocationSymbol::Arm64KernelImportCallTransfer => {
+            for entry in data.chunks_exact(4) {
+                let entry = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                // The page offset counts 4-byte instructions
+                let iat_index = (entry >> 17) as u16;
+                fixups.push(DynamicFixup::Arm64KernelImportCallTransfer {
+                    offset: ((entry & 0x3ff) << 2) as u16,
+                    indirect_call: entry & 0x400 != 0,
+                    register_index: ((entry >> 11) & 0x1f) as u8,
+                    import_type: ((entry >> 16) & 0x1) as u8,
+                    iat_index: (iat_index != ARM64_NO_IAT_INDEX).then_some(iat_index),
+                });
+            }
+        }
+        DynamicRelocationSymbol::Arm64X => {
+            let mut offset = 0;
+            while offset + 2 <= data.len() {
+                let header = read_u16(data, offset)?;
+                offset += 2;
+                // Blocks are padded to 4 bytes with empty headers
+                if header == 0 {
+                    continue;
+                }
+
+                let page_offset = header & 0xfff;
+                let size_field = ((header >> 14) & 0x3) as u8;
+                match (header >> 12) & 0x3 {
+                    0 => fixups.push(DynamicFixup::Arm64XZeroFill {
+                        offset: page_offset,
+                        size: 1 << size_field,
+                    }),
+                    1 => {
+                        let size = 1usize << size_field;
+                        let bytes = get_slice(data, offset, size)?;
+                        let mut value = [0u8; 8];
+                        value[..size].copy_from_slice(bytes);
+                        fixups.push(DynamicFixup::Arm64XValue {
+                            offset: page_offset,
+                            size: size as u8,
+                            value: u64::from_le_bytes(value),
+                        });
+                        offset += size;
+                    }
+                    2 => {
+                        // Bit 0 of the size field negates the delta, bit 1 scales it by 8 rather than 4
+                        let scale = if size_field & 0x2 != 0 { 8 } else { 4 };
+                        let delta = read_u16(data, offset)? as i64 * scale;
+                        fixups.push(DynamicFixup::Arm64XDelta {
+                            offset: page_offset,
+                            delta: if size_field & 0x1 != 0 { -delta } else { delta },
+                        });
+                        offset += 2;
+                    }
+                    _ => {
+                        return Err(Error::BadDynamicRelocation);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(fixups)
+}
+
+impl DynamicRelocation {
+    /// Returns the symbol as an enum
+    pub fn get_symbol(&self) -> Option<DynamicRelocationSymbol> {
+        DynamicRelocationSymbol::from_u64(self.symbol)
+    }
+}
+
+impl DynamicRelocationTable {
+    /// Produce the alternate view of an ARM64X image by applying its
+    /// ARM64X fixups to a copy of the file, as the loader does in memory.
+    /// Zero fills past a section's raw data are already zero and skipped.
+    pub fn apply_arm64x_fixups(&self, binary: &[u8], pe: &PortableExecutable) -> Result<Vec<u8>, Error> {
+        let mut image = binary.to_vec();
+
+        let relocations = self.relocations.iter()
+            .filter(|relocation| relocation.get_symbol() == Some(DynamicRelocationSymbol::Arm64X));
+        for relocation in relocations {
+            for block in relocation.blocks.iter() {
+                for fixup in block.fixups.iter() {
+                    let (offset, bytes) = match *fixup {
+                        DynamicFixup::Arm64XZeroFill { offset, size } => (offset, vec![0u8; size as usize]),
+                        DynamicFixup::Arm64XValue { offset, size, value } => (offset, value.to_le_bytes()[..size as usize].to_vec()),
+                        DynamicFixup::Arm64XDelta { offset, delta } => {
+                            let position = get_fixup_offset(&image, pe, block.virtual_address, offset, 8)?;
+                            let mut value = [0u8; 8];
+                            value.copy_from_slice(&image[position..position + 8]);
+                            (offset, u64::from_le_bytes(value).wrapping_add_signed(delta).to_le_bytes().to_vec())
+                        }
+                        _ => continue,
+                    };
+
+                    let position = match get_fixup_offset(&image, pe, block.virtual_address, offset, bytes.len()) {
+                        Ok(position) => position,
+                        Err(_) if bytes.iter().all(|&byte| byte == 0) => continue,
+                        Err(e) => {
+                            return Err(e);
+                        }
+                    };
+                    image[position..position + bytes.len()].copy_from_slice(&bytes);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+fn get_fixup_offset(image: &[u8], pe: &PortableExecutable, page: u32, offset: u16, size: usize) -> Result<usize, Error> {
+    match page.checked_add(offset.into()).and_then(|rva| pe.rva_to_offset(rva)) {
+        Some(position) if position.checked_add(size).is_some_and(|end| end <= image.len()) => Ok(position),
+        _ => Err(Error::OffsetOutOfRange),
+    }
+}
+
+impl fmt::Display for DynamicRelocationTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dynamic Relocation Table")?;
+        writeln!(f, "------------------------")?;
+        writeln!(f, "Version:                 {}", self.version)?;
+        writeln!(f, "Size:                    {}", self.size)?;
+
+        for relocation in self.relocations.iter() {
+            writeln!(f)?;
+            match relocation.get_symbol() {
+                Some(symbol) => writeln!(f, "Symbol:                  {:?}", symbol)?,
+                None => writeln!(f, "Symbol:                  {:#x}", relocation.symbol)?,
+            }
+            writeln!(f, "Fixup Size:              {}", relocation.data.len())?;
+            for block in relocation.blocks.iter() {
+                for fixup in block.fixups.iter() {
+                    writeln!(f, "{:#010x} {}", block.virtual_address, fixup)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for DynamicFixup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DynamicFixup::ImportControlTransfer { offset, indirect_call, iat_index } =>
+                write!(f, "+{:#05x} {} IAT[{}]", offset, if indirect_call { "call" } else { "jmp" }, iat_index),
+            DynamicFixup::IndirectControlTransfer { offset, indirect_call, rex_w_prefix, cfg_check } =>
+                write!(f, "+{:#05x} {}{}{}", offset, if indirect_call { "call" } else { "jmp" },
+                    if rex_w_prefix { " rex.w" } else { "" }, if cfg_check { " cfg" } else { "" }),
+            DynamicFixup::SwitchableBranch { offset, register_number } =>
+                write!(f, "+{:#05x} switch r{}", offset, register_number),
+            DynamicFixup::Arm64XZeroFill { offset, size } =>
+                write!(f, "+{:#05x} zero {}", offset, size),
+            DynamicFixup::Arm64XValue { offset, size, value } =>
+                write!(f, "+{:#05x} value {} {:#x}", offset, size, value),
+            DynamicFixup::Arm64XDelta { offset, delta } =>
+                write!(f, "+{:#05x} delta {}", offset, delta),
+            DynamicFixup::Arm64KernelImportCallTransfer { offset, indirect_call, register_index, import_type, iat_index } => {
+                write!(f, "+{:#05x} {} x{} type {}", offset, if indirect_call { "blr" } else { "br" }, register_index, import_type)?;
+                match iat_index {
+                    Some(iat_index) => write!(f, " IAT[{}]", iat_index),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries32(entries: &[u32]) -> Vec<u8> {
+        entries.iter().flat_map(|entry| entry.to_le_bytes()).collect()
+    }
+
+    fn entries16(entries: &[u16]) -> Vec<u8> {
+        entries.iter().flat_map(|entry| entry.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn import_control_transfer() {
+        let data = entries32(&[0x123 | 0x1000 | (42 << 13), 0x456]);
+        assert_eq!(parse_fixups(DynamicRelocationSymbol::GuardImportControlTransfer, &data).unwrap(), [
+            DynamicFixup::ImportControlTransfer { offset: 0x123, indirect_call: true, iat_index: 42 },
+            DynamicFixup::ImportControlTransfer { offset: 0x456, indirect_call: false, iat_index: 0 },
+        ]);
+    }
+
+    #[test]
+    fn indirect_control_transfer() {
+        // The trailing empty entry pads the block to 4 bytes
+        let data = entries16(&[0x123 | 0x1000 | 0x4000, 0x456 | 0x2000, 0x789, 0]);
+        assert_eq!(parse_fixups(DynamicRelocationSymbol::GuardIndirControlTransfer, &data).unwrap(), [
+            DynamicFixup::IndirectControlTransfer { offset: 0x123, indirect_call: true, rex_w_prefix: false, cfg_check: true },
+            DynamicFixup::IndirectControlTransfer { offset: 0x456, indirect_call: false, rex_w_prefix: true, cfg_check: false },
+            DynamicFixup::IndirectControlTransfer { offset: 0x789, indirect_call: false, rex_w_prefix: false, cfg_check: false },
+        ]);
+    }
+
+    #[test]
+    fn switchable_branch() {
+        let data = entries16(&[0x123 | (5 << 12), 0xfff | (15 << 12)]);
+        assert_eq!(parse_fixups(DynamicRelocationSymbol::GuardSwitchtableBranch, &data).unwrap(), [
+            DynamicFixup::SwitchableBranch { offset: 0x123, register_number: 5 },
+            DynamicFixup::SwitchableBranch { offset: 0xfff, register_number: 15 },
+        ]);
+    }
+
+    #[test]
+    fn arm64x() {
+        let mut data = entries16(&[0x010 | (2 << 14)]);
+        data.extend(entries16(&[0x020 | (1 << 12) | (1 << 14), 0xbeef]));
+        data.extend(entries16(&[0x030 | (2 << 12) | (3 << 14), 2]));
+        data.extend(entries16(&[0x040 | (2 << 12), 3, 0]));
+        assert_eq!(parse_fixups(DynamicRelocationSymbol::Arm64X, &data).unwrap(), [
+            DynamicFixup::Arm64XZeroFill { offset: 0x010, size: 4 },
+            DynamicFixup::Arm64XValue { offset: 0x020, size: 2, value: 0xbeef },
+            DynamicFixup::Arm64XDelta { offset: 0x030, delta: -16 },
+            DynamicFixup::Arm64XDelta { offset: 0x040, delta: 12 },
+        ]);
+    }
+
+    #[test]
+    fn arm64_kernel_import_call_transfer() {
+        let data = entries32(&[
+            0x3ff | 0x400 | (17 << 11) | (1 << 16) | (1234 << 17),
+            0x001 | (30 << 11) | (0x7fff << 17),
+        ]);
+        assert_eq!(parse_fixups(DynamicRelocationSymbol::Arm64KernelImportCallTransfer, &data).unwrap(), [
+            DynamicFixup::Arm64KernelImportCallTransfer { offset: 0xffc, indirect_call: true, register_index: 17, import_type: 1, iat_index: Some(1234) },
+            DynamicFixup::Arm64KernelImportCallTransfer { offset: 0x004, indirect_call: false, register_index: 30, import_type: 0, iat_index: None },
+        ]);
+    }
+}
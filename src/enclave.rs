@@ -0,0 +1,326 @@
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::{fmt, str};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use crate::{load_config::{LoadConfigDirectory64, read_versioned}, pe::{PortableExecutable, read_c_string, read_u32}, prelude::*, Error};
+
+/// Enclave configuration of a PE32 enclave image
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct EnclaveConfig32 {
+    /// The size of the structure, in bytes.
+    pub size: u32,
+    /// The minimum size of the structure the image loader must be able to process.
+    pub minimum_required_config_size: u32,
+    /// Policy flags for the enclave.
+    pub policy_flags: u32,
+    /// The number of images in the import list.
+    pub number_of_imports: u32,
+    /// The RVA of the array of images the enclave image may import.
+    pub import_list: u32,
+    /// The size of each entry in the import list.
+    pub import_entry_size: u32,
+    /// The family identifier the image author assigned.
+    pub family_id: [u8; 16],
+    /// The image identifier the image author assigned.
+    pub image_id: [u8; 16],
+    /// The version number the image author assigned.
+    pub image_version: u32,
+    /// The security version number the image author assigned.
+    pub security_version: u32,
+    /// The expected virtual size of the enclave.
+    pub enclave_size: u32,
+    /// The maximum number of threads that can be created within the enclave.
+    pub number_of_threads: u32,
+    /// Flags describing the enclave image.
+    pub enclave_flags: u32,
+}
+
+/// Enclave configuration of a PE32+ enclave image
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct EnclaveConfig64 {
+    /// The size of the structure, in bytes.
+    pub size: u32,
+    /// The minimum size of the structure the image loader must be able to process.
+    pub minimum_required_config_size: u32,
+    /// Policy flags for the enclave.
+    pub policy_flags: u32,
+    /// The number of images in the import list.
+    pub number_of_imports: u32,
+    /// The RVA of the array of images the enclave image may import.
+    pub import_list: u32,
+    /// The size of each entry in the import list.
+    pub import_entry_size: u32,
+    /// The family identifier the image author assigned.
+    pub family_id: [u8; 16],
+    /// The image identifier the image author assigned.
+    pub image_id: [u8; 16],
+    /// The version number the image author assigned.
+    pub image_version: u32,
+    /// The security version number the image author assigned.
+    pub security_version: u32,
+    /// The expected virtual size of the enclave.
+    pub enclave_size: u64,
+    /// The maximum number of threads that can be created within the enclave.
+    pub number_of_threads: u32,
+    /// Flags describing the enclave image.
+    pub enclave_flags: u32,
+}
+
+impl From<EnclaveConfig32> for EnclaveConfig64 {
+    fn from(config: EnclaveConfig32) -> Self {
+        EnclaveConfig64 {
+            size: config.size,
+            minimum_required_config_size: config.minimum_required_config_size,
+            policy_flags: config.policy_flags,
+            number_of_imports: config.number_of_imports,
+            import_list: config.import_list,
+            import_entry_size: config.import_entry_size,
+            family_id: config.family_id,
+            image_id: config.image_id,
+            image_version: config.image_version,
+            security_version: config.security_version,
+            enclave_size: config.enclave_size.into(),
+            number_of_threads: config.number_of_threads,
+            enclave_flags: config.enclave_flags,
+        }
+    }
+}
+
+/// An image the enclave image may import
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct EnclaveImport {
+    /// How the imported image is matched; see `EnclaveImportMatchType`.
+    pub match_type: u32,
+    /// The minimum security version the imported image must have.
+    pub minimum_security_version: u32,
+    /// The unique or author identifier the imported image must have, depending on the match type.
+    pub unique_or_author_id: [u8; 32],
+    /// The family identifier the imported image must have.
+    pub family_id: [u8; 16],
+    /// The image identifier the imported image must have.
+    pub image_id: [u8; 16],
+    /// The RVA of the name of the imported image.
+    pub import_name: u32,
+    /// Reserved.
+    pub reserved: u32,
+}
+
+/// How an enclave import is matched against the imported image
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum EnclaveImportMatchType {
+    /// No identifiers are checked.
+    None = 0,
+    /// The unique identifier of the image must match.
+    UniqueId = 1,
+    /// The author identifier, family identifier and image identifier must match.
+    AuthorId = 2,
+    /// The family identifier must match.
+    FamilyId = 3,
+    /// The image identifier must match.
+    ImageId = 4,
+}
+
+bitflags! {
+    /// Policy flags of an enclave configuration
+    pub struct EnclavePolicyFlags: u32 {
+        /// The enclave can be debugged.
+        const IMAGE_ENCLAVE_POLICY_DEBUGGABLE = 0x00000001;
+    }
+}
+
+// Allow Enclave policy flags to be easily printed
+impl fmt::Debug for EnclavePolicyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for EnclavePolicyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for EnclavePolicyFlags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+bitflags! {
+    /// Flags describing an enclave image
+    pub struct EnclaveFlags: u32 {
+        /// The image is the primary image of the enclave.
+        const IMAGE_ENCLAVE_FLAG_PRIMARY_IMAGE = 0x00000001;
+    }
+}
+
+// Allow Enclave flags to be easily printed
+impl fmt::Debug for EnclaveFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for EnclaveFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for EnclaveFlags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// Parse the enclave configuration the load configuration points to.
+/// PE32 structures are widened to the PE32+ layout.
+/// Returns `None` if the image has no enclave configuration.
+pub fn parse_enclave_config(binary: &[u8], pe: &PortableExecutable, config: &LoadConfigDirectory64) -> Result<Option<EnclaveConfig64>, Error> {
+    if config.enclave_configuration_pointer == 0 {
+        return Ok(None);
+    }
+
+    let data = match pe.va_to_rva(config.enclave_configuration_pointer).and_then(|rva| pe.rva_to_offset(rva)).and_then(|offset| binary.get(offset..)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let size = read_u32(data, 0)? as usize;
+    if pe.is_64_bit() {
+        Ok(Some(read_versioned::<EnclaveConfig64>(data, size)?))
+    } else {
+        Ok(Some(read_versioned::<EnclaveConfig32>(data, size)?.into()))
+    }
+}
+
+/// Parse the import list of an enclave configuration.
+/// Entries are `import_entry_size` bytes apart.
+pub fn parse_enclave_imports(binary: &[u8], pe: &PortableExecutable, config: &EnclaveConfig64) -> Result<Vec<EnclaveImport>, Error> {
+    let mut imports: Vec<EnclaveImport> = Vec::new();
+    if config.import_list == 0 || config.number_of_imports == 0 {
+        return Ok(imports);
+    }
+
+    let offset = match pe.rva_to_offset(config.import_list) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let entry_size = (config.import_entry_size as usize).max(size_of::<EnclaveImport>());
+    for index in 0..config.number_of_imports as usize {
+        let start = index.checked_mul(entry_size).and_then(|delta| offset.checked_add(delta));
+        let entry = match start.and_then(|start| binary.get(start..start.checked_add(size_of::<EnclaveImport>())?)) {
+            Some(entry) => entry,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        match try_pod_read_unaligned::<EnclaveImport>(entry) {
+            Ok(import) => imports.push(import),
+            Err(_) => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    }
+
+    Ok(imports)
+}
+
+impl EnclaveConfig64 {
+    /// Returns the policy flags as bitflags
+    pub fn get_policy_flags(&self) -> EnclavePolicyFlags {
+        EnclavePolicyFlags::from_bits_retain(self.policy_flags)
+    }
+
+    /// Returns the enclave flags as bitflags
+    pub fn get_enclave_flags(&self) -> EnclaveFlags {
+        EnclaveFlags::from_bits_retain(self.enclave_flags)
+    }
+
+    /// Returns true if the enclave policy allows debugging
+    pub fn is_debuggable(&self) -> bool {
+        self.get_policy_flags().contains(EnclavePolicyFlags::IMAGE_ENCLAVE_POLICY_DEBUGGABLE)
+    }
+}
+
+impl EnclaveImport {
+    /// Returns the match type as an enum
+    pub fn get_match_type(&self) -> Option<EnclaveImportMatchType> {
+        EnclaveImportMatchType::from_u32(self.match_type)
+    }
+
+    /// Get the name of the imported image
+    pub fn get_name(&self, binary: &[u8], pe: &PortableExecutable) -> Result<String, Error> {
+        match pe.rva_to_offset(self.import_name) {
+            Some(offset) => read_c_string(binary, offset),
+            None => Err(Error::OffsetOutOfRange),
+        }
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for byte in bytes.iter() {
+        write!(f, "{:02X}", byte)?;
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for EnclaveConfig64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Enclave Configuration")?;
+        writeln!(f, "---------------------")?;
+        writeln!(f, "Size:                    {}", self.size)?;
+        writeln!(f, "Minimum Required Size:   {}", self.minimum_required_config_size)?;
+        writeln!(f, "Policy Flags:            {}", self.get_policy_flags())?;
+        writeln!(f, "Import List:             {:#010x} ({})", self.import_list, self.number_of_imports)?;
+        writeln!(f, "Import Entry Size:       {}", self.import_entry_size)?;
+        write!(f, "Family ID:               ")?;
+        write_hex(f, &self.family_id)?;
+        write!(f, "Image ID:                ")?;
+        write_hex(f, &self.image_id)?;
+        writeln!(f, "Image Version:           {}", self.image_version)?;
+        writeln!(f, "Security Version:        {}", self.security_version)?;
+        writeln!(f, "Enclave Size:            {:#x}", self.enclave_size)?;
+        writeln!(f, "Number of Threads:       {}", self.number_of_threads)?;
+        writeln!(f, "Enclave Flags:           {}", self.get_enclave_flags())?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for EnclaveImport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Enclave Import")?;
+        writeln!(f, "--------------")?;
+        match self.get_match_type() {
+            Some(match_type) => writeln!(f, "Match Type:              {:?}", match_type)?,
+            None => writeln!(f, "Match Type:              {}", self.match_type)?,
+        }
+        writeln!(f, "Min Security Version:    {}", self.minimum_security_version)?;
+        write!(f, "Unique or Author ID:     ")?;
+        write_hex(f, &self.unique_or_author_id)?;
+        write!(f, "Family ID:               ")?;
+        write_hex(f, &self.family_id)?;
+        write!(f, "Image ID:                ")?;
+        write_hex(f, &self.image_id)?;
+        writeln!(f, "Import Name:             {:#010x}", self.import_name)?;
+
+        Ok(())
+    }
+}
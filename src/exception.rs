@@ -0,0 +1,389 @@
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::{fmt, str};
+use crate::{coff::MachineTypes, debug::parse_records, pe::{PortableExecutable, read_u32}, prelude::*, Error};
+
+/// Names of the x64 general purpose registers, by register number
+pub const X64_REGISTER_NAMES: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi",
+    "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15"
+];
+
+/// An x64 function table entry from the exception table (.pdata)
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct RuntimeFunction {
+    /// The RVA of the start of the function.
+    pub begin_address: u32,
+    /// The RVA of the end of the function.
+    pub end_address: u32,
+    /// The RVA of the unwind information.
+    pub unwind_info_address: u32,
+}
+
+bitflags! {
+    /// Flags of an x64 unwind info structure
+    pub struct UnwindFlags: u8 {
+        /// The function has an exception handler that should be called when looking for functions that need to examine exceptions.
+        const UNW_FLAG_EHANDLER = 0x01;
+        /// The function has a termination handler that should be called when unwinding an exception.
+        const UNW_FLAG_UHANDLER = 0x02;
+        /// This unwind info structure is not the primary one for the procedure.
+        /// Instead, the chained unwind info entry is the contents of a previous `RuntimeFunction` entry.
+        const UNW_FLAG_CHAININFO = 0x04;
+    }
+}
+
+// Allow Unwind flags to be easily printed
+impl fmt::Debug for UnwindFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for UnwindFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for UnwindFlags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// A decoded x64 unwind operation.
+/// Offsets are relative to the stack pointer, or the frame pointer if one is established.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnwindOperation {
+    /// Push a nonvolatile integer register.
+    PushNonVolatile {
+        /// The register number.
+        register: u8,
+    },
+    /// Allocate an area on the stack.
+    Allocate {
+        /// The size of the allocation in bytes.
+        size: u32,
+    },
+    /// Establish the frame pointer register at an offset from the stack pointer.
+    SetFramePointer,
+    /// Save a nonvolatile integer register on the stack.
+    SaveNonVolatile {
+        /// The register number.
+        register: u8,
+        /// The offset of the saved register.
+        offset: u32,
+    },
+    /// Save all 128 bits of a nonvolatile XMM register on the stack.
+    SaveXmm128 {
+        /// The XMM register number.
+        register: u8,
+        /// The offset of the saved register.
+        offset: u32,
+    },
+    /// Push a machine frame, as for a hardware interrupt or exception.
+    PushMachineFrame {
+        /// True if an error code was also pushed.
+        error_code: bool,
+    },
+    /// Describes an epilog (version 2).
+    Epilog {
+        /// The operation info, holding flags for the first entry.
+        info: u8,
+    },
+    /// An operation this parser does not decode.
+    Unknown {
+        /// The operation code.
+        operation: u8,
+        /// The operation info.
+        info: u8,
+    },
+}
+
+/// An x64 unwind code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnwindCode {
+    /// Offset from the start of the prolog of the end of the instruction.
+    /// For epilog codes, the size of the epilog or the offset of an epilog from the end of the function.
+    pub code_offset: u8,
+    /// The decoded operation.
+    pub operation: UnwindOperation
+}
+
+/// x64 unwind information of a function
+#[derive(Clone, Debug)]
+pub struct UnwindInfo {
+    /// The version of the structure, 1 or 2.
+    pub version: u8,
+    /// Flags; see `UnwindFlags`.
+    pub flags: u8,
+    /// The length of the prolog in bytes.
+    pub size_of_prolog: u8,
+    /// The number of the register used as frame pointer, or 0 if none.
+    pub frame_register: u8,
+    /// The scaled offset from the stack pointer applied to the frame pointer, in 16-byte units.
+    pub frame_offset: u8,
+    /// Unwind codes, in the order they are stored (reverse prolog order).
+    pub unwind_codes: Vec<UnwindCode>,
+    /// The RVA of the language-specific exception or termination handler.
+    pub exception_handler: Option<u32>,
+    /// The RVA of the language-specific handler data, which follows the handler's RVA.
+    pub handler_data: Option<u32>,
+    /// The function entry this unwind info is chained to.
+    pub chained_function: Option<RuntimeFunction>
+}
+
+/// Parse the exception table (.pdata) of an x64 image.
+/// Returns an empty list for other machine types, whose
+/// function table entries have different layouts.
+pub fn parse_exception_table(binary: &[u8], pe: &PortableExecutable) -> Result<Vec<RuntimeFunction>, Error> {
+    let data_directory = match pe.get_data_directories() {
+        Some(data_directories) => data_directories.exception_table,
+        None => {
+            return Ok(Vec::new());
+        }
+    };
+
+    if data_directory.virtual_address == 0 || pe.coff.get_machine_type() != Some(MachineTypes::AMD64) {
+        return Ok(Vec::new());
+    }
+
+    let start = match pe.rva_to_offset(data_directory.virtual_address) {
+        Some(start) => start,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    match start.checked_add(data_directory.size as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => parse_records(data),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Find the function table entry containing an RVA.
+/// The exception table is sorted by address.
+pub fn find_runtime_function(functions: &[RuntimeFunction], rva: u32) -> Option<&RuntimeFunction> {
+    let index = functions.partition_point(|function| function.begin_address <= rva);
+    let function = functions.get(index.checked_sub(1)?)?;
+    if rva < function.end_address {
+        Some(function)
+    } else {
+        None
+    }
+}
+
+/// Parse the unwind information of a function table entry.
+/// Entries whose unwind info address has the low bit set refer to another
+/// function table entry, whose unwind information is returned instead.
+pub fn parse_unwind_info(binary: &[u8], pe: &PortableExecutable, function: &RuntimeFunction) -> Result<UnwindInfo, Error> {
+    let mut rva = function.unwind_info_address;
+    if rva & 1 != 0 {
+        let offset = match pe.rva_to_offset(rva & !1) {
+            Some(offset) => offset,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+        rva = read_u32(binary, offset + 8)?;
+    }
+
+    let offset = match pe.rva_to_offset(rva) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let header = match binary.get(offset..offset + 4) {
+        Some(header) => header,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut info = UnwindInfo {
+        version: header[0] & 0x7,
+        flags: header[0] >> 3,
+        size_of_prolog: header[1],
+        frame_register: header[3] & 0xf,
+        frame_offset: header[3] >> 4,
+        unwind_codes: Vec::new(),
+        exception_handler: None,
+        handler_data: None,
+        chained_function: None,
+    };
+
+    if info.version != 1 && info.version != 2 {
+        return Err(Error::BadUnwindInfo);
+    }
+
+    // The array of codes is padded to an even number of slots
+    let count = header[2] as usize;
+    let codes = match binary.get(offset + 4..offset + 4 + count * 2) {
+        Some(codes) => codes,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+    info.unwind_codes = parse_unwind_codes(codes, info.version)?;
+
+    let trailer = offset + 4 + ((count + 1) & !1) * 2;
+    let trailer_rva = match rva.checked_add(4 + ((count as u32 + 1) & !1) * 2) {
+        Some(trailer_rva) => trailer_rva,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+    let flags = info.get_flags();
+    if flags.contains(UnwindFlags::UNW_FLAG_CHAININFO) {
+        let slice = match binary.get(trailer..trailer + size_of::<RuntimeFunction>()) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        match try_pod_read_unaligned::<RuntimeFunction>(slice) {
+            Ok(chained) => info.chained_function = Some(chained),
+            Err(_) => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    } else if flags.intersects(UnwindFlags::UNW_FLAG_EHANDLER | UnwindFlags::UNW_FLAG_UHANDLER) {
+        info.exception_handler = Some(read_u32(binary, trailer)?);
+        info.handler_data = match trailer_rva.checked_add(4) {
+            Some(handler_data) => Some(handler_data),
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+    }
+
+    Ok(info)
+}
+
+fn parse_unwind_codes(codes: &[u8], version: u8) -> Result<Vec<UnwindCode>, Error> {
+    let mut unwind_codes: Vec<UnwindCode> = Vec::new();
+    let slots: Vec<u16> = codes.chunks_exact(2).map(|slot| u16::from_le_bytes([slot[0], slot[1]])).collect();
+
+    let mut index = 0;
+    while index < slots.len() {
+        let code_offset = (slots[index] & 0xff) as u8;
+        let operation = ((slots[index] >> 8) & 0xf) as u8;
+        let info = (slots[index] >> 12) as u8;
+
+        // Operations with operands use the following one or two slots
+        let operand = |count: usize| -> Result<u32, Error> {
+            match slots.get(index + 1..index + 1 + count) {
+                Some([low]) => Ok((*low).into()),
+                Some([low, high]) => Ok(u32::from(*low) | u32::from(*high) << 16),
+                _ => Err(Error::BadUnwindInfo),
+            }
+        };
+
+        let (operation, size) = match operation {
+            0 => (UnwindOperation::PushNonVolatile { register: info }, 1),
+            1 if info == 0 => (UnwindOperation::Allocate { size: operand(1)? * 8 }, 2),
+            1 => (UnwindOperation::Allocate { size: operand(2)? }, 3),
+            2 => (UnwindOperation::Allocate { size: u32::from(info) * 8 + 8 }, 1),
+            3 => (UnwindOperation::SetFramePointer, 1),
+            4 => (UnwindOperation::SaveNonVolatile { register: info, offset: operand(1)? * 8 }, 2),
+            5 => (UnwindOperation::SaveNonVolatile { register: info, offset: operand(2)? }, 3),
+            6 if version >= 2 => (UnwindOperation::Epilog { info }, 1),
+            8 => (UnwindOperation::SaveXmm128 { register: info, offset: operand(1)? * 16 }, 2),
+            9 => (UnwindOperation::SaveXmm128 { register: info, offset: operand(2)? }, 3),
+            10 => (UnwindOperation::PushMachineFrame { error_code: info != 0 }, 1),
+            // Version 1's obsolete SAVE_XMM and SAVE_XMM_FAR use two and three slots
+            6 => (UnwindOperation::Unknown { operation, info }, 2),
+            7 => (UnwindOperation::Unknown { operation, info }, 3),
+            _ => (UnwindOperation::Unknown { operation, info }, 1),
+        };
+
+        unwind_codes.push(UnwindCode { code_offset, operation });
+        index += size;
+    }
+
+    Ok(unwind_codes)
+}
+
+impl RuntimeFunction {
+    /// Returns true if the function contains the given RVA
+    pub fn contains(&self, rva: u32) -> bool {
+        rva >= self.begin_address && rva < self.end_address
+    }
+}
+
+impl UnwindInfo {
+    /// Returns the flags as bitflags
+    pub fn get_flags(&self) -> UnwindFlags {
+        UnwindFlags::from_bits_retain(self.flags)
+    }
+}
+
+impl fmt::Display for RuntimeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Runtime Function")?;
+        writeln!(f, "----------------")?;
+        writeln!(f, "Begin Address:           {:#010x}", self.begin_address)?;
+        writeln!(f, "End Address:             {:#010x}", self.end_address)?;
+        writeln!(f, "Unwind Info Address:     {:#010x}", self.unwind_info_address)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for UnwindInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Unwind Info")?;
+        writeln!(f, "-----------")?;
+        writeln!(f, "Version:                 {}", self.version)?;
+        writeln!(f, "Flags:                   {}", self.get_flags())?;
+        writeln!(f, "Size of Prolog:          {}", self.size_of_prolog)?;
+        if self.frame_register != 0 {
+            writeln!(f, "Frame Register:          {}", X64_REGISTER_NAMES[self.frame_register as usize])?;
+            writeln!(f, "Frame Offset:            {:#x}", u32::from(self.frame_offset) * 16)?;
+        }
+        for code in self.unwind_codes.iter() {
+            writeln!(f, "Unwind Code:             {:#04x} {}", code.code_offset, code.operation)?;
+        }
+        if let Some(handler) = self.exception_handler {
+            writeln!(f, "Exception Handler:       {:#010x}", handler)?;
+        }
+        if let Some(handler_data) = self.handler_data {
+            writeln!(f, "Handler Data:            {:#010x}", handler_data)?;
+        }
+        if let Some(chained) = self.chained_function {
+            writeln!(f, "Chained Function:        {:#010x}-{:#010x} ({:#010x})", chained.begin_address, chained.end_address, chained.unwind_info_address)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for UnwindOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UnwindOperation::PushNonVolatile { register } =>
+                write!(f, "push {}", X64_REGISTER_NAMES[register as usize]),
+            UnwindOperation::Allocate { size } =>
+                write!(f, "alloc {:#x}", size),
+            UnwindOperation::SetFramePointer =>
+                write!(f, "set frame pointer"),
+            UnwindOperation::SaveNonVolatile { register, offset } =>
+                write!(f, "save {} at {:#x}", X64_REGISTER_NAMES[register as usize], offset),
+            UnwindOperation::SaveXmm128 { register, offset } =>
+                write!(f, "save xmm{} at {:#x}", register, offset),
+            UnwindOperation::PushMachineFrame { error_code } =>
+                write!(f, "push machine frame{}", if error_code { " with error code" } else { "" }),
+            UnwindOperation::Epilog { info } =>
+                write!(f, "epilog {:#x}", info),
+            UnwindOperation::Unknown { operation, info } =>
+                write!(f, "unknown {} {:#x}", operation, info),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::fmt;
+use crate::{pe::{PortableExecutable, read_c_string, read_u16, read_u32}, prelude::*, Error};
+
+/// Export Directory Table (.edata)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct ExportDirectory {
+    /// Reserved, must be 0.
+    pub export_flags: u32,
+    /// The time and date that the export data was created.
+    pub time_date_stamp: u32,
+    /// The major version number. The major and minor version numbers can be set by the user.
+    pub major_version: u16,
+    /// The minor version number.
+    pub minor_version: u16,
+    /// The address of the ASCII string that contains the name of the DLL. This address is relative to the image base.
+    pub name_rva: u32,
+    /// The starting ordinal number for exports in this image.
+    /// This field specifies the starting ordinal number for the export address table.
+    /// It is usually set to 1.
+    pub ordinal_base: u32,
+    /// The number of entries in the export address table.
+    pub address_table_entries: u32,
+    /// The number of entries in the name pointer table. This is also the number of entries in the ordinal table.
+    pub number_of_name_pointers: u32,
+    /// The address of the export address table, relative to the image base.
+    pub export_address_table_rva: u32,
+    /// The address of the export name pointer table, relative to the image base.
+    /// The table size is given by the Number of Name Pointers field.
+    pub name_pointer_rva: u32,
+    /// The address of the ordinal table, relative to the image base.
+    pub ordinal_table_rva: u32
+}
+
+impl fmt::Display for ExportDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Export Directory")?;
+        writeln!(f, "----------------")?;
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
+        writeln!(f, "Version:                 {}.{}", self.major_version, self.minor_version)?;
+        writeln!(f, "Name RVA:                {:#010x}", self.name_rva)?;
+        writeln!(f, "Ordinal Base:            {}", self.ordinal_base)?;
+        writeln!(f, "Address Table Entries:   {}", self.address_table_entries)?;
+        writeln!(f, "Number of Names:         {}", self.number_of_name_pointers)?;
+
+        Ok(())
+    }
+}
+
+/// A single exported symbol
+#[derive(Clone, Debug, PartialEq)]
+pub struct Export {
+    /// The biased ordinal of the export.
+    pub ordinal: u32,
+    /// The public name of the export, or `None` if it is exported by ordinal only.
+    pub name: Option<String>,
+    /// The RVA of the exported code or data, or 0 for forwarders.
+    pub address: u32,
+    /// The forwarder string, such as `NTDLL.RtlAllocateHeap`,
+    /// if the export is forwarded to another DLL.
+    pub forwarder: Option<String>
+}
+
+impl fmt::Display for Export {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>5}  ", self.ordinal)?;
+        match &self.forwarder {
+            Some(_) => write!(f, "forwarded ")?,
+            None => write!(f, "{:#010x}", self.address)?,
+        }
+        match &self.name {
+            Some(name) => write!(f, "  {}", name)?,
+            None => write!(f, "  [NONAME]")?,
+        }
+        if let Some(forwarder) = &self.forwarder {
+            write!(f, " -> {}", forwarder)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The parsed export data of an image
+pub struct ExportTable {
+    /// The export directory table.
+    pub directory: ExportDirectory,
+    /// The name of the DLL, as recorded by the linker.
+    pub dll_name: String,
+    /// Every export, in ordinal order.
+    pub exports: Vec<Export>,
+}
+
+impl fmt::Display for ExportTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.directory)?;
+        writeln!(f, "DLL Name:                {}", self.dll_name)?;
+        for export in self.exports.iter() {
+            writeln!(f, "{}", export)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the export table of an image.
+/// Returns `None` if the image has no export directory.
+pub fn parse_export_table(binary: &[u8], pe: &PortableExecutable) -> Result<Option<ExportTable>, Error> {
+    let data_directory = match pe.get_data_directories() {
+        Some(data_directories) => data_directories.export_table,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    if data_directory.virtual_address == 0 || data_directory.size == 0 {
+        return Ok(None);
+    }
+
+    let offset = rva_to_offset(pe, data_directory.virtual_address)?;
+    let slice = match binary.get(offset..offset+size_of::<ExportDirectory>()) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let directory = match try_pod_read_unaligned::<ExportDirectory>(slice) {
+        Ok(directory) => directory,
+        Err(_) => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let dll_name = read_c_string(binary, rva_to_offset(pe, directory.name_rva)?)?;
+
+    // Reject tables that could not fit in the file before allocating for them
+    if (directory.address_table_entries as usize).saturating_mul(4) > binary.len() {
+        return Err(Error::OffsetOutOfRange);
+    }
+
+    // Map each index in the export address table to its name
+    let mut names: Vec<Option<String>> = Vec::new();
+    names.resize(directory.address_table_entries as usize, None);
+    if directory.number_of_name_pointers != 0 {
+        let name_pointers = rva_to_offset(pe, directory.name_pointer_rva)?;
+        let ordinals = rva_to_offset(pe, directory.ordinal_table_rva)?;
+        for i in 0..directory.number_of_name_pointers as usize {
+            let name_rva = read_u32(binary, name_pointers + i * 4)?;
+            let index = read_u16(binary, ordinals + i * 2)? as usize;
+            if let Some(name) = names.get_mut(index) {
+                *name = Some(read_c_string(binary, rva_to_offset(pe, name_rva)?)?);
+            }
+        }
+    }
+
+    let directory_start = data_directory.virtual_address;
+    let directory_end = directory_start.saturating_add(data_directory.size);
+    let mut exports: Vec<Export> = Vec::new();
+    let address_table = match directory.address_table_entries {
+        0 => 0,
+        _ => rva_to_offset(pe, directory.export_address_table_rva)?,
+    };
+
+    for (i, name) in names.into_iter().enumerate() {
+        let address = read_u32(binary, address_table + i * 4)?;
+        if address == 0 {
+            continue;
+        }
+
+        // Addresses inside the export section are forwarder strings
+        let forwarder = if address >= directory_start && address < directory_end {
+            Some(read_c_string(binary, rva_to_offset(pe, address)?)?)
+        } else {
+            None
+        };
+
+        exports.push(Export {
+            ordinal: directory.ordinal_base.wrapping_add(i as u32),
+            name,
+            address: if forwarder.is_some() { 0 } else { address },
+            forwarder,
+        });
+    }
+
+    Ok(Some(ExportTable {
+        directory,
+        dll_name,
+        exports,
+    }))
+}
+
+fn rva_to_offset(pe: &PortableExecutable, rva: u32) -> Result<usize, Error> {
+    match pe.rva_to_offset(rva) {
+        Some(offset) => Ok(offset),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
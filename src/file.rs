@@ -0,0 +1,184 @@
+use core::fmt;
+use alloc::boxed::Box;
+use crate::{archive::{Archive, is_archive, parse_archive}, coff::is_big_object, import::{ImportObject, is_import_object, parse_import_object}, legacy::{LegacyExecutable, LE_SIGNATURE, LX_SIGNATURE, NE_SIGNATURE, parse_legacy_executable}, object::{CoffObject, is_coff_object, parse_big_object, parse_coff_object}, pdb::{Pdb, is_pdb, parse_pdb}, pe::{PortableExecutable, parse_portable_executable, read_u32}, prelude::*, resource_file::{ResourceFile, is_resource_file, parse_resource_file}, te::{TE_SIGNATURE, TerseExecutable, parse_terse_executable}, Error};
+
+/// Offset of the new executable header pointer in the MS-DOS header
+const IMAGE_DOS_NEW_HEADER_OFFSET: usize = 0x3c;
+
+/// Every file format `parse_file` can recognize, in the order they are tried
+pub const SUPPORTED_FORMATS: [FileFormat; 9] = [
+    FileFormat::Archive,
+    FileFormat::ResourceFile,
+    FileFormat::Pdb,
+    FileFormat::Image,
+    FileFormat::LegacyExecutable,
+    FileFormat::TerseExecutable,
+    FileFormat::BigObject,
+    FileFormat::ImportObject,
+    FileFormat::CoffObject,
+];
+
+/// Kinds of Windows build artifacts
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FileFormat {
+    /// MZ/PE executable image
+    Image,
+    /// COFF object file
+    CoffObject,
+    /// Big object file (`/bigobj`)
+    BigObject,
+    /// Short import object, found in import libraries
+    ImportObject,
+    /// `!<arch>` library archive
+    Archive,
+    /// Terse Executable image
+    TerseExecutable,
+    /// Compiled resource file (.res)
+    ResourceFile,
+    /// NE or LE/LX executable
+    LegacyExecutable,
+    /// Program database (MSF 7.0)
+    Pdb,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Image => f.write_str("PE image"),
+            FileFormat::CoffObject => f.write_str("COFF object"),
+            FileFormat::BigObject => f.write_str("big object"),
+            FileFormat::ImportObject => f.write_str("short import object"),
+            FileFormat::Archive => f.write_str("archive"),
+            FileFormat::TerseExecutable => f.write_str("TE image"),
+            FileFormat::ResourceFile => f.write_str("resource file"),
+            FileFormat::LegacyExecutable => f.write_str("NE/LE executable"),
+            FileFormat::Pdb => f.write_str("PDB"),
+        }
+    }
+}
+
+/// A parsed file of any supported format
+pub enum ParsedFile {
+    /// MZ/PE executable image
+    Image(Box<PortableExecutable>),
+    /// COFF object file
+    CoffObject(CoffObject),
+    /// Big object file (`/bigobj`)
+    BigObject(CoffObject),
+    /// Short import object
+    ImportObject(ImportObject),
+    /// `!<arch>` library archive
+    Archive(Archive),
+    /// Terse Executable image
+    TerseExecutable(TerseExecutable),
+    /// Compiled resource file (.res)
+    ResourceFile(ResourceFile),
+    /// NE or LE/LX executable
+    LegacyExecutable(LegacyExecutable),
+    /// Program database (MSF 7.0)
+    Pdb(Box<Pdb>),
+}
+
+/// Sniff the format of a file from its leading bytes.
+/// COFF object files have no signature, so they are only
+/// reported if their header fields are plausible.
+pub fn detect_file_format(binary: &[u8]) -> Option<FileFormat> {
+    if is_archive(binary) {
+        return Some(FileFormat::Archive);
+    }
+
+    if is_resource_file(binary) {
+        return Some(FileFormat::ResourceFile);
+    }
+
+    if is_pdb(binary) {
+        return Some(FileFormat::Pdb);
+    }
+
+    if binary.get(0..2) == Some(b"MZ") {
+        let offset = read_u32(binary, IMAGE_DOS_NEW_HEADER_OFFSET).ok()? as usize;
+        let signature = binary.get(offset..offset.checked_add(4)?)?;
+
+        return if signature == b"PE\0\0" {
+            Some(FileFormat::Image)
+        } else if signature[0..2] == NE_SIGNATURE || signature[0..2] == LE_SIGNATURE || signature[0..2] == LX_SIGNATURE {
+            Some(FileFormat::LegacyExecutable)
+        } else {
+            None
+        };
+    }
+
+    if binary.get(0..2) == Some(&TE_SIGNATURE[..]) {
+        return Some(FileFormat::TerseExecutable);
+    }
+
+    if is_big_object(binary) {
+        return Some(FileFormat::BigObject);
+    }
+
+    if is_import_object(binary) {
+        return Some(FileFormat::ImportObject);
+    }
+
+    if is_coff_object(binary) {
+        return Some(FileFormat::CoffObject);
+    }
+
+    None
+}
+
+/// Parse any supported Windows build artifact, dispatching on its detected format.
+/// Returns `Error::UnrecognizedFormat` if no format matches.
+pub fn parse_file(binary: &[u8]) -> Result<ParsedFile, Error> {
+    let format = match detect_file_format(binary) {
+        Some(format) => format,
+        None => {
+            return Err(Error::UnrecognizedFormat);
+        }
+    };
+
+    match format {
+        FileFormat::Image => Ok(ParsedFile::Image(Box::new(parse_portable_executable(binary)?))),
+        FileFormat::CoffObject => Ok(ParsedFile::CoffObject(parse_coff_object(binary)?)),
+        FileFormat::BigObject => Ok(ParsedFile::BigObject(parse_big_object(binary)?)),
+        FileFormat::ImportObject => Ok(ParsedFile::ImportObject(parse_import_object(binary)?)),
+        FileFormat::Archive => Ok(ParsedFile::Archive(parse_archive(binary)?)),
+        FileFormat::TerseExecutable => Ok(ParsedFile::TerseExecutable(parse_terse_executable(binary)?)),
+        FileFormat::ResourceFile => Ok(ParsedFile::ResourceFile(parse_resource_file(binary)?)),
+        FileFormat::LegacyExecutable => Ok(ParsedFile::LegacyExecutable(parse_legacy_executable(binary)?)),
+        FileFormat::Pdb => Ok(ParsedFile::Pdb(Box::new(parse_pdb(binary)?))),
+    }
+}
+
+impl ParsedFile {
+    /// Returns the format of the parsed file
+    pub fn get_format(&self) -> FileFormat {
+        match self {
+            ParsedFile::Image(_) => FileFormat::Image,
+            ParsedFile::CoffObject(_) => FileFormat::CoffObject,
+            ParsedFile::BigObject(_) => FileFormat::BigObject,
+            ParsedFile::ImportObject(_) => FileFormat::ImportObject,
+            ParsedFile::Archive(_) => FileFormat::Archive,
+            ParsedFile::TerseExecutable(_) => FileFormat::TerseExecutable,
+            ParsedFile::ResourceFile(_) => FileFormat::ResourceFile,
+            ParsedFile::LegacyExecutable(_) => FileFormat::LegacyExecutable,
+            ParsedFile::Pdb(_) => FileFormat::Pdb,
+        }
+    }
+}
+
+impl fmt::Display for ParsedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsedFile::Image(pe) => write!(f, "{}", pe),
+            ParsedFile::CoffObject(object) => write!(f, "{}", object),
+            ParsedFile::BigObject(object) => write!(f, "{}", object),
+            ParsedFile::ImportObject(import) => writeln!(f, "{}", import),
+            ParsedFile::Archive(archive) => write!(f, "{}", archive),
+            ParsedFile::TerseExecutable(te) => write!(f, "{}", te),
+            ParsedFile::ResourceFile(resource_file) => write!(f, "{}", resource_file),
+            ParsedFile::LegacyExecutable(legacy) => write!(f, "{}", legacy),
+            ParsedFile::Pdb(pdb) => write!(f, "{}", pdb),
+        }
+    }
+}
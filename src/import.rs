@@ -0,0 +1,285 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use core::fmt;
+use crate::{coff::MachineTypes, prelude::*, Error};
+
+/// Returns true if the binary starts with a short import object header
+pub fn is_import_object(binary: &[u8]) -> bool {
+    binary.get(0..6) == Some(&[0x00, 0x00, 0xff, 0xff, 0x00, 0x00][..])
+}
+
+/// Parse the header of a short import object, as found in import libraries
+pub fn parse_import_object_header(binary: &[u8]) -> Result<ImportObjectHeader, Error> {
+    if !is_import_object(binary) {
+        return Err(Error::MissingCoffHeader);
+    }
+
+    let slice = match binary.get(0..size_of::<ImportObjectHeader>()) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    match try_pod_read_unaligned::<ImportObjectHeader>(slice) {
+        Ok(header) => Ok(header),
+        Err(_) => Err(Error::MissingCoffHeader),
+    }
+}
+
+/// Parse a short import object, as found in import libraries.
+/// The header is followed by the null-terminated import symbol name,
+/// the null-terminated DLL name and, for `ImportNameType::ExportAs`,
+/// the null-terminated export name.
+pub fn parse_import_object(binary: &[u8]) -> Result<ImportObject, Error> {
+    let header = parse_import_object_header(binary)?;
+
+    let start = size_of::<ImportObjectHeader>();
+    let data = match start.checked_add(header.size_of_data as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut strings = data.split(|&c| c == 0);
+    let symbol_name = read_string(strings.next())?;
+    let dll_name = read_string(strings.next())?;
+    let export_name = if header.get_name_type() == Some(ImportNameType::ExportAs) {
+        Some(read_string(strings.next())?)
+    } else {
+        None
+    };
+
+    Ok(ImportObject {
+        header,
+        symbol_name,
+        dll_name,
+        export_name,
+    })
+}
+
+fn read_string(bytes: Option<&[u8]>) -> Result<String, Error> {
+    match bytes {
+        Some(bytes) => String::from_utf8(bytes.to_vec()).map_err(Error::BadString),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// A short import object: a single import described
+/// without a full COFF object
+#[derive(Clone)]
+pub struct ImportObject {
+    /// Import header.
+    pub header: ImportObjectHeader,
+    /// The public symbol name the import defines, such as `foo` or `_foo@4`.
+    pub symbol_name: String,
+    /// The name of the DLL to import from.
+    pub dll_name: String,
+    /// The name exported by the DLL, for `ImportNameType::ExportAs` only.
+    pub export_name: Option<String>
+}
+
+impl fmt::Display for ImportObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Import Object")?;
+        writeln!(f, "-------------")?;
+        match self.header.get_machine_type() {
+            Some(machine_type) => writeln!(f, "Machine Type:            {:?}", machine_type)?,
+            None => writeln!(f, "Machine Type:            {:#06x}", self.header.machine)?,
+        }
+        writeln!(f, "Symbol Name:             {:?}", self.symbol_name)?;
+        writeln!(f, "DLL Name:                {:?}", self.dll_name)?;
+        match self.header.get_import_type() {
+            Some(import_type) => writeln!(f, "Import Type:             {:?}", import_type)?,
+            None => writeln!(f, "Import Type:             {}", self.header.type_info & 0x3)?,
+        }
+        match self.header.get_name_type() {
+            Some(name_type) => writeln!(f, "Name Type:               {:?}", name_type)?,
+            None => writeln!(f, "Name Type:               {}", (self.header.type_info >> 2) & 0x7)?,
+        }
+        match self.get_ordinal() {
+            Some(ordinal) => writeln!(f, "Ordinal:                 {}", ordinal)?,
+            None => writeln!(f, "Hint:                    {}", self.header.ordinal_or_hint)?,
+        }
+        if let Some(name) = self.get_import_name() {
+            writeln!(f, "Import Name:             {:?}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ImportObject {
+    /// Returns the ordinal to import by, if the import is by ordinal
+    pub fn get_ordinal(&self) -> Option<u16> {
+        match self.header.get_name_type() {
+            Some(ImportNameType::Ordinal) => Some(self.header.ordinal_or_hint),
+            _ => None,
+        }
+    }
+
+    /// Returns the name the loader looks up in the DLL's export table,
+    /// derived from the symbol name according to the name type.
+    /// Returns `None` for imports by ordinal.
+    pub fn get_import_name(&self) -> Option<String> {
+        let name = self.symbol_name.as_str();
+
+        match self.header.get_name_type()? {
+            ImportNameType::Ordinal => None,
+            ImportNameType::Name => Some(String::from(name)),
+            ImportNameType::NoPrefix => Some(String::from(strip_prefix(name))),
+            ImportNameType::Undecorate => {
+                let name = strip_prefix(name);
+                Some(String::from(name.split('@').next().unwrap_or(name)))
+            }
+            ImportNameType::ExportAs => self.export_name.clone(),
+        }
+    }
+}
+
+/// Remove one leading `?`, `@` or `_`
+fn strip_prefix(name: &str) -> &str {
+    name.strip_prefix(['?', '@', '_']).unwrap_or(name)
+}
+
+/// Import Header (Short Import Library Format)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct ImportObjectHeader {
+    /// Must be `IMAGE_FILE_MACHINE_UNKNOWN` (0).
+    pub sig1: u16,
+    /// Must be 0xffff.
+    pub sig2: u16,
+    /// The structure version, 0.
+    pub version: u16,
+    /// The number that identifies the type of target machine.
+    pub machine: u16,
+    /// The time and date that the file was created.
+    pub time_date_stamp: u32,
+    /// The size of the strings that follow the header.
+    pub size_of_data: u32,
+    /// Either the ordinal or the hint for the import, determined by the value in the name type field.
+    pub ordinal_or_hint: u16,
+    /// The import type (bits 0-1), the import name type (bits 2-4) and reserved bits (5-15).
+    pub type_info: u16
+}
+
+impl fmt::Display for ImportObjectHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Import Header")?;
+        writeln!(f, "-------------")?;
+        match self.get_machine_type() {
+            Some(machine_type) => writeln!(f, "Machine Type:            {:?}", machine_type)?,
+            None => writeln!(f, "Machine Type:            {:#06x}", self.machine)?,
+        }
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
+        writeln!(f, "Size of Data:            {}", self.size_of_data)?;
+        writeln!(f, "Ordinal/Hint:            {}", self.ordinal_or_hint)?;
+        writeln!(f, "Type Info:               {:#06x}", self.type_info)?;
+
+        Ok(())
+    }
+}
+
+impl ImportObjectHeader {
+    /// Returns the machine type as an enum
+    pub fn get_machine_type(&self) -> Option<MachineTypes> {
+        MachineTypes::from_u16(self.machine)
+    }
+
+    /// Returns the import type as an enum
+    pub fn get_import_type(&self) -> Option<ImportType> {
+        ImportType::from_u16(self.type_info & 0x3)
+    }
+
+    /// Returns the import name type as an enum
+    pub fn get_name_type(&self) -> Option<ImportNameType> {
+        ImportNameType::from_u16((self.type_info >> 2) & 0x7)
+    }
+}
+
+/// The kind of symbol a short import object defines
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum ImportType {
+    /// Executable code.
+    Code = 0,
+    /// Data.
+    Data = 1,
+    /// Specified as CONST in the .def file.
+    Const = 2,
+}
+
+/// How the import name is derived from the symbol name
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum ImportNameType {
+    /// The import is by ordinal.
+    /// This indicates that the value in the ordinal/hint field of the import header is the import's ordinal.
+    /// If this constant is not specified, then the ordinal/hint field should always be interpreted as the import's hint.
+    Ordinal = 0,
+    /// The import name is identical to the public symbol name.
+    Name = 1,
+    /// The import name is the public symbol name, but skipping the leading ?, @, or optionally _.
+    NoPrefix = 2,
+    /// The import name is the public symbol name, but skipping the leading ?, @, or optionally _,
+    /// and truncating at the first @.
+    Undecorate = 3,
+    /// The import name is specified explicitly, following the DLL name.
+    ExportAs = 4,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::bytes_of;
+
+    fn import_object(import_type: ImportType, name_type: ImportNameType, ordinal_or_hint: u16, strings: &[u8]) -> Vec<u8> {
+        let header = ImportObjectHeader {
+            sig2: 0xffff,
+            machine: MachineTypes::I386 as u16,
+            size_of_data: strings.len() as u32,
+            ordinal_or_hint,
+            type_info: import_type as u16 | (name_type as u16) << 2,
+            ..Default::default()
+        };
+        let mut binary = bytes_of(&header).to_vec();
+        binary.extend_from_slice(strings);
+        binary
+    }
+
+    fn import_name(name_type: ImportNameType, symbol_name: &str) -> Option<String> {
+        let strings = format!("{}\0foo.dll\0", symbol_name);
+        parse_import_object(&import_object(ImportType::Code, name_type, 0, strings.as_bytes())).unwrap().get_import_name()
+    }
+
+    #[test]
+    fn import_names_follow_the_name_type() {
+        assert_eq!(import_name(ImportNameType::Name, "_foo@4").as_deref(), Some("_foo@4"));
+        assert_eq!(import_name(ImportNameType::NoPrefix, "_foo@4").as_deref(), Some("foo@4"));
+        assert_eq!(import_name(ImportNameType::Undecorate, "_foo@4").as_deref(), Some("foo"));
+        assert_eq!(import_name(ImportNameType::Undecorate, "?foo@@YAXXZ").as_deref(), Some("foo"));
+        assert_eq!(import_name(ImportNameType::Ordinal, "_foo@4"), None);
+    }
+
+    #[test]
+    fn ordinal_and_export_as_imports() {
+        let import = parse_import_object(&import_object(ImportType::Data, ImportNameType::Ordinal, 7, b"_foo\0foo.dll\0")).unwrap();
+        assert_eq!(import.header.get_import_type(), Some(ImportType::Data));
+        assert_eq!(import.get_ordinal(), Some(7));
+        assert_eq!(import.dll_name, "foo.dll");
+
+        let import = parse_import_object(&import_object(ImportType::Code, ImportNameType::ExportAs, 0, b"foo\0foo.dll\0bar\0")).unwrap();
+        assert_eq!(import.get_ordinal(), None);
+        assert_eq!(import.get_import_name().as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn truncated_strings() {
+        let binary = import_object(ImportType::Code, ImportNameType::Name, 0, b"foo\0foo.dll\0");
+        assert!(parse_import_object(&binary[..binary.len() - 4]).is_err());
+        assert!(!is_import_object(&[0x4c, 0x01, 0xff, 0xff]));
+    }
+}
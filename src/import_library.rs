@@ -0,0 +1,395 @@
+use bytemuck::bytes_of;
+use crate::{archive::{NewArchiveMember, write_archive}, coff::{Characteristics, CoffFileHeader, MachineTypes}, import::{ImportNameType, ImportObjectHeader, ImportType}, module_definition::{ModuleDefinition, ModuleExport}, prelude::*, relocation::{ARM64RelocationType, ARMRelocationType, CoffRelocation, I386RelocationType, X86RelocationType}, section::{SectionFlags, SectionHeader}, symbol::{CoffSymbol, StorageClass}, Error};
+
+const IMPORT_DESCRIPTOR_PREFIX: &str = "__IMPORT_DESCRIPTOR_";
+const NULL_IMPORT_DESCRIPTOR_SYMBOL_NAME: &str = "__NULL_IMPORT_DESCRIPTOR";
+const NULL_THUNK_DATA_PREFIX: &str = "\x7f";
+const NULL_THUNK_DATA_SUFFIX: &str = "_NULL_THUNK_DATA";
+const IMPORT_SYMBOL_PREFIX: &str = "__imp_";
+
+/// Size of an IMAGE_IMPORT_DESCRIPTOR
+const IMPORT_DESCRIPTOR_SIZE: usize = 20;
+
+/// Build an import library for the exports of a module definition.
+/// The library contains the import descriptor, null import descriptor
+/// and null thunk objects, followed by one short import object per export.
+/// PRIVATE exports are skipped, and NONAME exports must have an ordinal.
+///
+/// To build an import library for a DLL, describe its exports with
+/// `ModuleDefinition::from_export_table` first.
+pub fn write_import_library(definition: &ModuleDefinition, machine: MachineTypes) -> Result<Vec<u8>, Error> {
+    let dll_name = match &definition.library {
+        Some(library) => library.as_str(),
+        None => {
+            return Err(Error::BadModuleDefinition);
+        }
+    };
+
+    let relocation_type = match machine {
+        MachineTypes::AMD64 => X86RelocationType::Addr32Nb as u16,
+        MachineTypes::I386 => I386RelocationType::Dir32Nb as u16,
+        MachineTypes::ARMNT => ARMRelocationType::Addr32Nb as u16,
+        MachineTypes::ARM64 => ARM64RelocationType::Addr32Nb as u16,
+        _ => {
+            return Err(Error::UnsupportedMachine);
+        }
+    };
+
+    // The descriptor symbols are named after the DLL without its extension
+    let library = match dll_name.rfind('.') {
+        Some(index) => &dll_name[..index],
+        None => dll_name,
+    };
+
+    let import_descriptor_name = format!("{}{}", IMPORT_DESCRIPTOR_PREFIX, library);
+    let null_thunk_data_name = format!("{}{}{}", NULL_THUNK_DATA_PREFIX, library, NULL_THUNK_DATA_SUFFIX);
+
+    let mut members: Vec<NewArchiveMember> = Vec::new();
+    members.push(NewArchiveMember {
+        name: String::from(dll_name),
+        data: write_import_descriptor(machine, relocation_type, dll_name, &import_descriptor_name, &null_thunk_data_name),
+        symbols: vec![import_descriptor_name],
+    });
+    members.push(NewArchiveMember {
+        name: String::from(dll_name),
+        data: write_null_import_descriptor(machine),
+        symbols: vec![String::from(NULL_IMPORT_DESCRIPTOR_SYMBOL_NAME)],
+    });
+    members.push(NewArchiveMember {
+        name: String::from(dll_name),
+        data: write_null_thunk(machine, &null_thunk_data_name),
+        symbols: vec![null_thunk_data_name],
+    });
+
+    for export in definition.exports.iter().filter(|export| !export.private) {
+        if export.noname && export.ordinal.is_none() {
+            return Err(Error::BadModuleDefinition);
+        }
+
+        let symbol_name = get_symbol_name(&export.name, machine);
+        let import_symbol_name = format!("{}{}", IMPORT_SYMBOL_PREFIX, symbol_name);
+        let symbols = if export.data {
+            vec![import_symbol_name]
+        } else {
+            vec![symbol_name.clone(), import_symbol_name]
+        };
+
+        members.push(NewArchiveMember {
+            name: String::from(dll_name),
+            data: write_short_import(export, machine, &symbol_name, dll_name),
+            symbols,
+        });
+    }
+
+    Ok(write_archive(&members))
+}
+
+/// C++, fastcall and vectorcall names are used as-is
+fn is_decorated(name: &str) -> bool {
+    name.starts_with('?') || name.starts_with('@') || name.contains("@@")
+}
+
+/// The symbol an import defines: other x86 names carry a leading underscore
+fn get_symbol_name(name: &str, machine: MachineTypes) -> String {
+    if machine == MachineTypes::I386 && !is_decorated(name) {
+        format!("_{}", name)
+    } else {
+        String::from(name)
+    }
+}
+
+/// How the loader derives the exported name from the symbol name
+fn get_name_type(export: &ModuleExport, machine: MachineTypes) -> ImportNameType {
+    if export.noname {
+        ImportNameType::Ordinal
+    } else if export.export_as.is_some() {
+        ImportNameType::ExportAs
+    } else if machine == MachineTypes::I386 && !is_decorated(&export.name) {
+        ImportNameType::Undecorate
+    } else {
+        ImportNameType::Name
+    }
+}
+
+fn write_short_import(export: &ModuleExport, machine: MachineTypes, symbol_name: &str, dll_name: &str) -> Vec<u8> {
+    let import_type = if export.data {
+        ImportType::Data
+    } else if export.constant {
+        ImportType::Const
+    } else {
+        ImportType::Code
+    };
+    let name_type = get_name_type(export, machine);
+    let export_as = match (&export.export_as, name_type) {
+        (Some(export_as), ImportNameType::ExportAs) => Some(export_as.as_str()),
+        _ => None,
+    };
+
+    let header = ImportObjectHeader {
+        sig1: 0,
+        sig2: 0xffff,
+        version: 0,
+        machine: machine as u16,
+        time_date_stamp: 0,
+        size_of_data: (symbol_name.len() + 1 + dll_name.len() + 1 + export_as.map_or(0, |name| name.len() + 1)) as u32,
+        ordinal_or_hint: export.ordinal.unwrap_or_default(),
+        type_info: import_type as u16 | (name_type as u16) << 2,
+    };
+
+    let mut data = bytes_of(&header).to_vec();
+    data.extend_from_slice(symbol_name.as_bytes());
+    data.push(0);
+    data.extend_from_slice(dll_name.as_bytes());
+    data.push(0);
+    if let Some(export_as) = export_as {
+        data.extend_from_slice(export_as.as_bytes());
+        data.push(0);
+    }
+    data
+}
+
+/// A section of an object file being written
+struct NewSection {
+    name: &'static [u8],
+    characteristics: u32,
+    data: Vec<u8>,
+    relocations: Vec<CoffRelocation>,
+}
+
+/// A symbol of an object file being written
+struct NewSymbol {
+    name: String,
+    section_number: i16,
+    storage_class: StorageClass,
+}
+
+/// The object defining `__IMPORT_DESCRIPTOR_<library>`:
+/// the import directory entry (.idata$2) and the DLL name (.idata$6)
+fn write_import_descriptor(machine: MachineTypes, relocation_type: u16, dll_name: &str, import_descriptor_name: &str, null_thunk_data_name: &str) -> Vec<u8> {
+    let mut dll_name_data = dll_name.as_bytes().to_vec();
+    dll_name_data.push(0);
+    if dll_name_data.len() & 1 != 0 {
+        dll_name_data.push(0);
+    }
+
+    let relocation = |virtual_address: u32, symbol_table_index: u32| CoffRelocation {
+        virtual_address,
+        symbol_table_index,
+        relocation_type,
+    };
+
+    let data_flags = (SectionFlags::IMAGE_SCN_CNT_INITALIZED_DATA | SectionFlags::IMAGE_SCN_MEM_READ | SectionFlags::IMAGE_SCN_MEM_WRITE).bits();
+    let sections = [
+        NewSection {
+            name: b".idata$2",
+            characteristics: data_flags | SectionFlags::IMAGE_SCN_ALIGN_4BYTES.bits(),
+            data: vec![0; IMPORT_DESCRIPTOR_SIZE],
+            // Name, Import Lookup Table and Import Address Table RVAs
+            relocations: vec![relocation(12, 2), relocation(0, 3), relocation(16, 4)],
+        },
+        NewSection {
+            name: b".idata$6",
+            characteristics: data_flags | SectionFlags::IMAGE_SCN_ALIGN_2BYTES.bits(),
+            data: dll_name_data,
+            relocations: Vec::new(),
+        },
+    ];
+
+    let symbols = [
+        NewSymbol { name: String::from(import_descriptor_name), section_number: 1, storage_class: StorageClass::External },
+        NewSymbol { name: String::from(".idata$2"), section_number: 1, storage_class: StorageClass::Section },
+        NewSymbol { name: String::from(".idata$6"), section_number: 2, storage_class: StorageClass::Static },
+        NewSymbol { name: String::from(".idata$4"), section_number: 0, storage_class: StorageClass::Section },
+        NewSymbol { name: String::from(".idata$5"), section_number: 0, storage_class: StorageClass::Section },
+        NewSymbol { name: String::from(NULL_IMPORT_DESCRIPTOR_SYMBOL_NAME), section_number: 0, storage_class: StorageClass::External },
+        NewSymbol { name: String::from(null_thunk_data_name), section_number: 0, storage_class: StorageClass::External },
+    ];
+
+    write_object(machine, &sections, &symbols)
+}
+
+/// The object defining `__NULL_IMPORT_DESCRIPTOR`, which terminates the import directory
+fn write_null_import_descriptor(machine: MachineTypes) -> Vec<u8> {
+    let sections = [NewSection {
+        name: b".idata$3",
+        characteristics: (SectionFlags::IMAGE_SCN_CNT_INITALIZED_DATA | SectionFlags::IMAGE_SCN_MEM_READ | SectionFlags::IMAGE_SCN_MEM_WRITE | SectionFlags::IMAGE_SCN_ALIGN_4BYTES).bits(),
+        data: vec![0; IMPORT_DESCRIPTOR_SIZE],
+        relocations: Vec::new(),
+    }];
+
+    let symbols = [NewSymbol {
+        name: String::from(NULL_IMPORT_DESCRIPTOR_SYMBOL_NAME),
+        section_number: 1,
+        storage_class: StorageClass::External,
+    }];
+
+    write_object(machine, &sections, &symbols)
+}
+
+/// The object defining `\x7f<library>_NULL_THUNK_DATA`,
+/// which terminates the DLL's import lookup and address tables
+fn write_null_thunk(machine: MachineTypes, null_thunk_data_name: &str) -> Vec<u8> {
+    let (pointer_size, alignment) = match machine {
+        MachineTypes::AMD64 | MachineTypes::ARM64 => (8, SectionFlags::IMAGE_SCN_ALIGN_8BYTES),
+        _ => (4, SectionFlags::IMAGE_SCN_ALIGN_4BYTES),
+    };
+
+    let characteristics = (SectionFlags::IMAGE_SCN_CNT_INITALIZED_DATA | SectionFlags::IMAGE_SCN_MEM_READ | SectionFlags::IMAGE_SCN_MEM_WRITE | alignment).bits();
+    let sections = [
+        NewSection {
+            name: b".idata$5",
+            characteristics,
+            data: vec![0; pointer_size],
+            relocations: Vec::new(),
+        },
+        NewSection {
+            name: b".idata$4",
+            characteristics,
+            data: vec![0; pointer_size],
+            relocations: Vec::new(),
+        },
+    ];
+
+    let symbols = [NewSymbol {
+        name: String::from(null_thunk_data_name),
+        section_number: 1,
+        storage_class: StorageClass::External,
+    }];
+
+    write_object(machine, &sections, &symbols)
+}
+
+/// Lay out a COFF object: header, section table, each section's data
+/// followed by its relocations, then the symbol and string tables
+fn write_object(machine: MachineTypes, sections: &[NewSection], symbols: &[NewSymbol]) -> Vec<u8> {
+    let header_size = size_of::<CoffFileHeader>() + sections.len() * size_of::<SectionHeader>();
+    let mut section_headers: Vec<SectionHeader> = Vec::new();
+    let mut offset = header_size;
+
+    for section in sections {
+        let mut header = SectionHeader::default();
+        header.name[..section.name.len()].copy_from_slice(section.name);
+        header.size_of_raw_data = section.data.len() as u32;
+        header.pointer_to_raw_data = offset as u32;
+        offset += section.data.len();
+        if !section.relocations.is_empty() {
+            header.pointer_to_relocations = offset as u32;
+            header.number_of_relocations = section.relocations.len() as u16;
+            offset += section.relocations.len() * size_of::<CoffRelocation>();
+        }
+        header.characteristics = section.characteristics;
+        section_headers.push(header);
+    }
+
+    let coff = CoffFileHeader {
+        machine: machine as u16,
+        number_of_sections: sections.len() as u16,
+        time_date_stamp: 0,
+        pointer_to_symbol_table: offset as u32,
+        number_of_symbols: symbols.len() as u32,
+        size_of_optional_header: 0,
+        characteristics: if machine == MachineTypes::I386 { Characteristics::IMAGE_FILE_32BIT_MACHINE.bits() } else { 0 },
+    };
+
+    let mut binary = bytes_of(&coff).to_vec();
+    for header in section_headers.iter() {
+        binary.extend_from_slice(bytes_of(header));
+    }
+    for section in sections {
+        binary.extend_from_slice(&section.data);
+        for relocation in section.relocations.iter() {
+            binary.extend_from_slice(bytes_of(relocation));
+        }
+    }
+
+    // The string table size includes its own 4-byte length field
+    let mut strings: Vec<u8> = Vec::new();
+    for symbol in symbols {
+        let mut name = [0u8; 8];
+        if symbol.name.len() <= name.len() {
+            name[..symbol.name.len()].copy_from_slice(symbol.name.as_bytes());
+        } else {
+            let string_offset = (strings.len() + 4) as u32;
+            name[4..].copy_from_slice(&string_offset.to_le_bytes());
+            strings.extend_from_slice(symbol.name.as_bytes());
+            strings.push(0);
+        }
+
+        let record = CoffSymbol {
+            name,
+            value: 0,
+            section_number: symbol.section_number,
+            symbol_type: 0,
+            storage_class: symbol.storage_class as u8,
+            number_of_aux_symbols: 0,
+        };
+        binary.extend_from_slice(bytes_of(&record));
+    }
+
+    binary.extend_from_slice(&((strings.len() + 4) as u32).to_le_bytes());
+    binary.extend_from_slice(&strings);
+    binary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{archive::{ArchiveMemberContents, parse_archive}, import::ImportObject, module_definition::parse_module_definition};
+
+    fn build(text: &str, machine: MachineTypes) -> Result<Vec<u8>, Error> {
+        write_import_library(&parse_module_definition(text)?, machine)
+    }
+
+    fn find_import(library: &[u8], symbol_name: &str) -> Option<ImportObject> {
+        let archive = parse_archive(library).ok()?;
+        match archive.find_symbol(symbol_name)?.parse(library).ok()? {
+            ArchiveMemberContents::ImportObject(import) => Some(import),
+            ArchiveMemberContents::CoffObject(_) => None,
+        }
+    }
+
+    #[test]
+    fn stdcall_names_are_prefixed_and_undecorated_on_x86() {
+        let library = build("LIBRARY foo.dll\nEXPORTS foo@4", MachineTypes::I386).unwrap();
+        let import = find_import(&library, "_foo@4").unwrap();
+
+        assert_eq!(import.symbol_name, "_foo@4");
+        assert_eq!(import.header.get_name_type(), Some(ImportNameType::Undecorate));
+        assert_eq!(import.get_import_name().as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn export_as_names_follow_the_dll_name() {
+        let library = build("LIBRARY foo.dll\nEXPORTS foo == bar", MachineTypes::AMD64).unwrap();
+        let import = find_import(&library, "foo").unwrap();
+
+        assert_eq!(import.header.get_name_type(), Some(ImportNameType::ExportAs));
+        assert_eq!(import.export_name.as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn constants_are_imported_as_const() {
+        let library = build("LIBRARY foo.dll\nEXPORTS foo CONSTANT", MachineTypes::AMD64).unwrap();
+        let import = find_import(&library, "foo").unwrap();
+
+        assert_eq!(import.header.get_import_type(), Some(ImportType::Const));
+    }
+
+    #[test]
+    fn x86_objects_are_32_bit() {
+        let library = build("LIBRARY foo.dll\nEXPORTS foo", MachineTypes::I386).unwrap();
+        let archive = parse_archive(&library).unwrap();
+        let member = archive.find_symbol("__IMPORT_DESCRIPTOR_foo").unwrap();
+        let ArchiveMemberContents::CoffObject(object) = member.parse(&library).unwrap() else {
+            unreachable!();
+        };
+
+        let characteristics = object.coff.map(|coff| coff.characteristics).unwrap_or_default();
+        assert_eq!(characteristics, Characteristics::IMAGE_FILE_32BIT_MACHINE.bits());
+    }
+
+    #[test]
+    fn noname_exports_need_an_ordinal() {
+        assert!(matches!(build("LIBRARY foo.dll\nEXPORTS foo NONAME", MachineTypes::AMD64), Err(Error::BadModuleDefinition)));
+    }
+}
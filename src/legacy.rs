@@ -0,0 +1,270 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::fmt;
+use crate::{pe::read_u32, prelude::*, Error};
+
+/// Offset of the new executable header pointer in the MS-DOS header
+const IMAGE_DOS_NEW_HEADER_OFFSET: usize = 0x3c;
+
+/// Signature of a 16-bit New Executable ("NE")
+pub const NE_SIGNATURE: [u8; 2] = *b"NE";
+/// Signature of a Linear Executable ("LE"), used by VxDs and some DOS extenders
+pub const LE_SIGNATURE: [u8; 2] = *b"LE";
+/// Signature of a Linear eXecutable ("LX"), used by OS/2
+pub const LX_SIGNATURE: [u8; 2] = *b"LX";
+
+/// An executable that predates PE, found behind an MS-DOS stub
+pub enum LegacyExecutable {
+    /// 16-bit Windows or OS/2 New Executable
+    NewExecutable(NeHeader),
+    /// Linear Executable (LE) or Linear eXecutable (LX)
+    LinearExecutable(LeHeader),
+}
+
+/// Parse the NE or LE/LX header that the MS-DOS header points to
+pub fn parse_legacy_executable(binary: &[u8]) -> Result<LegacyExecutable, Error> {
+    if binary.get(0..2) != Some(b"MZ") {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let offset = read_u32(binary, IMAGE_DOS_NEW_HEADER_OFFSET)? as usize;
+    let signature = match binary.get(offset..offset+2) {
+        Some(slice) => [slice[0], slice[1]],
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if signature == NE_SIGNATURE {
+        Ok(LegacyExecutable::NewExecutable(read_header(binary, offset)?))
+    } else if signature == LE_SIGNATURE || signature == LX_SIGNATURE {
+        Ok(LegacyExecutable::LinearExecutable(read_header(binary, offset)?))
+    } else {
+        Err(Error::MissingMagicNumber)
+    }
+}
+
+fn read_header<T: Pod>(binary: &[u8], offset: usize) -> Result<T, Error> {
+    let slice = match binary.get(offset..offset+size_of::<T>()) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    match try_pod_read_unaligned::<T>(slice) {
+        Ok(header) => Ok(header),
+        Err(_) => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// New Executable Header (IMAGE_OS2_HEADER)
+/// Offsets are relative to the start of this header unless noted.
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct NeHeader {
+    /// Signature, "NE".
+    pub magic: u16,
+    /// Linker version number.
+    pub version: u8,
+    /// Linker revision number.
+    pub revision: u8,
+    /// Offset of the entry table.
+    pub entry_table: u16,
+    /// Number of bytes in the entry table.
+    pub entry_table_size: u16,
+    /// Checksum of the whole file.
+    pub crc: u32,
+    /// Module flags.
+    pub flags: u16,
+    /// Segment number of the automatic data segment.
+    pub auto_data_segment: u16,
+    /// Initial heap allocation.
+    pub heap_size: u16,
+    /// Initial stack allocation.
+    pub stack_size: u16,
+    /// Initial CS:IP setting.
+    pub cs_ip: u32,
+    /// Initial SS:SP setting.
+    pub ss_sp: u32,
+    /// Number of entries in the segment table.
+    pub segment_count: u16,
+    /// Number of entries in the module reference table.
+    pub module_reference_count: u16,
+    /// Number of bytes in the non-resident name table.
+    pub non_resident_name_table_size: u16,
+    /// Offset of the segment table.
+    pub segment_table: u16,
+    /// Offset of the resource table.
+    pub resource_table: u16,
+    /// Offset of the resident name table.
+    pub resident_name_table: u16,
+    /// Offset of the module reference table.
+    pub module_reference_table: u16,
+    /// Offset of the imported names table.
+    pub imported_names_table: u16,
+    /// File offset of the non-resident name table.
+    pub non_resident_name_table: u32,
+    /// Number of movable entries.
+    pub movable_entry_count: u16,
+    /// Segment alignment shift count.
+    pub alignment_shift: u16,
+    /// Number of resource segments.
+    pub resource_segment_count: u16,
+    /// Target operating system.
+    pub target_os: u8,
+    /// Other executable flags.
+    pub other_flags: u8,
+    /// Offset of the return thunks.
+    pub return_thunks: u16,
+    /// Offset of the segment reference thunks.
+    pub segment_reference_thunks: u16,
+    /// Minimum code swap area size.
+    pub swap_area: u16,
+    /// Expected Windows version number.
+    pub expected_windows_version: u16
+}
+
+impl fmt::Display for NeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "NE Header")?;
+        writeln!(f, "---------")?;
+        writeln!(f, "Linker Version:          {}.{}", self.version, self.revision)?;
+        writeln!(f, "Flags:                   {:#06x}", self.flags)?;
+        writeln!(f, "Target OS:               {}", self.target_os)?;
+        writeln!(f, "Expected Windows:        {}.{}", self.expected_windows_version >> 8, self.expected_windows_version & 0xff)?;
+        writeln!(f, "CS:IP:                   {:04x}:{:04x}", self.cs_ip >> 16, self.cs_ip & 0xffff)?;
+        writeln!(f, "SS:SP:                   {:04x}:{:04x}", self.ss_sp >> 16, self.ss_sp & 0xffff)?;
+        writeln!(f, "Number of Segments:      {}", self.segment_count)?;
+        writeln!(f, "Number of Modules:       {}", self.module_reference_count)?;
+        writeln!(f, "Alignment Shift:         {}", self.alignment_shift)?;
+
+        Ok(())
+    }
+}
+
+/// Linear Executable Header, shared by LE and LX
+/// Offsets are relative to the start of this header unless noted.
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct LeHeader {
+    /// Signature, "LE" or "LX".
+    pub magic: u16,
+    /// Byte ordering; 0 for little endian.
+    pub byte_order: u8,
+    /// Word ordering; 0 for little endian.
+    pub word_order: u8,
+    /// Format level of the executable.
+    pub format_level: u32,
+    /// Target CPU type.
+    pub cpu_type: u16,
+    /// Target operating system type.
+    pub os_type: u16,
+    /// Module version.
+    pub module_version: u32,
+    /// Module flags.
+    pub module_flags: u32,
+    /// Number of memory pages in the module.
+    pub module_page_count: u32,
+    /// Object number of the initial EIP.
+    pub eip_object: u32,
+    /// Initial EIP, relative to `eip_object`.
+    pub eip: u32,
+    /// Object number of the initial ESP.
+    pub esp_object: u32,
+    /// Initial ESP, relative to `esp_object`.
+    pub esp: u32,
+    /// Size of one page.
+    pub page_size: u32,
+    /// LE: size of the last page. LX: page offset shift.
+    pub last_page_size: u32,
+    /// Size of the fixup section.
+    pub fixup_section_size: u32,
+    /// Checksum of the fixup section.
+    pub fixup_section_checksum: u32,
+    /// Size of the loader section.
+    pub loader_section_size: u32,
+    /// Checksum of the loader section.
+    pub loader_section_checksum: u32,
+    /// Offset of the object table.
+    pub object_table: u32,
+    /// Number of entries in the object table.
+    pub object_count: u32,
+    /// Offset of the object page map.
+    pub object_page_map: u32,
+    /// Offset of the object iterated data map.
+    pub object_iterated_data_map: u32,
+    /// Offset of the resource table.
+    pub resource_table: u32,
+    /// Number of entries in the resource table.
+    pub resource_count: u32,
+    /// Offset of the resident name table.
+    pub resident_name_table: u32,
+    /// Offset of the entry table.
+    pub entry_table: u32,
+    /// Offset of the module directives table.
+    pub module_directives_table: u32,
+    /// Number of module directives.
+    pub module_directives_count: u32,
+    /// Offset of the fixup page table.
+    pub fixup_page_table: u32,
+    /// Offset of the fixup record table.
+    pub fixup_record_table: u32,
+    /// Offset of the imported module name table.
+    pub imported_module_table: u32,
+    /// Number of imported modules.
+    pub imported_module_count: u32,
+    /// Offset of the imported procedure name table.
+    pub imported_procedure_table: u32,
+    /// Offset of the per-page checksum table.
+    pub page_checksum_table: u32,
+    /// File offset of the data pages.
+    pub data_pages: u32,
+    /// Number of preload pages.
+    pub preload_page_count: u32,
+    /// File offset of the non-resident name table.
+    pub non_resident_name_table: u32,
+    /// Size of the non-resident name table.
+    pub non_resident_name_table_size: u32,
+    /// Checksum of the non-resident name table.
+    pub non_resident_name_table_checksum: u32,
+    /// Object number of the automatic data object.
+    pub auto_data_object: u32,
+    /// File offset of the debug information.
+    pub debug_info: u32,
+    /// Size of the debug information.
+    pub debug_info_size: u32,
+    /// Number of instance pages in the preload section.
+    pub instance_preload_count: u32,
+    /// Number of instance pages in the demand load section.
+    pub instance_demand_count: u32,
+    /// Size of the heap (16-bit applications only).
+    pub heap_size: u32
+}
+
+impl fmt::Display for LeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Linear Executable Header")?;
+        writeln!(f, "------------------------")?;
+        writeln!(f, "Signature:               {}", String::from_utf8_lossy(&self.magic.to_le_bytes()))?;
+        writeln!(f, "Format Level:            {}", self.format_level)?;
+        writeln!(f, "CPU Type:                {}", self.cpu_type)?;
+        writeln!(f, "OS Type:                 {}", self.os_type)?;
+        writeln!(f, "Module Version:          {}", self.module_version)?;
+        writeln!(f, "Module Flags:            {:#010x}", self.module_flags)?;
+        writeln!(f, "Number of Pages:         {}", self.module_page_count)?;
+        writeln!(f, "Entry Point:             {}:{:#010x}", self.eip_object, self.eip)?;
+        writeln!(f, "Page Size:               {}", self.page_size)?;
+        writeln!(f, "Number of Objects:       {}", self.object_count)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for LegacyExecutable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LegacyExecutable::NewExecutable(header) => writeln!(f, "{}", header),
+            LegacyExecutable::LinearExecutable(header) => writeln!(f, "{}", header),
+        }
+    }
+}
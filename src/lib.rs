@@ -30,6 +30,8 @@ extern crate alloc;
 use crate::prelude::*;
 use core::fmt;
 
+/// MS-DOS header definitions and helper functions
+pub mod dos;
 /// COFF file header definitions and helper functions
 pub mod coff;
 /// COFF relocation definitions and helper functions
@@ -38,6 +40,63 @@ pub mod relocation;
 pub mod optional;
 /// Section header definitions and helper functions
 pub mod section;
+/// COFF symbol table definitions and helper functions
+pub mod symbol;
+/// COFF string table definitions and helper functions
+pub mod string_table;
+/// COMDAT section definitions and helper functions
+pub mod comdat;
+/// COFF line number definitions and helper functions
+pub mod line_number;
+/// Linker directive (.drectve) definitions and helper functions
+pub mod drectve;
+/// CodeView debug information definitions and helper functions
+pub mod codeview;
+/// COFF object file definitions and helper functions
+pub mod object;
+/// Short import object definitions and helper functions
+pub mod import;
+/// Archive (.lib) definitions and helper functions
+pub mod archive;
+/// Export table (.edata) definitions and helper functions
+pub mod export;
+/// Module-definition (.def) file parsing and writing
+pub mod module_definition;
+/// Import library (.lib) generation
+pub mod import_library;
+/// CLI metadata root definitions and helper functions
+pub mod metadata;
+/// Debug directory definitions and helper functions
+pub mod debug;
+/// PDB (MSF) definitions and helper functions
+pub mod pdb;
+/// Load configuration and Control Flow Guard definitions and helper functions
+pub mod load_config;
+/// Dynamic value relocation table definitions and helper functions
+pub mod dvrt;
+/// CHPE, ARM64EC and ARM64X hybrid metadata definitions and helper functions
+pub mod chpe;
+/// SafeSEH handler table (.sxdata) definitions and helper functions
+pub mod safe_seh;
+/// Enclave configuration definitions and helper functions
+pub mod enclave;
+/// Exception table (.pdata) and x64 unwind information definitions and helper functions
+pub mod exception;
+/// MSVC C++ exception handling (FH3 and FH4) definitions and helper functions
+pub mod cxx_eh;
+/// MSVC RTTI definitions and helper functions
+pub mod rtti;
+/// Rich header definitions and helper functions
+pub mod rich;
+/// Terse Executable (TE) definitions and helper functions
+pub mod te;
+/// Compiled resource file (.res) definitions and helper functions
+pub mod resource_file;
+/// NE and LE/LX executable header definitions and helper functions
+pub mod legacy;
+/// File format detection and a single entry point for every
+/// supported format
+pub mod file;
 /// Monolith struct containing all the information
 /// you will ever need
 pub mod pe;
@@ -58,6 +117,34 @@ pub enum Error {
     MissingCoffHeader,
     /// Missing magic number from header.
     MissingMagicNumber,
+    /// Failed to parse a symbol table record.
+    BadSymbol,
+    /// Failed to parse a resource file entry.
+    BadResource,
+    /// Failed to parse an archive member header.
+    BadArchiveMember,
+    /// The input does not match any supported file format.
+    UnrecognizedFormat,
+    /// Failed to parse a module-definition (.def) file.
+    BadModuleDefinition,
+    /// The machine type is not supported by this operation.
+    UnsupportedMachine,
+    /// Failed to parse CodeView debug information.
+    BadCodeView,
+    /// Failed to parse a PDB stream.
+    BadPdb,
+    /// Failed to decompress data.
+    BadCompressedData,
+    /// Failed to parse a dynamic value relocation.
+    BadDynamicRelocation,
+    /// Failed to parse unwind info.
+    BadUnwindInfo,
+    /// Failed to parse C++ exception handling data.
+    BadFuncInfo,
+    /// Failed to parse RTTI data.
+    BadRtti,
+    /// Failed to parse the Rich header.
+    BadRichHeader,
 }
 
 impl fmt::Display for Error {
@@ -69,6 +156,29 @@ impl fmt::Display for Error {
             Error::MissingPeHeader => f.write_str("Missing PE header!"),
             Error::MissingCoffHeader => f.write_str("Missing COFF header!"),
             Error::MissingMagicNumber => f.write_str("Missing magic number!"),
+            Error::BadSymbol => f.write_str("Failed to parse symbol!"),
+            Error::BadResource => f.write_str("Failed to parse resource!"),
+            Error::BadArchiveMember => f.write_str("Failed to parse archive member!"),
+            Error::BadModuleDefinition => f.write_str("Failed to parse module definition!"),
+            Error::UnsupportedMachine => f.write_str("Unsupported machine type!"),
+            Error::BadCodeView => f.write_str("Failed to parse CodeView debug information!"),
+            Error::BadPdb => f.write_str("Failed to parse PDB!"),
+            Error::BadCompressedData => f.write_str("Failed to decompress data!"),
+            Error::BadDynamicRelocation => f.write_str("Failed to parse dynamic value relocation!"),
+            Error::BadUnwindInfo => f.write_str("Failed to parse unwind info!"),
+            Error::BadFuncInfo => f.write_str("Failed to parse C++ exception handling data!"),
+            Error::BadRtti => f.write_str("Failed to parse RTTI data!"),
+            Error::BadRichHeader => f.write_str("Failed to parse Rich header!"),
+            Error::UnrecognizedFormat => {
+                f.write_str("Unrecognized file format! Tried: ")?;
+                for (i, format) in file::SUPPORTED_FORMATS.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_fmt(format_args!("{}", format))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,186 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::fmt;
+use crate::{prelude::*, section::SectionHeader, symbol::{AuxSymbol, Symbol}, Error};
+
+/// Size in bytes of a COFF line number record
+pub const LINE_NUMBER_SIZE: usize = 6;
+
+/// Parse the COFF line numbers of a section, as described by
+/// `pointer_to_line_numbers` and `number_of_line_numbers` in its header.
+pub fn parse_line_numbers(binary: &[u8], section: &SectionHeader) -> Result<Vec<LineNumber>, Error> {
+    let mut line_numbers: Vec<LineNumber> = Vec::new();
+    let mut offset = section.pointer_to_line_numbers as usize;
+
+    if offset == 0 {
+        return Ok(line_numbers);
+    }
+
+    for _ in 0..section.number_of_line_numbers {
+        let slice = match binary.get(offset..offset+LINE_NUMBER_SIZE) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        match try_pod_read_unaligned::<LineNumber>(slice) {
+            Ok(line_number) => line_numbers.push(line_number),
+            Err(_) => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+        offset += LINE_NUMBER_SIZE;
+    }
+
+    Ok(line_numbers)
+}
+
+/// Join line number records with the symbol table to produce
+/// absolute line numbers for each address.
+/// Records are grouped by function: a record with a line number of zero
+/// names the function symbol, and the following records give line numbers
+/// relative to the line of the function's `.bf` symbol.
+/// Function symbols hold section-relative values, so the containing
+/// section's `virtual_address` is added to put function starts in the
+/// same address space as the line records (RVAs in images).
+pub fn map_line_numbers(line_numbers: &[LineNumber], symbol_table: &[Symbol], section_table: &[SectionHeader]) -> Vec<LineMapping> {
+    let mut mappings: Vec<LineMapping> = Vec::new();
+    let mut function: Option<(u32, u32)> = None;
+
+    for line_number in line_numbers.iter() {
+        if let Some(index) = line_number.get_symbol_table_index() {
+            let symbol = find_symbol(symbol_table, index);
+            let line_base = symbol.and_then(|symbol| get_function_line_base(symbol, symbol_table));
+            function = Some((index, line_base.unwrap_or(1)));
+
+            // The function record itself maps the function start to its first line
+            let start = symbol.and_then(|symbol| get_function_address(symbol, section_table));
+            if let (Some(start), Some(line_base)) = (start, line_base) {
+                mappings.push(LineMapping {
+                    function_symbol_index: index,
+                    virtual_address: start,
+                    line_number: line_base,
+                });
+            }
+            continue;
+        }
+
+        let Some((index, line_base)) = function else {
+            continue;
+        };
+
+        let mapping = LineMapping {
+            function_symbol_index: index,
+            virtual_address: line_number.address,
+            line_number: line_base + line_number.line_number as u32 - 1,
+        };
+
+        // An explicit record for the function start replaces the implied one
+        match mappings.last_mut() {
+            Some(last) if last.function_symbol_index == index && last.virtual_address == mapping.virtual_address => {
+                *last = mapping;
+            }
+            _ => mappings.push(mapping),
+        }
+    }
+
+    mappings
+}
+
+/// Find the line covering `address`: the mapping with the highest
+/// address that does not exceed it.
+pub fn find_line(mappings: &[LineMapping], address: u32) -> Option<&LineMapping> {
+    mappings.iter()
+        .filter(|mapping| mapping.virtual_address <= address)
+        .max_by_key(|mapping| mapping.virtual_address)
+}
+
+/// Find the source line of the `.bf` symbol belonging to a function symbol
+fn get_function_line_base(function: &Symbol, symbol_table: &[Symbol]) -> Option<u32> {
+    let tag_index = function.aux_symbols.iter().find_map(|aux| match aux {
+        AuxSymbol::FunctionDefinition(definition) => Some(definition.tag_index),
+        _ => None,
+    })?;
+
+    let begin_function = find_symbol(symbol_table, tag_index)?;
+    begin_function.aux_symbols.iter().find_map(|aux| match aux {
+        AuxSymbol::BeginEndFunction(begin_end) => Some(begin_end.line_number as u32),
+        _ => None,
+    })
+}
+
+/// Find the address of a function symbol, in the address space of line records
+fn get_function_address(function: &Symbol, section_table: &[SectionHeader]) -> Option<u32> {
+    let section = usize::try_from(function.section_number).ok()?.checked_sub(1)?;
+    section_table.get(section)?.virtual_address.checked_add(function.value)
+}
+
+/// Find a symbol by its symbol table index; records are in index order
+fn find_symbol(symbol_table: &[Symbol], index: u32) -> Option<&Symbol> {
+    match symbol_table.binary_search_by_key(&index, |symbol| symbol.index) {
+        Ok(position) => symbol_table.get(position),
+        Err(_) => None,
+    }
+}
+
+/// COFF Line Number record (deprecated)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct LineNumber {
+    /// When `line_number` is zero, the symbol table index of the function.
+    /// Otherwise, the RVA of the executable code that corresponds to the source line.
+    /// In an object file, this contains the VA within the section.
+    pub address: u32,
+    /// When nonzero, this field specifies a one-based line number, relative to the start of the function.
+    /// When zero, `address` is interpreted as a symbol table index for a function.
+    pub line_number: u16
+}
+
+impl fmt::Display for LineNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (address, line_number) = (self.address, self.line_number);
+
+        match self.get_symbol_table_index() {
+            Some(index) => writeln!(f, "Function Symbol Index:   {}", index),
+            None => writeln!(f, "{:#010x}:              {}", address, line_number),
+        }
+    }
+}
+
+impl LineNumber {
+    /// Returns the function symbol table index if this record starts a function
+    pub fn get_symbol_table_index(&self) -> Option<u32> {
+        if self.line_number == 0 {
+            Some(self.address)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the address of the code for this line,
+    /// or `None` if this record starts a function
+    pub fn get_virtual_address(&self) -> Option<u32> {
+        if self.line_number != 0 {
+            Some(self.address)
+        } else {
+            None
+        }
+    }
+}
+
+/// An address mapped to an absolute source line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineMapping {
+    /// Symbol table index of the function containing the address.
+    pub function_symbol_index: u32,
+    /// RVA (or section-relative VA in an object file) of the code.
+    pub virtual_address: u32,
+    /// Absolute one-based source line number.
+    pub line_number: u32
+}
+
+impl fmt::Display for LineMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010x} -> line {} (function symbol {})", self.virtual_address, self.line_number, self.function_symbol_index)
+    }
+}
@@ -0,0 +1,588 @@
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::{fmt, str};
+use crate::{pe::PortableExecutable, prelude::*, Error};
+
+/// Code Integrity information in the load configuration
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct CodeIntegrity {
+    /// Flags to indicate if CI information is available, etc.
+    pub flags: u16,
+    /// 0xFFFF means not available.
+    pub catalog: u16,
+    /// Offset of the catalog.
+    pub catalog_offset: u32,
+    /// Additional bitmask to be defined later.
+    pub reserved: u32
+}
+
+/// PE32 Load Configuration Structure (Image Only)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct LoadConfigDirectory32 {
+    /// Size of the structure, used as a version.
+    pub size: u32,
+    /// Date and time stamp value.
+    pub time_date_stamp: u32,
+    /// Major version number.
+    pub major_version: u16,
+    /// Minor version number.
+    pub minor_version: u16,
+    /// The global loader flags to clear for this process as the loader starts the process.
+    pub global_flags_clear: u32,
+    /// The global loader flags to set for this process as the loader starts the process.
+    pub global_flags_set: u32,
+    /// The default timeout value to use for this process's critical sections that are abandoned.
+    pub critical_section_default_timeout: u32,
+    /// Memory that must be freed before it is returned to the system, in bytes.
+    pub de_commit_free_block_threshold: u32,
+    /// Total amount of free memory, in bytes.
+    pub de_commit_total_free_threshold: u32,
+    /// The VA of a list of addresses where the LOCK prefix is used.
+    pub lock_prefix_table: u32,
+    /// Maximum allocation size, in bytes.
+    pub maximum_allocation_size: u32,
+    /// Maximum virtual memory size, in bytes.
+    pub virtual_memory_threshold: u32,
+    /// Process heap flags that correspond to the first argument of the HeapCreate function.
+    pub process_heap_flags: u32,
+    /// Setting this field to a non-zero value is equivalent to calling SetProcessAffinityMask.
+    pub process_affinity_mask: u32,
+    /// The service pack version identifier.
+    pub csd_version: u16,
+    /// Default load flags used when the operating system resolves the statically linked imports of a module.
+    pub dependent_load_flags: u16,
+    /// Reserved for use by the system.
+    pub edit_list: u32,
+    /// A pointer to a cookie that is used by Visual C++ or GS implementation.
+    pub security_cookie: u32,
+    /// The VA of the sorted table of RVAs of each valid, unique SE handler in the image.
+    pub se_handler_table: u32,
+    /// The count of unique handlers in the table.
+    pub se_handler_count: u32,
+    /// The VA where Control Flow Guard check-function pointer is stored.
+    pub guard_cf_check_function_pointer: u32,
+    /// The VA where Control Flow Guard dispatch-function pointer is stored.
+    pub guard_cf_dispatch_function_pointer: u32,
+    /// The VA of the sorted table of RVAs of each Control Flow Guard function in the image.
+    pub guard_cf_function_table: u32,
+    /// The count of unique RVAs in the above table.
+    pub guard_cf_function_count: u32,
+    /// Control Flow Guard related flags.
+    pub guard_flags: u32,
+    /// Code integrity information.
+    pub code_integrity: CodeIntegrity,
+    /// The VA where Control Flow Guard address taken IAT table is stored.
+    pub guard_address_taken_iat_entry_table: u32,
+    /// The count of unique RVAs in the above table.
+    pub guard_address_taken_iat_entry_count: u32,
+    /// The VA where Control Flow Guard long jump target table is stored.
+    pub guard_long_jump_target_table: u32,
+    /// The count of unique RVAs in the above table.
+    pub guard_long_jump_target_count: u32,
+    /// The VA of the dynamic value relocation table.
+    pub dynamic_value_reloc_table: u32,
+    /// The VA of the hybrid (CHPE) metadata.
+    pub chpe_metadata_pointer: u32,
+    /// The VA of the failure routine.
+    pub guard_rf_failure_routine: u32,
+    /// The VA of the failure routine function pointer.
+    pub guard_rf_failure_routine_function_pointer: u32,
+    /// The offset of the dynamic value relocation table within its section.
+    pub dynamic_value_reloc_table_offset: u32,
+    /// The one-based section holding the dynamic value relocation table.
+    pub dynamic_value_reloc_table_section: u16,
+    /// Must be zero.
+    pub reserved2: u16,
+    /// The VA of the stack pointer verification function pointer.
+    pub guard_rf_verify_stack_pointer_function_pointer: u32,
+    /// The offset of the hot patch table.
+    pub hot_patch_table_offset: u32,
+    /// Must be zero.
+    pub reserved3: u32,
+    /// The VA of the enclave configuration.
+    pub enclave_configuration_pointer: u32,
+    /// The VA of the volatile metadata.
+    pub volatile_metadata_pointer: u32,
+    /// The VA of the sorted table of EH continuation target RVAs.
+    pub guard_eh_continuation_table: u32,
+    /// The count of unique RVAs in the above table.
+    pub guard_eh_continuation_count: u32,
+    /// The VA where the XFG check-function pointer is stored.
+    pub guard_xfg_check_function_pointer: u32,
+    /// The VA where the XFG dispatch-function pointer is stored.
+    pub guard_xfg_dispatch_function_pointer: u32,
+    /// The VA where the XFG table dispatch-function pointer is stored.
+    pub guard_xfg_table_dispatch_function_pointer: u32,
+    /// The VA of the CastGuard failure mode.
+    pub cast_guard_os_determined_failure_mode: u32,
+    /// The VA where the guarded memcpy function pointer is stored.
+    pub guard_memcpy_function_pointer: u32
+}
+
+/// PE32+ Load Configuration Structure (Image Only)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct LoadConfigDirectory64 {
+    /// Size of the structure, used as a version.
+    pub size: u32,
+    /// Date and time stamp value.
+    pub time_date_stamp: u32,
+    /// Major version number.
+    pub major_version: u16,
+    /// Minor version number.
+    pub minor_version: u16,
+    /// The global loader flags to clear for this process as the loader starts the process.
+    pub global_flags_clear: u32,
+    /// The global loader flags to set for this process as the loader starts the process.
+    pub global_flags_set: u32,
+    /// The default timeout value to use for this process's critical sections that are abandoned.
+    pub critical_section_default_timeout: u32,
+    /// Memory that must be freed before it is returned to the system, in bytes.
+    pub de_commit_free_block_threshold: u64,
+    /// Total amount of free memory, in bytes.
+    pub de_commit_total_free_threshold: u64,
+    /// The VA of a list of addresses where the LOCK prefix is used.
+    pub lock_prefix_table: u64,
+    /// Maximum allocation size, in bytes.
+    pub maximum_allocation_size: u64,
+    /// Maximum virtual memory size, in bytes.
+    pub virtual_memory_threshold: u64,
+    /// Setting this field to a non-zero value is equivalent to calling SetProcessAffinityMask.
+    pub process_affinity_mask: u64,
+    /// Process heap flags that correspond to the first argument of the HeapCreate function.
+    pub process_heap_flags: u32,
+    /// The service pack version identifier.
+    pub csd_version: u16,
+    /// Default load flags used when the operating system resolves the statically linked imports of a module.
+    pub dependent_load_flags: u16,
+    /// Reserved for use by the system.
+    pub edit_list: u64,
+    /// A pointer to a cookie that is used by Visual C++ or GS implementation.
+    pub security_cookie: u64,
+    /// The VA of the sorted table of RVAs of each valid, unique SE handler in the image.
+    pub se_handler_table: u64,
+    /// The count of unique handlers in the table.
+    pub se_handler_count: u64,
+    /// The VA where Control Flow Guard check-function pointer is stored.
+    pub guard_cf_check_function_pointer: u64,
+    /// The VA where Control Flow Guard dispatch-function pointer is stored.
+    pub guard_cf_dispatch_function_pointer: u64,
+    /// The VA of the sorted table of RVAs of each Control Flow Guard function in the image.
+    pub guard_cf_function_table: u64,
+    /// The count of unique RVAs in the above table.
+    pub guard_cf_function_count: u64,
+    /// Control Flow Guard related flags.
+    pub guard_flags: u32,
+    /// Code integrity information.
+    pub code_integrity: CodeIntegrity,
+    /// The VA where Control Flow Guard address taken IAT table is stored.
+    pub guard_address_taken_iat_entry_table: u64,
+    /// The count of unique RVAs in the above table.
+    pub guard_address_taken_iat_entry_count: u64,
+    /// The VA where Control Flow Guard long jump target table is stored.
+    pub guard_long_jump_target_table: u64,
+    /// The count of unique RVAs in the above table.
+    pub guard_long_jump_target_count: u64,
+    /// The VA of the dynamic value relocation table.
+    pub dynamic_value_reloc_table: u64,
+    /// The VA of the hybrid (CHPE) metadata.
+    pub chpe_metadata_pointer: u64,
+    /// The VA of the failure routine.
+    pub guard_rf_failure_routine: u64,
+    /// The VA of the failure routine function pointer.
+    pub guard_rf_failure_routine_function_pointer: u64,
+    /// The offset of the dynamic value relocation table within its section.
+    pub dynamic_value_reloc_table_offset: u32,
+    /// The one-based section holding the dynamic value relocation table.
+    pub dynamic_value_reloc_table_section: u16,
+    /// Must be zero.
+    pub reserved2: u16,
+    /// The VA of the stack pointer verification function pointer.
+    pub guard_rf_verify_stack_pointer_function_pointer: u64,
+    /// The offset of the hot patch table.
+    pub hot_patch_table_offset: u32,
+    /// Must be zero.
+    pub reserved3: u32,
+    /// The VA of the enclave configuration.
+    pub enclave_configuration_pointer: u64,
+    /// The VA of the volatile metadata.
+    pub volatile_metadata_pointer: u64,
+    /// The VA of the sorted table of EH continuation target RVAs.
+    pub guard_eh_continuation_table: u64,
+    /// The count of unique RVAs in the above table.
+    pub guard_eh_continuation_count: u64,
+    /// The VA where the XFG check-function pointer is stored.
+    pub guard_xfg_check_function_pointer: u64,
+    /// The VA where the XFG dispatch-function pointer is stored.
+    pub guard_xfg_dispatch_function_pointer: u64,
+    /// The VA where the XFG table dispatch-function pointer is stored.
+    pub guard_xfg_table_dispatch_function_pointer: u64,
+    /// The VA of the CastGuard failure mode.
+    pub cast_guard_os_determined_failure_mode: u64,
+    /// The VA where the guarded memcpy function pointer is stored.
+    pub guard_memcpy_function_pointer: u64
+}
+
+impl From<LoadConfigDirectory32> for LoadConfigDirectory64 {
+    fn from(config: LoadConfigDirectory32) -> Self {
+        LoadConfigDirectory64 {
+            size: config.size,
+            time_date_stamp: config.time_date_stamp,
+            major_version: config.major_version,
+            minor_version: config.minor_version,
+            global_flags_clear: config.global_flags_clear,
+            global_flags_set: config.global_flags_set,
+            critical_section_default_timeout: config.critical_section_default_timeout,
+            de_commit_free_block_threshold: config.de_commit_free_block_threshold.into(),
+            de_commit_total_free_threshold: config.de_commit_total_free_threshold.into(),
+            lock_prefix_table: config.lock_prefix_table.into(),
+            maximum_allocation_size: config.maximum_allocation_size.into(),
+            virtual_memory_threshold: config.virtual_memory_threshold.into(),
+            process_affinity_mask: config.process_affinity_mask.into(),
+            process_heap_flags: config.process_heap_flags,
+            csd_version: config.csd_version,
+            dependent_load_flags: config.dependent_load_flags,
+            edit_list: config.edit_list.into(),
+            security_cookie: config.security_cookie.into(),
+            se_handler_table: config.se_handler_table.into(),
+            se_handler_count: config.se_handler_count.into(),
+            guard_cf_check_function_pointer: config.guard_cf_check_function_pointer.into(),
+            guard_cf_dispatch_function_pointer: config.guard_cf_dispatch_function_pointer.into(),
+            guard_cf_function_table: config.guard_cf_function_table.into(),
+            guard_cf_function_count: config.guard_cf_function_count.into(),
+            guard_flags: config.guard_flags,
+            code_integrity: config.code_integrity,
+            guard_address_taken_iat_entry_table: config.guard_address_taken_iat_entry_table.into(),
+            guard_address_taken_iat_entry_count: config.guard_address_taken_iat_entry_count.into(),
+            guard_long_jump_target_table: config.guard_long_jump_target_table.into(),
+            guard_long_jump_target_count: config.guard_long_jump_target_count.into(),
+            dynamic_value_reloc_table: config.dynamic_value_reloc_table.into(),
+            chpe_metadata_pointer: config.chpe_metadata_pointer.into(),
+            guard_rf_failure_routine: config.guard_rf_failure_routine.into(),
+            guard_rf_failure_routine_function_pointer: config.guard_rf_failure_routine_function_pointer.into(),
+            dynamic_value_reloc_table_offset: config.dynamic_value_reloc_table_offset,
+            dynamic_value_reloc_table_section: config.dynamic_value_reloc_table_section,
+            reserved2: config.reserved2,
+            guard_rf_verify_stack_pointer_function_pointer: config.guard_rf_verify_stack_pointer_function_pointer.into(),
+            hot_patch_table_offset: config.hot_patch_table_offset,
+            reserved3: config.reserved3,
+            enclave_configuration_pointer: config.enclave_configuration_pointer.into(),
+            volatile_metadata_pointer: config.volatile_metadata_pointer.into(),
+            guard_eh_continuation_table: config.guard_eh_continuation_table.into(),
+            guard_eh_continuation_count: config.guard_eh_continuation_count.into(),
+            guard_xfg_check_function_pointer: config.guard_xfg_check_function_pointer.into(),
+            guard_xfg_dispatch_function_pointer: config.guard_xfg_dispatch_function_pointer.into(),
+            guard_xfg_table_dispatch_function_pointer: config.guard_xfg_table_dispatch_function_pointer.into(),
+            cast_guard_os_determined_failure_mode: config.cast_guard_os_determined_failure_mode.into(),
+            guard_memcpy_function_pointer: config.guard_memcpy_function_pointer.into(),
+        }
+    }
+}
+
+/// Read a structure that may be truncated, as older linkers write
+/// shorter versions; fields past `size` read as zero
+pub(crate) fn read_versioned<T: Pod>(data: &[u8], size: usize) -> Result<T, Error> {
+    let mut buffer = vec![0u8; size_of::<T>()];
+    let length = size.min(size_of::<T>()).min(data.len());
+    buffer[..length].copy_from_slice(&data[..length]);
+
+    match try_pod_read_unaligned::<T>(&buffer) {
+        Ok(value) => Ok(value),
+        Err(_) => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Parse the load configuration structure of an image.
+/// PE32 structures are widened to the PE32+ layout, and fields
+/// beyond the structure's size are zero.
+/// Returns `None` if the image has no load configuration.
+pub fn parse_load_config(binary: &[u8], pe: &PortableExecutable) -> Result<Option<LoadConfigDirectory64>, Error> {
+    let data_directory = match pe.get_data_directories() {
+        Some(data_directories) => data_directories.load_config_table,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    if data_directory.virtual_address == 0 {
+        return Ok(None);
+    }
+
+    let data = match pe.rva_to_offset(data_directory.virtual_address).and_then(|offset| binary.get(offset..)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let size = match data.get(0..4) {
+        Some(size) => u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if pe.is_64_bit() {
+        Ok(Some(read_versioned::<LoadConfigDirectory64>(data, size)?))
+    } else {
+        Ok(Some(read_versioned::<LoadConfigDirectory32>(data, size)?.into()))
+    }
+}
+
+impl LoadConfigDirectory64 {
+    /// Returns the Control Flow Guard flags as bitflags.
+    /// Unknown bits, including the table stride, are retained.
+    pub fn get_guard_flags(&self) -> GuardFlags {
+        GuardFlags::from_bits_retain(self.guard_flags)
+    }
+
+    /// Returns the number of metadata bytes following each
+    /// RVA in the Control Flow Guard tables
+    pub fn get_guard_table_stride(&self) -> usize {
+        ((self.guard_flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK) >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT) as usize
+    }
+}
+
+impl fmt::Display for LoadConfigDirectory64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Load Config")?;
+        writeln!(f, "-----------")?;
+        writeln!(f, "Size:                    {}", self.size)?;
+        writeln!(f, "Time Date Stamp:         {}", self.time_date_stamp)?;
+        writeln!(f, "Version:                 {}.{}", self.major_version, self.minor_version)?;
+        writeln!(f, "Global Flags Clear:      {:#010x}", self.global_flags_clear)?;
+        writeln!(f, "Global Flags Set:        {:#010x}", self.global_flags_set)?;
+        writeln!(f, "Security Cookie:         {:#018x}", self.security_cookie)?;
+        writeln!(f, "SE Handler Table:        {:#018x} ({})", self.se_handler_table, self.se_handler_count)?;
+        writeln!(f, "Guard CF Check:          {:#018x}", self.guard_cf_check_function_pointer)?;
+        writeln!(f, "Guard CF Dispatch:       {:#018x}", self.guard_cf_dispatch_function_pointer)?;
+        writeln!(f, "Guard CF Functions:      {:#018x} ({})", self.guard_cf_function_table, self.guard_cf_function_count)?;
+        writeln!(f, "Guard Flags:             {}", self.get_guard_flags())?;
+        writeln!(f, "Guard IAT Table:         {:#018x} ({})", self.guard_address_taken_iat_entry_table, self.guard_address_taken_iat_entry_count)?;
+        writeln!(f, "Guard Long Jump Table:   {:#018x} ({})", self.guard_long_jump_target_table, self.guard_long_jump_target_count)?;
+        writeln!(f, "Guard EH Cont. Table:    {:#018x} ({})", self.guard_eh_continuation_table, self.guard_eh_continuation_count)?;
+        writeln!(f, "Dynamic Value Relocs:    {:#018x}", self.dynamic_value_reloc_table)?;
+        writeln!(f, "CHPE Metadata:           {:#018x}", self.chpe_metadata_pointer)?;
+        writeln!(f, "Enclave Configuration:   {:#018x}", self.enclave_configuration_pointer)?;
+        writeln!(f, "Volatile Metadata:       {:#018x}", self.volatile_metadata_pointer)?;
+        writeln!(f, "Guard XFG Check:         {:#018x}", self.guard_xfg_check_function_pointer)?;
+        writeln!(f, "Guard XFG Dispatch:      {:#018x}", self.guard_xfg_dispatch_function_pointer)?;
+
+        Ok(())
+    }
+}
+
+/// Mask of the Control Flow Guard flags giving the table stride
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xf0000000;
+/// Shift of the table stride in the Control Flow Guard flags
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+
+bitflags! {
+    /// Control Flow Guard related flags from the load configuration
+    pub struct GuardFlags: u32 {
+        /// Module performs control flow integrity checks using system-supplied support.
+        const IMAGE_GUARD_CF_INSTRUMENTED = 0x00000100;
+        /// Module performs control flow and write integrity checks.
+        const IMAGE_GUARD_CFW_INSTRUMENTED = 0x00000200;
+        /// Module contains valid control flow target metadata.
+        const IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT = 0x00000400;
+        /// Module does not make use of the /GS security cookie.
+        const IMAGE_GUARD_SECURITY_COOKIE_UNUSED = 0x00000800;
+        /// Module supports read only delay load IAT.
+        const IMAGE_GUARD_PROTECT_DELAYLOAD_IAT = 0x00001000;
+        /// Delayload import table in its own .didat section (with nothing else in it) that can be freely reprotected.
+        const IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION = 0x00002000;
+        /// Module contains suppressed export information.
+        const IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT = 0x00004000;
+        /// Module enables suppression of exports.
+        const IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION = 0x00008000;
+        /// Module contains longjmp target information.
+        const IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT = 0x00010000;
+        /// Module contains return flow instrumentation and metadata.
+        const IMAGE_GUARD_RF_INSTRUMENTED = 0x00020000;
+        /// Module requests that the OS enable return flow protection.
+        const IMAGE_GUARD_RF_ENABLE = 0x00040000;
+        /// Module requests that the OS enable return flow protection in strict mode.
+        const IMAGE_GUARD_RF_STRICT = 0x00080000;
+        /// Module was built with retpoline support.
+        const IMAGE_GUARD_RETPOLINE_PRESENT = 0x00100000;
+        /// Module contains EH continuation target information.
+        const IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT = 0x00400000;
+        /// Module was built with XFG.
+        const IMAGE_GUARD_XFG_ENABLED = 0x00800000;
+        /// Module has CastGuard instrumentation present.
+        const IMAGE_GUARD_CASTGUARD_PRESENT = 0x01000000;
+        /// Module has guarded memcpy instrumentation present.
+        const IMAGE_GUARD_MEMCPY_PRESENT = 0x02000000;
+    }
+}
+
+// Allow Guard flags to be easily printed
+impl fmt::Debug for GuardFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for GuardFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for GuardFlags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+bitflags! {
+    /// Flags in the first metadata byte of a Control Flow Guard table entry
+    pub struct GuardFunctionFlags: u8 {
+        /// The call target is explicitly suppressed (do not treat it as valid for purposes of CFG).
+        const IMAGE_GUARD_FLAG_FID_SUPPRESSED = 0x01;
+        /// The call target is export suppressed.
+        const IMAGE_GUARD_FLAG_EXPORT_SUPPRESSED = 0x02;
+        /// The call target is a language exception handler.
+        const IMAGE_GUARD_FLAG_FID_LANGEXCPTHANDLER = 0x04;
+        /// The call target is XFG-enabled.
+        const IMAGE_GUARD_FLAG_FID_XFG = 0x08;
+    }
+}
+
+// Allow Guard function flags to be easily printed
+impl fmt::Debug for GuardFunctionFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for GuardFunctionFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for GuardFunctionFlags {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// An entry of a Control Flow Guard table
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GuardTableEntry {
+    /// The RVA of the target.
+    pub rva: u32,
+    /// The first metadata byte, or 0 if the table has no metadata.
+    pub metadata: u8
+}
+
+impl GuardTableEntry {
+    /// Returns the metadata flags as bitflags
+    pub fn get_flags(&self) -> GuardFunctionFlags {
+        GuardFunctionFlags::from_bits_retain(self.metadata)
+    }
+}
+
+/// The Control Flow Guard tables of an image
+#[derive(Clone, Debug, Default)]
+pub struct GuardCfTables {
+    /// Valid indirect call targets.
+    pub functions: Vec<GuardTableEntry>,
+    /// IAT entries whose address is taken.
+    pub address_taken_iat_entries: Vec<GuardTableEntry>,
+    /// Valid `longjmp` targets.
+    pub long_jump_targets: Vec<GuardTableEntry>,
+    /// Valid EH continuation targets.
+    pub eh_continuation_targets: Vec<GuardTableEntry>
+}
+
+/// Parse the Control Flow Guard tables of an image.
+/// Each entry is an RVA followed by the number of metadata bytes
+/// given by the guard flags' table stride.
+pub fn parse_guard_cf_tables(binary: &[u8], pe: &PortableExecutable, config: &LoadConfigDirectory64) -> Result<GuardCfTables, Error> {
+    let stride = config.get_guard_table_stride();
+
+    Ok(GuardCfTables {
+        functions: parse_guard_table(binary, pe, config.guard_cf_function_table, config.guard_cf_function_count, stride)?,
+        address_taken_iat_entries: parse_guard_table(binary, pe, config.guard_address_taken_iat_entry_table, config.guard_address_taken_iat_entry_count, stride)?,
+        long_jump_targets: parse_guard_table(binary, pe, config.guard_long_jump_target_table, config.guard_long_jump_target_count, stride)?,
+        eh_continuation_targets: parse_guard_table(binary, pe, config.guard_eh_continuation_table, config.guard_eh_continuation_count, stride)?,
+    })
+}
+
+fn parse_guard_table(binary: &[u8], pe: &PortableExecutable, va: u64, count: u64, stride: usize) -> Result<Vec<GuardTableEntry>, Error> {
+    let mut entries: Vec<GuardTableEntry> = Vec::new();
+    if va == 0 || count == 0 {
+        return Ok(entries);
+    }
+
+    let offset = match pe.va_to_rva(va).and_then(|rva| pe.rva_to_offset(rva)) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let entry_size = 4 + stride;
+    let table = match usize::try_from(count).ok().and_then(|count| count.checked_mul(entry_size)).and_then(|size| binary.get(offset..offset.checked_add(size)?)) {
+        Some(table) => table,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    for entry in table.chunks_exact(entry_size) {
+        entries.push(GuardTableEntry {
+            rva: u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+            metadata: entry.get(4).copied().unwrap_or(0),
+        });
+    }
+
+    Ok(entries)
+}
+
+impl GuardCfTables {
+    /// Returns true if every table is empty
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.address_taken_iat_entries.is_empty()
+            && self.long_jump_targets.is_empty() && self.eh_continuation_targets.is_empty()
+    }
+}
+
+impl fmt::Display for GuardCfTables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tables = [
+            ("Guard CF Functions", &self.functions),
+            ("Guard Address Taken IAT Entries", &self.address_taken_iat_entries),
+            ("Guard Long Jump Targets", &self.long_jump_targets),
+            ("Guard EH Continuation Targets", &self.eh_continuation_targets),
+        ];
+
+        for (title, entries) in tables {
+            if entries.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "{}", title)?;
+            writeln!(f, "{}", "-".repeat(title.len()))?;
+            for entry in entries.iter() {
+                if entry.metadata != 0 {
+                    writeln!(f, "{:#010x} {}", entry.rva, entry.get_flags())?;
+                } else {
+                    writeln!(f, "{:#010x}", entry.rva)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
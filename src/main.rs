@@ -1,18 +1,26 @@
 use core::env;
 use std::fs;
-use pe_parser::pe::parse_portable_executable;
+use pe_parser::{debug::{DebugType, get_codeview, parse_debug_directories, parse_ex_dll_characteristics, parse_fpo_data, parse_pdb_checksum, parse_pogo, parse_repro, parse_vc_feature}, export::parse_export_table, chpe::parse_chpe_metadata, cxx_eh::{get_type_name, parse_cxx_eh_data}, dvrt::parse_dynamic_relocation_table, enclave::{parse_enclave_config, parse_enclave_imports}, exception::{find_runtime_function, parse_exception_table, parse_unwind_info}, file::{ParsedFile, parse_file}, load_config::{parse_guard_cf_tables, parse_load_config}, module_definition::ModuleDefinition, safe_seh::{SafeSehStatus, get_safe_seh_status, parse_se_handler_table}, pdb::{is_pdb, parse_pdb}, rich::parse_rich_header, rtti::find_vtables};
+#[cfg(feature = "miniz_oxide")]
+use pe_parser::debug::parse_embedded_portable_pdb;
+#[cfg(feature = "sha2")]
+use pe_parser::debug::get_pdb_checksums;
 use clap::{Arg, command, ArgAction};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = command!()
         .arg(Arg::new("file")
             .action(ArgAction::Set)
-            .help("The PE binary to parse"))
+            .help("The PE binary, object, library or other Windows build artifact to parse"))
         .arg(Arg::new("all")
             .short('a')
             .long("all")
             .action(ArgAction::SetTrue)
             .help("Print all sections of PE"))
+        .arg(Arg::new("dos")
+            .long("dos")
+            .action(ArgAction::SetTrue)
+            .help("Print DOS header and stub size"))
         .arg(Arg::new("coff")
             .short('c')
             .long("coff")
@@ -28,6 +36,61 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .long("section")
             .action(ArgAction::SetTrue)
             .help("Print section table"))
+        .arg(Arg::new("symbols")
+            .short('y')
+            .long("symbols")
+            .action(ArgAction::SetTrue)
+            .help("Print COFF symbol table (if present)"))
+        .arg(Arg::new("exports")
+            .short('e')
+            .long("exports")
+            .action(ArgAction::SetTrue)
+            .help("Print export table (if present)"))
+        .arg(Arg::new("def")
+            .long("def")
+            .action(ArgAction::SetTrue)
+            .help("Print a module-definition (.def) file describing the exports"))
+        .arg(Arg::new("rich")
+            .short('r')
+            .long("rich")
+            .action(ArgAction::SetTrue)
+            .help("Print Rich header and the tools that built the image (if present)"))
+        .arg(Arg::new("debug")
+            .short('d')
+            .long("debug")
+            .action(ArgAction::SetTrue)
+            .help("Print debug directory (if present)"))
+        .arg(Arg::new("load-config")
+            .short('l')
+            .long("load-config")
+            .action(ArgAction::SetTrue)
+            .help("Print load configuration, Control Flow Guard, SafeSEH and enclave tables (if present)"))
+        .arg(Arg::new("exceptions")
+            .short('x')
+            .long("exceptions")
+            .action(ArgAction::SetTrue)
+            .help("Print exception table, unwind info and C++ exception handling data (if present)"))
+        .arg(Arg::new("catch")
+            .long("catch")
+            .action(ArgAction::Set)
+            .help("Find the C++ catch handlers covering a hexadecimal RVA"))
+        .arg(Arg::new("rtti")
+            .long("rtti")
+            .action(ArgAction::SetTrue)
+            .help("Print vtables and class hierarchies recovered from MSVC RTTI (if present)"))
+        .arg(Arg::new("pdb")
+            .long("pdb")
+            .action(ArgAction::Set)
+            .help("Check that a PDB matches the image's CodeView record and checksums"))
+        .arg(Arg::new("lookup")
+            .long("lookup")
+            .action(ArgAction::Set)
+            .requires("pdb")
+            .help("Resolve a hexadecimal RVA to its function, file and line using the PDB"))
+        .arg(Arg::new("arm64x")
+            .long("arm64x")
+            .action(ArgAction::Set)
+            .help("Write the alternate view of an ARM64X image, with its dynamic relocations applied, to a file"))
         .get_matches();
 
     match matches.get_one::<String>("file") { 
@@ -39,12 +102,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let binary = fs::read(file)
                 .expect("Failed to read file");
         
-            let pe = parse_portable_executable(binary.as_slice())
-                .expect("Failed to parse Portable Executable!");
+            let parsed = parse_file(binary.as_slice())
+                .expect("Failed to parse file!");
+
+            let pe = match parsed {
+                ParsedFile::Image(pe) => pe,
+                other => {
+                    println!("Format: {}\n", other.get_format());
+                    print!("{}", other);
+                    return Ok(());
+                }
+            };
         
             if matches.get_flag("all") {
                 print!("{}", pe);
             } else {
+                if matches.get_flag("dos") {
+                    print!("{}", pe.dos_header);
+                    if let Some(stub) = pe.dos_header.get_stub(binary.as_slice()) {
+                        println!("DOS Stub Size:           {}", stub.len());
+                    }
+                    println!();
+                }
+
                 if matches.get_flag("coff") {
                     println!("{}", pe.coff);
                 }
@@ -61,7 +141,264 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
                 if matches.get_flag("section") {
                     for section in pe.section_table.iter() {
-                        println!("{}", section);
+                        println!("{}", section.with_string_table(pe.string_table.as_ref()));
+                    }
+                }
+
+                if matches.get_flag("symbols") {
+                    for symbol in pe.symbol_table.iter() {
+                        println!("{}", symbol.with_string_table(pe.string_table.as_ref()));
+                    }
+                }
+
+                if matches.get_flag("exports") || matches.get_flag("def") {
+                    let export_table = parse_export_table(binary.as_slice(), &pe)
+                        .expect("Failed to parse export table!");
+
+                    if let Some(export_table) = export_table {
+                        if matches.get_flag("exports") {
+                            println!("{}", export_table);
+                        }
+
+                        if matches.get_flag("def") {
+                            print!("{}", ModuleDefinition::from_export_table(&export_table, &pe));
+                        }
+                    }
+                }
+
+                if matches.get_flag("rich") {
+                    let rich_header = parse_rich_header(binary.as_slice())
+                        .expect("Failed to parse Rich header!");
+
+                    if let Some(rich_header) = rich_header {
+                        print!("{}", rich_header);
+                        println!("Checksum Valid:          {}\n", rich_header.is_checksum_valid(binary.as_slice()));
+                    }
+                }
+
+                if matches.get_flag("debug") {
+                    let directories = parse_debug_directories(binary.as_slice(), &pe)
+                        .expect("Failed to parse debug directory!");
+
+                    for directory in directories.iter() {
+                        println!("{}", directory);
+
+                        match directory.get_debug_type() {
+                            #[cfg(feature = "miniz_oxide")]
+                            Some(DebugType::EmbeddedPortablePdb) => {
+                                let embedded = parse_embedded_portable_pdb(binary.as_slice(), directory)
+                                    .expect("Failed to decompress embedded portable PDB!");
+                                println!("{}", embedded);
+                            }
+                            Some(DebugType::Fpo) => {
+                                let records = parse_fpo_data(binary.as_slice(), directory)
+                                    .expect("Failed to parse FPO data!");
+                                for record in records.iter() {
+                                    println!("{}", record);
+                                }
+                            }
+                            Some(DebugType::VcFeature) => {
+                                let vc_feature = parse_vc_feature(binary.as_slice(), directory)
+                                    .expect("Failed to parse VC feature counts!");
+                                println!("{}", vc_feature);
+                            }
+                            Some(DebugType::Pogo) => {
+                                let pogo = parse_pogo(binary.as_slice(), directory)
+                                    .expect("Failed to parse POGO data!");
+                                println!("{}", pogo);
+                            }
+                            Some(DebugType::Repro) => {
+                                let repro = parse_repro(binary.as_slice(), directory)
+                                    .expect("Failed to parse repro hash!");
+                                println!("{}", repro);
+                            }
+                            Some(DebugType::ExDllCharacteristics) => {
+                                let characteristics = parse_ex_dll_characteristics(binary.as_slice(), directory)
+                                    .expect("Failed to parse extended DLL characteristics!");
+                                println!("Extended DLL Characteristics: {}\n", characteristics);
+                            }
+                            Some(DebugType::PdbChecksum) => {
+                                let checksum = parse_pdb_checksum(binary.as_slice(), directory)
+                                    .expect("Failed to parse PDB checksum!");
+                                println!("{}", checksum);
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    if let Ok(Some(codeview)) = get_codeview(binary.as_slice(), &pe) {
+                        println!("{}", codeview);
+                    }
+                }
+
+                if matches.get_flag("load-config") {
+                    let load_config = parse_load_config(binary.as_slice(), &pe)
+                        .expect("Failed to parse load configuration!");
+
+                    if let Some(load_config) = load_config {
+                        println!("{}", load_config);
+
+                        let tables = parse_guard_cf_tables(binary.as_slice(), &pe, &load_config)
+                            .expect("Failed to parse Control Flow Guard tables!");
+                        print!("{}", tables);
+
+                        let dvrt = parse_dynamic_relocation_table(binary.as_slice(), &pe, &load_config)
+                            .expect("Failed to parse dynamic value relocation table!");
+                        if let Some(dvrt) = dvrt {
+                            println!("{}", dvrt);
+                        }
+
+                        let chpe = parse_chpe_metadata(binary.as_slice(), &pe, &load_config)
+                            .expect("Failed to parse CHPE metadata!");
+                        if let Some(chpe) = chpe {
+                            println!("{}", chpe);
+                        }
+
+                        let enclave = parse_enclave_config(binary.as_slice(), &pe, &load_config)
+                            .expect("Failed to parse enclave configuration!");
+                        if let Some(enclave) = enclave {
+                            println!("{}", enclave);
+
+                            let imports = parse_enclave_imports(binary.as_slice(), &pe, &enclave)
+                                .expect("Failed to parse enclave imports!");
+                            for import in imports.iter() {
+                                print!("{}", import);
+                                match import.get_name(binary.as_slice(), &pe) {
+                                    Ok(name) => println!("Name:                    {}\n", name),
+                                    Err(_) => println!(),
+                                }
+                            }
+                        }
+                    }
+
+                    let status = get_safe_seh_status(binary.as_slice(), &pe)
+                        .expect("Failed to parse load configuration!");
+                    if status != SafeSehStatus::NotApplicable {
+                        let handlers = parse_se_handler_table(binary.as_slice(), &pe)
+                            .expect("Failed to parse SE handler table!");
+
+                        println!("Safe Exception Handlers");
+                        println!("-----------------------");
+                        println!("Status:                  {}", status);
+                        for handler in handlers.iter() {
+                            println!("Handler:                 {:#010x}", handler);
+                        }
+                        println!();
+                    }
+                }
+
+                if matches.get_flag("exceptions") {
+                    let functions = parse_exception_table(binary.as_slice(), &pe)
+                        .expect("Failed to parse exception table!");
+
+                    for function in functions.iter() {
+                        println!("{}", function);
+
+                        let unwind_info = parse_unwind_info(binary.as_slice(), &pe, function)
+                            .expect("Failed to parse unwind info!");
+                        println!("{}", unwind_info);
+
+                        let func_info = parse_cxx_eh_data(binary.as_slice(), &pe, function, &unwind_info, None)
+                            .expect("Failed to parse C++ exception handling data!");
+                        if let Some(func_info) = func_info {
+                            println!("{}", func_info);
+                        }
+                    }
+                }
+
+                if let Some(catch) = matches.get_one::<String>("catch") {
+                    let rva = u32::from_str_radix(catch.trim_start_matches("0x"), 16)
+                        .expect("Failed to parse RVA!");
+
+                    let functions = parse_exception_table(binary.as_slice(), &pe)
+                        .expect("Failed to parse exception table!");
+
+                    let func_info = match find_runtime_function(&functions, rva) {
+                        Some(function) => {
+                            let unwind_info = parse_unwind_info(binary.as_slice(), &pe, function)
+                                .expect("Failed to parse unwind info!");
+                            parse_cxx_eh_data(binary.as_slice(), &pe, function, &unwind_info, None)
+                                .expect("Failed to parse C++ exception handling data!")
+                        }
+                        None => None,
+                    };
+
+                    match func_info {
+                        Some(func_info) => {
+                            println!("{:#010x}: state {}", rva, func_info.get_state(rva));
+                            for handler in func_info.find_catch_handlers(rva) {
+                                match handler.type_descriptor {
+                                    0 => println!("catch (...) at {:#010x}", handler.handler),
+                                    type_descriptor => println!("catch ({}) at {:#010x}", get_type_name(binary.as_slice(), &pe, type_descriptor)
+                                        .unwrap_or_else(|_| format!("{:#010x}", type_descriptor)), handler.handler),
+                                }
+                            }
+                        }
+                        None => println!("{:#010x}: no C++ exception handling data", rva),
+                    }
+                }
+
+                if matches.get_flag("rtti") {
+                    for vtable in find_vtables(binary.as_slice(), &pe) {
+                        println!("{}", vtable);
+                    }
+                }
+
+                if let Some(path) = matches.get_one::<String>("arm64x") {
+                    let dvrt = parse_load_config(binary.as_slice(), &pe)
+                        .expect("Failed to parse load configuration!")
+                        .and_then(|load_config| parse_dynamic_relocation_table(binary.as_slice(), &pe, &load_config)
+                            .expect("Failed to parse dynamic value relocation table!"));
+
+                    match dvrt {
+                        Some(dvrt) => {
+                            let image = dvrt.apply_arm64x_fixups(binary.as_slice(), &pe)
+                                .expect("Failed to apply ARM64X fixups!");
+                            fs::write(path, image)
+                                .expect("Failed to write alternate view");
+                        }
+                        None => println!("No dynamic value relocation table"),
+                    }
+                }
+
+                if let Some(path) = matches.get_one::<String>("pdb") {
+                    let pdb_binary = fs::read(path)
+                        .expect("Failed to read PDB");
+
+                    #[cfg(feature = "sha2")]
+                    {
+                        let checksums = get_pdb_checksums(binary.as_slice(), &pe)
+                            .expect("Failed to parse PDB checksums!");
+
+                        for checksum in checksums.iter() {
+                            match checksum.verify(pdb_binary.as_slice()) {
+                                Some(true) => println!("PDB {} checksum matches", checksum.algorithm),
+                                Some(false) => println!("PDB {} checksum does not match", checksum.algorithm),
+                                None => println!("PDB {} checksum not supported", checksum.algorithm),
+                            }
+                        }
+                    }
+
+                    if !is_pdb(pdb_binary.as_slice()) {
+                        return Ok(());
+                    }
+
+                    let pdb = parse_pdb(pdb_binary.as_slice())
+                        .expect("Failed to parse PDB!");
+
+                    match get_codeview(binary.as_slice(), &pe) {
+                        Ok(Some(codeview)) if pdb.matches(&codeview) => println!("PDB matches image"),
+                        _ => println!("PDB does not match image"),
+                    }
+
+                    if let Some(lookup) = matches.get_one::<String>("lookup") {
+                        let rva = u32::from_str_radix(lookup.trim_start_matches("0x"), 16)
+                            .expect("Failed to parse RVA!");
+
+                        let location = pdb.symbolize(pdb_binary.as_slice(), rva)
+                            .expect("Failed to symbolize RVA!");
+
+                        println!("{:#010x}: {}", rva, location);
                     }
                 }
             }
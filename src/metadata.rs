@@ -0,0 +1,117 @@
+use core::fmt;
+use crate::{pe::{read_c_string, read_u16, read_u32}, prelude::*, Error};
+
+/// Signature of a CLI metadata root ("BSJB")
+pub const METADATA_SIGNATURE: u32 = 0x424a5342;
+/// Name of the stream holding a portable PDB's id and entry point
+pub const PDB_STREAM_NAME: &str = "#Pdb";
+/// Size of a portable PDB id: a GUID followed by a timestamp
+pub const PORTABLE_PDB_ID_SIZE: usize = 20;
+
+/// Returns true if the binary starts with a CLI metadata root,
+/// as .NET metadata and portable PDBs do
+pub fn is_metadata_root(binary: &[u8]) -> bool {
+    read_u32(binary, 0).ok() == Some(METADATA_SIGNATURE)
+}
+
+/// CLI metadata root (ECMA-335 II.24.2.1)
+#[derive(Clone, Debug)]
+pub struct MetadataRoot {
+    /// Major version, 1.
+    pub major_version: u16,
+    /// Minor version, 1.
+    pub minor_version: u16,
+    /// The runtime version string, such as `v4.0.30319` or `PDB v1.0`.
+    pub version: String,
+    /// Reserved, 0.
+    pub flags: u16,
+    /// Stream headers, in order.
+    pub streams: Vec<MetadataStreamHeader>
+}
+
+/// CLI metadata stream header
+#[derive(Clone, Debug)]
+pub struct MetadataStreamHeader {
+    /// Offset of the stream from the start of the metadata root.
+    pub offset: u32,
+    /// Size of the stream in bytes.
+    pub size: u32,
+    /// The stream name, such as `#~`, `#Strings` or `#Pdb`.
+    pub name: String
+}
+
+/// Parse a CLI metadata root and its stream headers
+pub fn parse_metadata_root(binary: &[u8]) -> Result<MetadataRoot, Error> {
+    if !is_metadata_root(binary) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let length = read_u32(binary, 12)? as usize;
+    let version = match 16usize.checked_add(length).and_then(|end| binary.get(16..end)) {
+        Some(version) => version,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    // The version string is padded with nulls to a 4-byte boundary
+    let version = version.split(|&c| c == 0).next().unwrap_or_default();
+    let mut offset = 16 + length;
+    let mut root = MetadataRoot {
+        major_version: read_u16(binary, 4)?,
+        minor_version: read_u16(binary, 6)?,
+        version: String::from_utf8(version.to_vec()).map_err(Error::BadString)?,
+        flags: read_u16(binary, offset)?,
+        streams: Vec::new(),
+    };
+
+    let number_of_streams = read_u16(binary, offset + 2)?;
+    offset += 4;
+    for _ in 0..number_of_streams {
+        let name = read_c_string(binary, offset + 8)?;
+        root.streams.push(MetadataStreamHeader {
+            offset: read_u32(binary, offset)?,
+            size: read_u32(binary, offset + 4)?,
+            name: name.clone(),
+        });
+        offset += 8 + ((name.len() + 4) & !3);
+    }
+
+    Ok(root)
+}
+
+impl MetadataRoot {
+    /// Find a stream header by name
+    pub fn get_stream_header(&self, name: &str) -> Option<&MetadataStreamHeader> {
+        self.streams.iter().find(|stream| stream.name == name)
+    }
+
+    /// Get the contents of a stream by name
+    pub fn get_stream<'a>(&self, binary: &'a [u8], name: &str) -> Option<&'a [u8]> {
+        let stream = self.get_stream_header(name)?;
+        let start = stream.offset as usize;
+        binary.get(start..start.checked_add(stream.size as usize)?)
+    }
+
+    /// Returns the id of a portable PDB, from the start of its `#Pdb` stream.
+    /// The first 16 bytes match the GUID of the image's CodeView record.
+    pub fn get_portable_pdb_id(&self, binary: &[u8]) -> Option<[u8; PORTABLE_PDB_ID_SIZE]> {
+        let stream = self.get_stream(binary, PDB_STREAM_NAME)?;
+        stream.get(0..PORTABLE_PDB_ID_SIZE)?.try_into().ok()
+    }
+}
+
+impl fmt::Display for MetadataRoot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Metadata Root")?;
+        writeln!(f, "-------------")?;
+        writeln!(f, "Version:                 {}.{}", self.major_version, self.minor_version)?;
+        writeln!(f, "Version String:          {}", self.version)?;
+        writeln!(f, "Flags:                   {:#06x}", self.flags)?;
+        for stream in self.streams.iter() {
+            writeln!(f, "Stream:                  {} ({:#x}, {})", stream.name, stream.offset, stream.size)?;
+        }
+
+        Ok(())
+    }
+}
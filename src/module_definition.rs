@@ -0,0 +1,346 @@
+use core::fmt;
+use crate::{export::ExportTable, pe::PortableExecutable, prelude::*, section::SectionFlags, Error};
+
+/// Statements that end an EXPORTS block
+const KEYWORDS: [&str; 11] = [
+    "NAME", "LIBRARY", "EXPORTS", "IMPORTS", "HEAPSIZE", "STACKSIZE",
+    "SECTIONS", "SEGMENTS", "VERSION", "DESCRIPTION", "STUB",
+];
+
+/// Representation of a module-definition (.def) file.
+/// Only the statements needed to describe a DLL's exports are kept.
+#[derive(Clone, Default)]
+pub struct ModuleDefinition {
+    /// The name of the DLL, from the LIBRARY or NAME statement.
+    /// An extension is added if the statement omitted one.
+    pub library: Option<String>,
+    /// Entries of the EXPORTS statement, in file order.
+    pub exports: Vec<ModuleExport>,
+}
+
+/// An entry of the EXPORTS statement:
+/// `entryname[=internalname] [==exportas] [@ordinal [NONAME]] [PRIVATE] [DATA | CONSTANT]`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModuleExport {
+    /// The name the DLL exports.
+    pub name: String,
+    /// The function the export refers to, either a name within the DLL
+    /// or a forwarder of the form `module.function`.
+    pub internal_name: Option<String>,
+    /// The name the import library asks the loader to look up,
+    /// if it differs from the entry name.
+    pub export_as: Option<String>,
+    /// The export's ordinal, if one was assigned.
+    pub ordinal: Option<u16>,
+    /// The export is by ordinal only; its name is not placed in the export table.
+    pub noname: bool,
+    /// The export is data rather than code.
+    pub data: bool,
+    /// The export is a constant (CONSTANT, obsolete), imported through its IAT entry.
+    pub constant: bool,
+    /// The export is left out of the import library.
+    pub private: bool
+}
+
+impl ModuleExport {
+    /// Returns true if the export forwards to a function in another DLL
+    pub fn is_forwarder(&self) -> bool {
+        self.internal_name.as_ref().is_some_and(|internal_name| internal_name.contains('.'))
+    }
+}
+
+/// Parse a module-definition file.
+/// Statements other than LIBRARY, NAME and EXPORTS are skipped.
+pub fn parse_module_definition(text: &str) -> Result<ModuleDefinition, Error> {
+    let tokens = tokenize(text)?;
+    let mut definition = ModuleDefinition::default();
+    let mut i = 0;
+    let mut in_exports = false;
+
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        i += 1;
+
+        match token {
+            "LIBRARY" | "NAME" => {
+                in_exports = false;
+                if let Some(name) = tokens.get(i).filter(|name| !is_keyword(name)) {
+                    let extension = if token == "LIBRARY" { ".dll" } else { ".exe" };
+                    definition.library = Some(if name.contains('.') {
+                        name.clone()
+                    } else {
+                        format!("{}{}", name, extension)
+                    });
+                    i += 1;
+                }
+            }
+            "EXPORTS" => in_exports = true,
+            _ if is_keyword(token) => in_exports = false,
+            _ if in_exports => {
+                let mut export = ModuleExport {
+                    name: String::from(token),
+                    ..Default::default()
+                };
+
+                if tokens.get(i).map(String::as_str) == Some("=") {
+                    match tokens.get(i + 1) {
+                        Some(internal_name) => export.internal_name = Some(internal_name.clone()),
+                        None => {
+                            return Err(Error::BadModuleDefinition);
+                        }
+                    }
+                    i += 2;
+                }
+
+                if tokens.get(i).map(String::as_str) == Some("==") {
+                    match tokens.get(i + 1) {
+                        Some(export_as) => export.export_as = Some(export_as.clone()),
+                        None => {
+                            return Err(Error::BadModuleDefinition);
+                        }
+                    }
+                    i += 2;
+                }
+
+                while let Some(token) = tokens.get(i) {
+                    match token.as_str() {
+                        "NONAME" => export.noname = true,
+                        "DATA" => export.data = true,
+                        "CONSTANT" => export.constant = true,
+                        "PRIVATE" => export.private = true,
+                        "@" => {
+                            export.ordinal = Some(parse_ordinal(tokens.get(i + 1).map(String::as_str))?);
+                            i += 1;
+                        }
+                        ordinal if ordinal.starts_with('@') => {
+                            export.ordinal = Some(parse_ordinal(Some(&ordinal[1..]))?);
+                        }
+                        _ => break,
+                    }
+                    i += 1;
+                }
+
+                definition.exports.push(export);
+            }
+            // Arguments of statements that are not kept
+            _ => (),
+        }
+    }
+
+    Ok(definition)
+}
+
+fn is_keyword(token: &str) -> bool {
+    KEYWORDS.contains(&token)
+}
+
+fn parse_ordinal(token: Option<&str>) -> Result<u16, Error> {
+    match token.and_then(|token| token.parse().ok()) {
+        Some(ordinal) => Ok(ordinal),
+        None => Err(Error::BadModuleDefinition),
+    }
+}
+
+/// Split a module-definition file into tokens.
+/// Comments start with `;`, `=` and `==` are their own tokens,
+/// commas separate arguments and strings may be quoted.
+fn tokenize(text: &str) -> Result<Vec<String>, Error> {
+    let mut tokens: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let mut chars = line.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                ';' => break,
+                ',' => {
+                    chars.next();
+                }
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '=' => {
+                    chars.next();
+                    if chars.next_if_eq(&'=').is_some() {
+                        tokens.push(String::from("=="));
+                    } else {
+                        tokens.push(String::from("="));
+                    }
+                }
+                '"' => {
+                    chars.next();
+                    let mut token = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => token.push(c),
+                            None => {
+                                return Err(Error::BadModuleDefinition);
+                            }
+                        }
+                    }
+                    tokens.push(token);
+                }
+                _ => {
+                    let mut token = String::new();
+                    while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !matches!(c, '=' | ',' | ';' | '"')) {
+                        token.push(c);
+                    }
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+impl ModuleDefinition {
+    /// Build a module definition describing the exports of a DLL.
+    /// Exports in sections that are not executable are marked as DATA,
+    /// and exports without a name are given the name `OrdinalN` and marked NONAME.
+    pub fn from_export_table(export_table: &ExportTable, pe: &PortableExecutable) -> ModuleDefinition {
+        let mut exports: Vec<ModuleExport> = Vec::new();
+
+        for export in export_table.exports.iter() {
+            let data = export.forwarder.is_none() && pe.section_table.iter().any(|section| {
+                let size = section.virtual_size.max(section.size_of_raw_data);
+                export.address >= section.virtual_address
+                    && export.address - section.virtual_address < size
+                    && !SectionFlags::from_bits_retain(section.characteristics).contains(SectionFlags::IMAGE_SCN_MEM_EXECUTE)
+            });
+
+            exports.push(ModuleExport {
+                name: match &export.name {
+                    Some(name) => name.clone(),
+                    None => format!("Ordinal{}", export.ordinal),
+                },
+                internal_name: export.forwarder.clone(),
+                export_as: None,
+                ordinal: export.ordinal.try_into().ok(),
+                noname: export.name.is_none(),
+                data,
+                constant: false,
+                private: false,
+            });
+        }
+
+        ModuleDefinition {
+            library: Some(export_table.dll_name.clone()),
+            exports,
+        }
+    }
+}
+
+/// Quote a name if it would not survive tokenizing
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if name.is_empty() || name.chars().any(|c| c.is_whitespace() || matches!(c, '=' | ',' | ';')) {
+        write!(f, "\"{}\"", name)
+    } else {
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for ModuleExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_name(f, &self.name)?;
+        if let Some(internal_name) = &self.internal_name {
+            f.write_str(" = ")?;
+            write_name(f, internal_name)?;
+        }
+        if let Some(export_as) = &self.export_as {
+            f.write_str(" == ")?;
+            write_name(f, export_as)?;
+        }
+        if let Some(ordinal) = self.ordinal {
+            write!(f, " @{}", ordinal)?;
+            if self.noname {
+                f.write_str(" NONAME")?;
+            }
+        }
+        if self.private {
+            f.write_str(" PRIVATE")?;
+        }
+        if self.data {
+            f.write_str(" DATA")?;
+        }
+        if self.constant {
+            f.write_str(" CONSTANT")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModuleDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(library) = &self.library {
+            f.write_str("LIBRARY ")?;
+            write_name(f, library)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "EXPORTS")?;
+        for export in self.exports.iter() {
+            writeln!(f, "    {}", export)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITION: &str = r#"; exports of foo
+LIBRARY foo
+HEAPSIZE 0x1000
+EXPORTS
+    plain
+    alias = internal
+    forwarded = bar.baz @3
+    renamed == exported
+    hidden @ 4 NONAME PRIVATE
+    "with space" DATA
+    old CONSTANT
+STACKSIZE 0x2000
+    not_an_export
+"#;
+
+    #[test]
+    fn parse() {
+        let definition = parse_module_definition(DEFINITION).unwrap();
+        assert_eq!(definition.library.as_deref(), Some("foo.dll"));
+
+        let export = |name: &str| ModuleExport { name: String::from(name), ..Default::default() };
+        assert_eq!(definition.exports, [
+            export("plain"),
+            ModuleExport { internal_name: Some(String::from("internal")), ..export("alias") },
+            ModuleExport { internal_name: Some(String::from("bar.baz")), ordinal: Some(3), ..export("forwarded") },
+            ModuleExport { export_as: Some(String::from("exported")), ..export("renamed") },
+            ModuleExport { ordinal: Some(4), noname: true, private: true, ..export("hidden") },
+            ModuleExport { data: true, ..export("with space") },
+            ModuleExport { constant: true, ..export("old") },
+        ]);
+        assert!(definition.exports[2].is_forwarder());
+        assert!(!definition.exports[1].is_forwarder());
+    }
+
+    #[test]
+    fn write_then_parse() {
+        let definition = parse_module_definition(DEFINITION).unwrap();
+        let text = format!("{}", definition);
+        assert!(text.starts_with("LIBRARY foo.dll\nEXPORTS\n    plain\n"));
+        assert!(text.contains("    \"with space\" DATA\n"));
+
+        let reparsed = parse_module_definition(&text).unwrap();
+        assert_eq!(reparsed.library, definition.library);
+        assert_eq!(reparsed.exports, definition.exports);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(parse_module_definition("EXPORTS\n    \"unterminated"), Err(Error::BadModuleDefinition)));
+        assert!(matches!(parse_module_definition("EXPORTS\n    foo @bar"), Err(Error::BadModuleDefinition)));
+        assert!(matches!(parse_module_definition("EXPORTS\n    foo ="), Err(Error::BadModuleDefinition)));
+    }
+}
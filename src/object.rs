@@ -0,0 +1,269 @@
+use bytemuck::checked::try_pod_read_unaligned;
+use core::fmt;
+use crate::{coff::{BigObjHeader, CoffFileHeader, MachineTypes, is_big_object}, drectve::{Directive, is_directive_section, parse_directive_section}, prelude::*, relocation::{CoffRelocation, parse_relocations}, safe_seh::{FEAT00_SAFE_SEH, FEAT00_SYMBOL_NAME, is_sxdata_section, parse_sxdata_section}, section::{SectionHeader, parse_section_table}, string_table::{StringTable, parse_string_table}, symbol::{Symbol, SYMBOL_SIZE, SYMBOL_EX_SIZE, parse_symbol_table, parse_symbol_table_ex}, Error};
+
+/// Representation of a COFF object file
+pub struct CoffObject {
+    /// COFF File Header (regular object files)
+    pub coff: Option<CoffFileHeader>,
+    /// Big Object File Header (`/bigobj` object files)
+    pub big_object: Option<BigObjHeader>,
+    /// Table containing a list of section headers
+    pub section_table: Vec<SectionHeader>,
+    /// Relocations of each section, in the same order as `section_table`
+    pub relocations: Vec<Vec<CoffRelocation>>,
+    /// COFF symbol table
+    pub symbol_table: Vec<Symbol>,
+    /// COFF string table following the symbol table
+    pub string_table: Option<StringTable>,
+    /// Linker directives from the `.drectve` section
+    pub directives: Vec<Directive>,
+    /// Symbol table indices of the exception handlers registered in the `.sxdata` section
+    pub safe_seh_handlers: Vec<u32>,
+}
+
+/// Returns true if the binary starts with a plausible COFF file header.
+/// Object files have no signature, so only the header fields are checked:
+/// the machine type must be known and not an anonymous object header,
+/// and the section and symbol tables must lie within the file.
+pub fn is_coff_object(binary: &[u8]) -> bool {
+    let header_size = size_of::<CoffFileHeader>();
+    let header = match binary.get(0..header_size).map(try_pod_read_unaligned::<CoffFileHeader>) {
+        Some(Ok(header)) => header,
+        _ => {
+            return false;
+        }
+    };
+
+    if header.get_machine_type().is_none() || (header.machine == 0 && header.number_of_sections == 0xffff) {
+        return false;
+    }
+
+    let section_table_end = header_size + header.size_of_optional_header as usize
+        + header.number_of_sections as usize * size_of::<SectionHeader>();
+    let symbol_table_end = (header.pointer_to_symbol_table as usize)
+        .checked_add(header.number_of_symbols as usize * SYMBOL_SIZE);
+
+    section_table_end <= binary.len() && matches!(symbol_table_end, Some(end) if end <= binary.len())
+}
+
+/// Parse a COFF object file from a given byte array.
+/// Unlike images, object files have no MS-DOS stub or PE signature:
+/// the COFF file header starts at offset 0 and there is no optional header.
+/// Big object files are detected and parsed with `parse_big_object`.
+pub fn parse_coff_object(binary: &[u8]) -> Result<CoffObject, Error> {
+    if is_big_object(binary) {
+        return parse_big_object(binary);
+    }
+
+    let header_size = size_of::<CoffFileHeader>();
+    let slice = match binary.get(0..header_size) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let header = match try_pod_read_unaligned::<CoffFileHeader>(slice) {
+        Ok(header) => header,
+        Err(_) => {
+            return Err(Error::MissingCoffHeader);
+        }
+    };
+
+    // Anonymous object headers (import objects, bigobj) share this prefix
+    if header.get_machine_type().is_none() || (header.machine == 0 && header.number_of_sections == 0xffff) {
+        return Err(Error::MissingCoffHeader);
+    }
+
+    let mut object = CoffObject {
+        coff: Some(header),
+        big_object: None,
+        section_table: Vec::new(),
+        relocations: Vec::new(),
+        symbol_table: Vec::new(),
+        string_table: None,
+        directives: Vec::new(),
+        safe_seh_handlers: Vec::new()
+    };
+
+    // Object files should not have an optional header, but skip one if present
+    let offset = header_size + header.size_of_optional_header as usize;
+    object.section_table = parse_section_table(binary, offset, header.number_of_sections);
+    object.relocations = parse_section_relocations(binary, &object.section_table)?;
+    object.directives = parse_section_directives(binary, &object.section_table)?;
+    object.safe_seh_handlers = parse_section_sxdata(binary, &object.section_table)?;
+
+    if header.pointer_to_symbol_table != 0 {
+        let symbol_offset = header.pointer_to_symbol_table as usize;
+        object.symbol_table = parse_symbol_table(binary, symbol_offset, header.number_of_symbols)?;
+        object.string_table = parse_string_table(binary, symbol_offset + header.number_of_symbols as usize * SYMBOL_SIZE)
+            .ok();
+    }
+
+    Ok(object)
+}
+
+/// Parse a big object file (MSVC `/bigobj`) from a given byte array
+pub fn parse_big_object(binary: &[u8]) -> Result<CoffObject, Error> {
+    if !is_big_object(binary) {
+        return Err(Error::MissingCoffHeader);
+    }
+
+    let header_size = size_of::<BigObjHeader>();
+    let header = match try_pod_read_unaligned::<BigObjHeader>(&binary[..header_size]) {
+        Ok(header) => header,
+        Err(_) => {
+            return Err(Error::MissingCoffHeader);
+        }
+    };
+
+    let mut object = CoffObject {
+        coff: None,
+        big_object: Some(header),
+        section_table: Vec::new(),
+        relocations: Vec::new(),
+        symbol_table: Vec::new(),
+        string_table: None,
+        directives: Vec::new(),
+        safe_seh_handlers: Vec::new()
+    };
+
+    object.section_table = parse_section_table(binary, header_size, header.number_of_sections);
+    object.relocations = parse_section_relocations(binary, &object.section_table)?;
+    object.directives = parse_section_directives(binary, &object.section_table)?;
+    object.safe_seh_handlers = parse_section_sxdata(binary, &object.section_table)?;
+
+    if header.pointer_to_symbol_table != 0 {
+        let symbol_offset = header.pointer_to_symbol_table as usize;
+        object.symbol_table = parse_symbol_table_ex(binary, symbol_offset, header.number_of_symbols)?;
+        object.string_table = parse_string_table(binary, symbol_offset + header.number_of_symbols as usize * SYMBOL_EX_SIZE)
+            .ok();
+    }
+
+    Ok(object)
+}
+
+fn parse_section_relocations(binary: &[u8], section_table: &[SectionHeader]) -> Result<Vec<Vec<CoffRelocation>>, Error> {
+    let mut relocations: Vec<Vec<CoffRelocation>> = Vec::new();
+
+    for section in section_table.iter() {
+        relocations.push(parse_relocations(binary, section)?);
+    }
+
+    Ok(relocations)
+}
+
+fn parse_section_directives(binary: &[u8], section_table: &[SectionHeader]) -> Result<Vec<Directive>, Error> {
+    let mut directives: Vec<Directive> = Vec::new();
+
+    for section in section_table.iter().filter(|section| is_directive_section(section)) {
+        directives.append(&mut parse_directive_section(binary, section)?);
+    }
+
+    Ok(directives)
+}
+
+fn parse_section_sxdata(binary: &[u8], section_table: &[SectionHeader]) -> Result<Vec<u32>, Error> {
+    let mut handlers: Vec<u32> = Vec::new();
+
+    for section in section_table.iter().filter(|section| is_sxdata_section(section)) {
+        handlers.append(&mut parse_sxdata_section(binary, section)?);
+    }
+
+    Ok(handlers)
+}
+
+impl fmt::Display for CoffObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(header) = self.coff {
+            writeln!(f, "{}", header)?;
+        }
+
+        if let Some(header) = self.big_object {
+            writeln!(f, "{}", header)?;
+        }
+
+        for section in self.section_table.iter() {
+            writeln!(f, "{}", section.with_string_table(self.string_table.as_ref()))?;
+        }
+
+        for symbol in self.symbol_table.iter() {
+            writeln!(f, "{}", symbol.with_string_table(self.string_table.as_ref()))?;
+        }
+
+        if !self.directives.is_empty() {
+            writeln!(f, "Linker Directives")?;
+            writeln!(f, "-----------------")?;
+            for directive in self.directives.iter() {
+                writeln!(f, "{}", directive)?;
+            }
+        }
+
+        if !self.safe_seh_handlers.is_empty() {
+            writeln!(f, "Safe Exception Handlers")?;
+            writeln!(f, "-----------------------")?;
+            for handler in self.get_safe_seh_handlers() {
+                writeln!(f, "{}", self.get_symbol_name(handler).unwrap_or_default())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl CoffObject {
+    /// Returns the machine type as an enum
+    pub fn get_machine_type(&self) -> Option<MachineTypes> {
+        match (self.coff, self.big_object) {
+            (Some(header), _) => header.get_machine_type(),
+            (_, Some(header)) => header.get_machine_type(),
+            _ => None,
+        }
+    }
+
+    /// Get the resolved name of a section, using the object's string table
+    pub fn get_section_name(&self, section: &SectionHeader) -> Option<String> {
+        section.get_name(self.string_table.as_ref())
+    }
+
+    /// Get the resolved name of a symbol, using the object's string table
+    pub fn get_symbol_name(&self, symbol: &Symbol) -> Option<String> {
+        symbol.get_name(self.string_table.as_ref())
+    }
+
+    /// Returns every library requested with `/DEFAULTLIB`, in order
+    pub fn get_default_libraries(&self) -> Vec<&str> {
+        self.directives.iter().filter_map(|directive| match directive {
+            Directive::DefaultLib(library) => Some(library.as_str()),
+            _ => None,
+        }).collect()
+    }
+
+    /// Find the symbol a relocation refers to
+    pub fn get_relocation_symbol(&self, relocation: &CoffRelocation) -> Option<&Symbol> {
+        let index = relocation.symbol_table_index;
+        match self.symbol_table.binary_search_by_key(&index, |symbol| symbol.index) {
+            Ok(position) => self.symbol_table.get(position),
+            Err(_) => None,
+        }
+    }
+
+    /// Find the symbols of the exception handlers registered in `.sxdata`
+    pub fn get_safe_seh_handlers(&self) -> Vec<&Symbol> {
+        self.safe_seh_handlers.iter().filter_map(|&index| {
+            match self.symbol_table.binary_search_by_key(&index, |symbol| symbol.index) {
+                Ok(position) => self.symbol_table.get(position),
+                Err(_) => None,
+            }
+        }).collect()
+    }
+
+    /// Returns true if the object's `@feat.00` symbol marks it SafeSEH compatible,
+    /// meaning every handler it uses is registered in `.sxdata`
+    pub fn is_safe_seh_compatible(&self) -> bool {
+        self.symbol_table.iter()
+            .find(|symbol| self.get_symbol_name(symbol).as_deref() == Some(FEAT00_SYMBOL_NAME))
+            .is_some_and(|symbol| symbol.value & FEAT00_SAFE_SEH != 0)
+    }
+}
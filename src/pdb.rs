@@ -0,0 +1,1225 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use core::fmt;
+use alloc::collections::BTreeMap;
+use crate::{codeview::{DebugSubsection, SymbolKind, SymbolRecord, parse_subsection_list, parse_symbol_records}, debug::{CodeViewPdb70, FpoData, OmapEntry, format_guid, parse_records, translate_omap}, pe::{read_c_string, read_u16, read_u32}, prelude::*, section::{SectionHeader, parse_section_table}, Error};
+
+/// Magic at the start of an MSF 7.0 (PDB) file
+pub const MSF_MAGIC: [u8; 32] = *b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+/// Stream index of the PDB info stream
+pub const PDB_STREAM_INDEX: u32 = 1;
+/// Stream index of the TPI (type) stream
+pub const TPI_STREAM_INDEX: u32 = 2;
+/// Stream index of the DBI (debug info) stream
+pub const DBI_STREAM_INDEX: u32 = 3;
+/// Stream index of the IPI (id) stream
+pub const IPI_STREAM_INDEX: u32 = 4;
+/// Stream index meaning "no stream"
+pub const NIL_STREAM_INDEX: u16 = 0xffff;
+/// Signature of the `/names` string table stream
+pub const NAMES_STREAM_SIGNATURE: u32 = 0xeffeeffe;
+/// Section contribution substream version
+pub const SECTION_CONTRIBUTION_VER60: u32 = 0xeffe0000 + 19970605;
+/// Section contribution substream version with an extra COFF section index
+pub const SECTION_CONTRIBUTION_V2: u32 = 0xeffe0000 + 20140516;
+
+/// Streams listed in the DBI optional debug header, by position
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(usize)]
+pub enum DebugStream {
+    /// FPO records (`FPO_DATA`).
+    Fpo = 0,
+    /// Exception data.
+    Exception = 1,
+    /// Fixup data.
+    Fixup = 2,
+    /// OMAP from the image to the original layout.
+    OmapToSrc = 3,
+    /// OMAP from the original layout to the image.
+    OmapFromSrc = 4,
+    /// The image's section headers.
+    SectionHeaders = 5,
+    /// Token to RID map.
+    TokenRidMap = 6,
+    /// A copy of `.xdata`.
+    Xdata = 7,
+    /// A copy of `.pdata`.
+    Pdata = 8,
+    /// New-style FPO records.
+    NewFpo = 9,
+    /// The section headers before the image was rearranged.
+    OriginalSectionHeaders = 10,
+}
+
+/// Returns true if the binary starts with the MSF 7.0 magic
+pub fn is_pdb(binary: &[u8]) -> bool {
+    binary.get(0..MSF_MAGIC.len()) == Some(&MSF_MAGIC[..])
+}
+
+/// MSF Super Block, found at the start of the file
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct MsfSuperBlock {
+    /// Must be `MSF_MAGIC`.
+    pub magic: [u8; 32],
+    /// The size of a block; 512, 1024, 2048 or 4096.
+    pub block_size: u32,
+    /// The block holding the active free block map.
+    pub free_block_map_block: u32,
+    /// The number of blocks in the file.
+    pub number_of_blocks: u32,
+    /// The size of the stream directory in bytes.
+    pub number_of_directory_bytes: u32,
+    /// Unknown, reserved.
+    pub unknown: u32,
+    /// The block holding the list of blocks the stream directory occupies.
+    pub block_map_address: u32
+}
+
+impl fmt::Display for MsfSuperBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MSF Super Block")?;
+        writeln!(f, "---------------")?;
+        writeln!(f, "Block Size:              {}", self.block_size)?;
+        writeln!(f, "Free Block Map Block:    {}", self.free_block_map_block)?;
+        writeln!(f, "Number of Blocks:        {}", self.number_of_blocks)?;
+        writeln!(f, "Directory Size:          {}", self.number_of_directory_bytes)?;
+        writeln!(f, "Block Map Address:       {}", self.block_map_address)?;
+
+        Ok(())
+    }
+}
+
+/// A stream of an MSF file: its size and the blocks holding it
+#[derive(Clone, Debug)]
+pub struct MsfStream {
+    /// The size of the stream in bytes.
+    pub size: u32,
+    /// The blocks holding the stream, in order.
+    pub blocks: Vec<u32>
+}
+
+/// An MSF container: the super block and the stream directory.
+/// Streams are read on demand with `read_stream`.
+#[derive(Clone)]
+pub struct Msf {
+    /// MSF Super Block.
+    pub super_block: MsfSuperBlock,
+    /// Streams by index; `None` for nil streams.
+    pub streams: Vec<Option<MsfStream>>
+}
+
+/// Parse the super block and stream directory of an MSF file
+pub fn parse_msf(binary: &[u8]) -> Result<Msf, Error> {
+    if !is_pdb(binary) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let slice = match binary.get(0..size_of::<MsfSuperBlock>()) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let super_block = match try_pod_read_unaligned::<MsfSuperBlock>(slice) {
+        Ok(super_block) => super_block,
+        Err(_) => {
+            return Err(Error::BadPdb);
+        }
+    };
+
+    if !matches!(super_block.block_size, 512 | 1024 | 2048 | 4096) {
+        return Err(Error::BadPdb);
+    }
+
+    let block_size = super_block.block_size as usize;
+    let directory_size = super_block.number_of_directory_bytes as usize;
+    let mut directory_blocks: Vec<u32> = Vec::new();
+    let block_map = super_block.block_map_address as usize * block_size;
+    for i in 0..directory_size.div_ceil(block_size) {
+        directory_blocks.push(read_u32(binary, block_map + i * 4)?);
+    }
+
+    let directory = read_blocks(binary, block_size, &directory_blocks, directory_size)?;
+
+    let number_of_streams = read_u32(&directory, 0)? as usize;
+    if number_of_streams > directory.len() / 4 {
+        return Err(Error::BadPdb);
+    }
+
+    let mut offset = 4 + number_of_streams * 4;
+    let mut streams: Vec<Option<MsfStream>> = Vec::new();
+    for i in 0..number_of_streams {
+        let size = read_u32(&directory, 4 + i * 4)?;
+        if size == u32::MAX {
+            streams.push(None);
+            continue;
+        }
+
+        let mut stream = MsfStream { size, blocks: Vec::new() };
+        for _ in 0..(size as usize).div_ceil(block_size) {
+            stream.blocks.push(read_u32(&directory, offset)?);
+            offset += 4;
+        }
+        streams.push(Some(stream));
+    }
+
+    Ok(Msf { super_block, streams })
+}
+
+fn read_blocks(binary: &[u8], block_size: usize, blocks: &[u32], size: usize) -> Result<Vec<u8>, Error> {
+    let mut data: Vec<u8> = Vec::with_capacity(size);
+
+    for &block in blocks.iter() {
+        let start = block as usize * block_size;
+        let length = block_size.min(size - data.len());
+        match binary.get(start..start+length) {
+            Some(slice) => data.extend_from_slice(slice),
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+impl Msf {
+    /// Read the contents of a stream.
+    /// Nil and missing streams read as empty.
+    pub fn read_stream(&self, binary: &[u8], index: u32) -> Result<Vec<u8>, Error> {
+        match self.streams.get(index as usize) {
+            Some(Some(stream)) => read_blocks(binary, self.super_block.block_size as usize, &stream.blocks, stream.size as usize),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// The PDB info stream (stream 1), identifying the PDB
+#[derive(Clone)]
+pub struct PdbInfo {
+    /// Stream version, 20000404 for PDB 7.0.
+    pub version: u32,
+    /// The time the PDB was created.
+    pub signature: u32,
+    /// Incremented each time the PDB is written.
+    pub age: u32,
+    /// The PDB's GUID, matched against the image's CodeView record.
+    pub guid: [u8; 16],
+    /// Named streams, such as `/names` and `/LinkInfo`, by name.
+    pub named_streams: BTreeMap<String, u32>
+}
+
+fn parse_pdb_info(data: &[u8]) -> Result<PdbInfo, Error> {
+    let guid = match data.get(12..28) {
+        Some(guid) => guid,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut info = PdbInfo {
+        version: read_u32(data, 0)?,
+        signature: read_u32(data, 4)?,
+        age: read_u32(data, 8)?,
+        guid: [0; 16],
+        named_streams: BTreeMap::new(),
+    };
+    info.guid.copy_from_slice(guid);
+
+    // Named stream map: a string buffer followed by a hash table of
+    // (name offset, stream index) pairs, stored for present buckets only
+    let buffer_size = read_u32(data, 28)? as usize;
+    let buffer = match 32usize.checked_add(buffer_size).and_then(|end| data.get(32..end)) {
+        Some(buffer) => buffer,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let mut offset = 32 + buffer_size;
+    let size = read_u32(data, offset)?;
+    // Skip the capacity and the present and deleted bit vectors
+    offset += 8;
+    for _ in 0..2 {
+        let words = read_u32(data, offset)? as usize;
+        offset = match words.checked_mul(4).and_then(|bytes| bytes.checked_add(offset + 4)) {
+            Some(offset) => offset,
+            None => {
+                return Err(Error::BadPdb);
+            }
+        };
+    }
+
+    for _ in 0..size {
+        let name = read_c_string(buffer, read_u32(data, offset)? as usize)?;
+        info.named_streams.insert(name, read_u32(data, offset + 4)?);
+        offset += 8;
+    }
+
+    Ok(info)
+}
+
+impl fmt::Display for PdbInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "PDB Info")?;
+        writeln!(f, "--------")?;
+        writeln!(f, "Version:                 {}", self.version)?;
+        writeln!(f, "Signature:               {}", self.signature)?;
+        writeln!(f, "Age:                     {}", self.age)?;
+        writeln!(f, "GUID:                    {{{}}}", format_guid(&self.guid))?;
+        for (name, index) in self.named_streams.iter() {
+            writeln!(f, "Named Stream:            {} ({})", name, index)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// DBI Stream Header (stream 3)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct DbiHeader {
+    /// Always -1.
+    pub version_signature: i32,
+    /// Stream version, 19990903 for current PDBs.
+    pub version_header: u32,
+    /// The age of the PDB, matched against the image's CodeView record.
+    pub age: u32,
+    /// Stream index of the global symbol hash.
+    pub global_stream_index: u16,
+    /// Version of the toolchain that built the PDB.
+    pub build_number: u16,
+    /// Stream index of the public symbol hash.
+    pub public_stream_index: u16,
+    /// Version of mspdbXXXX.dll that built the PDB.
+    pub pdb_dll_version: u16,
+    /// Stream index of the symbol records referenced by the hashes.
+    pub sym_record_stream: u16,
+    /// Unknown.
+    pub pdb_dll_rbld: u16,
+    /// Size of the module info substream.
+    pub mod_info_size: i32,
+    /// Size of the section contribution substream.
+    pub section_contribution_size: i32,
+    /// Size of the section map substream.
+    pub section_map_size: i32,
+    /// Size of the file info substream.
+    pub source_info_size: i32,
+    /// Size of the type server map substream.
+    pub type_server_map_size: i32,
+    /// Index of the MFC type server.
+    pub mfc_type_server_index: u32,
+    /// Size of the optional debug header substream.
+    pub optional_dbg_header_size: i32,
+    /// Size of the EC substream.
+    pub ec_substream_size: i32,
+    /// Incrementally linked, stripped, or built with conflicting types.
+    pub flags: u16,
+    /// The machine type the PDB describes.
+    pub machine: u16,
+    /// Reserved.
+    pub padding: u32
+}
+
+impl fmt::Display for DbiHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DBI Header")?;
+        writeln!(f, "----------")?;
+        writeln!(f, "Version:                 {}", self.version_header)?;
+        writeln!(f, "Age:                     {}", self.age)?;
+        writeln!(f, "Global Stream Index:     {}", self.global_stream_index)?;
+        writeln!(f, "Public Stream Index:     {}", self.public_stream_index)?;
+        writeln!(f, "Symbol Record Stream:    {}", self.sym_record_stream)?;
+        writeln!(f, "Machine:                 {:#06x}", self.machine)?;
+        writeln!(f, "Flags:                   {:#06x}", self.flags)?;
+
+        Ok(())
+    }
+}
+
+/// A range of a section contributed by a module
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub struct SectionContribution {
+    /// The one-based index of the image section.
+    pub section: u16,
+    /// Reserved.
+    pub padding1: u16,
+    /// Offset of the contribution within the section.
+    pub offset: u32,
+    /// Size of the contribution.
+    pub size: u32,
+    /// The characteristics of the section the contribution came from.
+    pub characteristics: u32,
+    /// The index of the contributing module.
+    pub module_index: u16,
+    /// Reserved.
+    pub padding2: u16,
+    /// CRC of the contribution's data.
+    pub data_crc: u32,
+    /// CRC of the contribution's relocations.
+    pub reloc_crc: u32
+}
+
+impl SectionContribution {
+    /// Returns true if the contribution covers the section offset
+    pub fn contains(&self, section: u16, offset: u32) -> bool {
+        self.section == section && offset >= self.offset && offset - self.offset < self.size
+    }
+}
+
+/// Module Info Header, the fixed part of a module info substream entry
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct ModuleInfoHeader {
+    /// Unused.
+    pub unused1: u32,
+    /// The module's first section contribution.
+    pub section_contribution: SectionContribution,
+    /// Bit 0 set if the module has been written since the DBI was opened.
+    pub flags: u16,
+    /// Stream index of the module's symbols and line info.
+    pub module_sym_stream: u16,
+    /// Size of the module's symbols, including the signature.
+    pub sym_byte_size: u32,
+    /// Size of the module's C11 line info.
+    pub c11_byte_size: u32,
+    /// Size of the module's C13 line info.
+    pub c13_byte_size: u32,
+    /// The number of source files contributing to the module.
+    pub source_file_count: u16,
+    /// Reserved.
+    pub padding: u16,
+    /// Unused.
+    pub unused2: u32,
+    /// Offset of the module's source file name in the `/names` stream.
+    pub source_file_name_index: u32,
+    /// Offset of the module's PDB path in the `/names` stream.
+    pub pdb_file_path_name_index: u32
+}
+
+/// A module (object file or import) that contributed to the image
+#[derive(Clone)]
+pub struct DbiModule {
+    /// Module Info Header.
+    pub header: ModuleInfoHeader,
+    /// The module name, usually the object file path.
+    pub module_name: String,
+    /// The object file or archive the module came from.
+    pub object_name: String
+}
+
+impl fmt::Display for DbiModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Module")?;
+        writeln!(f, "------")?;
+        writeln!(f, "Module Name:             {}", self.module_name)?;
+        writeln!(f, "Object Name:             {}", self.object_name)?;
+        if self.header.module_sym_stream != NIL_STREAM_INDEX {
+            writeln!(f, "Symbol Stream:           {}", self.header.module_sym_stream)?;
+            writeln!(f, "Symbols Size:            {}", self.header.sym_byte_size)?;
+            writeln!(f, "C13 Lines Size:          {}", self.header.c13_byte_size)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_modules(data: &[u8]) -> Result<Vec<DbiModule>, Error> {
+    let mut modules: Vec<DbiModule> = Vec::new();
+    let header_size = size_of::<ModuleInfoHeader>();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let header = match data.get(offset..offset+header_size).map(try_pod_read_unaligned::<ModuleInfoHeader>) {
+            Some(Ok(header)) => header,
+            _ => {
+                return Err(Error::BadPdb);
+            }
+        };
+
+        let module_name = read_c_string(data, offset + header_size)?;
+        let object_offset = offset + header_size + module_name.len() + 1;
+        let object_name = read_c_string(data, object_offset)?;
+        offset = (object_offset + object_name.len() + 1 + 3) & !3;
+
+        modules.push(DbiModule { header, module_name, object_name });
+    }
+
+    Ok(modules)
+}
+
+fn parse_section_contributions(data: &[u8]) -> Result<Vec<SectionContribution>, Error> {
+    let mut contributions: Vec<SectionContribution> = Vec::new();
+    if data.is_empty() {
+        return Ok(contributions);
+    }
+
+    let entry_size = match read_u32(data, 0)? {
+        SECTION_CONTRIBUTION_VER60 => size_of::<SectionContribution>(),
+        // V2 entries are followed by the index of the COFF section
+        SECTION_CONTRIBUTION_V2 => size_of::<SectionContribution>() + 4,
+        _ => {
+            return Err(Error::BadPdb);
+        }
+    };
+
+    for entry in data[4..].chunks_exact(entry_size) {
+        match try_pod_read_unaligned::<SectionContribution>(&entry[..size_of::<SectionContribution>()]) {
+            Ok(contribution) => contributions.push(contribution),
+            Err(_) => {
+                return Err(Error::BadPdb);
+            }
+        }
+    }
+
+    Ok(contributions)
+}
+
+/// A public symbol (`S_PUB32`)
+#[derive(Clone, Debug)]
+pub struct PublicSymbol {
+    /// Code, function, managed or MSIL flags.
+    pub flags: u32,
+    /// Offset of the symbol within its section.
+    pub offset: u32,
+    /// The one-based index of the symbol's section.
+    pub section: u16,
+    /// The decorated symbol name.
+    pub name: String
+}
+
+impl PublicSymbol {
+    fn from_record(record: &SymbolRecord) -> Option<PublicSymbol> {
+        Some(PublicSymbol {
+            flags: read_u32(&record.data, 0).ok()?,
+            offset: read_u32(&record.data, 4).ok()?,
+            section: read_u16(&record.data, 8).ok()?,
+            name: record.get_name()?,
+        })
+    }
+}
+
+impl fmt::Display for PublicSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}:{:08x} {}", self.section, self.offset, self.name)
+    }
+}
+
+/// The debug information of one module: its symbols and C13 subsections
+#[derive(Clone)]
+pub struct ModuleDebugInfo {
+    /// Symbol records, such as procedures and their locals.
+    pub symbols: Vec<SymbolRecord>,
+    /// C13 subsections, such as lines and file checksums.
+    pub subsections: Vec<DebugSubsection>
+}
+
+/// A source location an address was resolved to
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceLocation {
+    /// The name of the module containing the address.
+    pub module: Option<String>,
+    /// The name of the function containing the address.
+    pub function: Option<String>,
+    /// The source file of the line containing the address.
+    pub file: Option<String>,
+    /// The line containing the address.
+    pub line: Option<u32>
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.function.as_deref().unwrap_or("??"))?;
+        if let Some(file) = &self.file {
+            write!(f, " at {}", file)?;
+            if let Some(line) = self.line {
+                write!(f, ":{}", line)?;
+            }
+        }
+        if let Some(module) = &self.module {
+            write!(f, " [{}]", module)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A parsed PDB file.
+/// Module symbols and lines are parsed on demand with `parse_module`.
+#[derive(Clone)]
+pub struct Pdb {
+    /// The MSF container.
+    pub msf: Msf,
+    /// PDB info stream.
+    pub info: PdbInfo,
+    /// DBI Stream Header, if the PDB has a DBI stream.
+    pub dbi: Option<DbiHeader>,
+    /// The `/names` string table buffer.
+    pub names: Vec<u8>,
+    /// Modules that contributed to the image.
+    pub modules: Vec<DbiModule>,
+    /// Section contributions, sorted by section and offset.
+    pub section_contributions: Vec<SectionContribution>,
+    /// Stream indices from the optional debug header, indexed by `DebugStream`.
+    pub debug_streams: Vec<u16>,
+    /// The image's section headers, used to translate RVAs.
+    pub section_headers: Vec<SectionHeader>,
+    /// The section headers before the image was rearranged, if it was.
+    pub original_section_headers: Vec<SectionHeader>,
+    /// OMAP from the image to the original layout, if the image was rearranged.
+    pub omap_to_src: Vec<OmapEntry>,
+    /// OMAP from the original layout to the image, if the image was rearranged.
+    pub omap_from_src: Vec<OmapEntry>,
+    /// Public symbols (`S_PUB32`) from the symbol record stream.
+    pub public_symbols: Vec<PublicSymbol>,
+    /// Global symbols: every other record in the symbol record stream.
+    pub global_symbols: Vec<SymbolRecord>
+}
+
+/// Parse a PDB file from a given byte array
+pub fn parse_pdb(binary: &[u8]) -> Result<Pdb, Error> {
+    let msf = parse_msf(binary)?;
+    let info = parse_pdb_info(&msf.read_stream(binary, PDB_STREAM_INDEX)?)?;
+
+    let mut pdb = Pdb {
+        msf,
+        info,
+        dbi: None,
+        names: Vec::new(),
+        modules: Vec::new(),
+        section_contributions: Vec::new(),
+        debug_streams: Vec::new(),
+        section_headers: Vec::new(),
+        original_section_headers: Vec::new(),
+        omap_to_src: Vec::new(),
+        omap_from_src: Vec::new(),
+        public_symbols: Vec::new(),
+        global_symbols: Vec::new(),
+    };
+
+    if let Some(&index) = pdb.info.named_streams.get("/names") {
+        let names = pdb.msf.read_stream(binary, index)?;
+        if read_u32(&names, 0)? != NAMES_STREAM_SIGNATURE {
+            return Err(Error::BadPdb);
+        }
+
+        let size = read_u32(&names, 8)? as usize;
+        pdb.names = match size.checked_add(12).and_then(|end| names.get(12..end)) {
+            Some(buffer) => buffer.to_vec(),
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+    }
+
+    let data = pdb.msf.read_stream(binary, DBI_STREAM_INDEX)?;
+    if data.is_empty() {
+        return Ok(pdb);
+    }
+
+    let header_size = size_of::<DbiHeader>();
+    let dbi = match data.get(0..header_size).map(try_pod_read_unaligned::<DbiHeader>) {
+        Some(Ok(header)) => header,
+        _ => {
+            return Err(Error::BadPdb);
+        }
+    };
+    pdb.dbi = Some(dbi);
+
+    // Substreams follow the header in a fixed order
+    let sizes = [
+        dbi.mod_info_size,
+        dbi.section_contribution_size,
+        dbi.section_map_size,
+        dbi.source_info_size,
+        dbi.type_server_map_size,
+        dbi.ec_substream_size,
+        dbi.optional_dbg_header_size,
+    ];
+    let mut substreams: Vec<&[u8]> = Vec::new();
+    let mut offset = header_size;
+    for size in sizes {
+        let size = match usize::try_from(size) {
+            Ok(size) => size,
+            Err(_) => {
+                return Err(Error::BadPdb);
+            }
+        };
+        match offset.checked_add(size).and_then(|end| data.get(offset..end)) {
+            Some(substream) => substreams.push(substream),
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+        offset += size;
+    }
+
+    pdb.modules = parse_modules(substreams[0])?;
+    pdb.section_contributions = parse_section_contributions(substreams[1])?;
+
+    // The optional debug header is a list of stream indices
+    for index in substreams[6].chunks_exact(2) {
+        pdb.debug_streams.push(u16::from_le_bytes([index[0], index[1]]));
+    }
+
+    let headers = pdb.read_debug_stream(binary, DebugStream::SectionHeaders)?;
+    pdb.section_headers = parse_section_table(&headers, 0, (headers.len() / size_of::<SectionHeader>()) as u32);
+
+    // Symbols and lines of a rearranged image describe its original layout
+    pdb.omap_from_src = pdb.parse_omap(binary, DebugStream::OmapFromSrc)?;
+    if !pdb.omap_from_src.is_empty() {
+        pdb.omap_to_src = pdb.parse_omap(binary, DebugStream::OmapToSrc)?;
+        let headers = pdb.read_debug_stream(binary, DebugStream::OriginalSectionHeaders)?;
+        pdb.original_section_headers = parse_section_table(&headers, 0, (headers.len() / size_of::<SectionHeader>()) as u32);
+    }
+
+    if dbi.sym_record_stream != NIL_STREAM_INDEX {
+        let records = parse_symbol_records(&pdb.msf.read_stream(binary, dbi.sym_record_stream.into())?)?;
+        for record in records {
+            if record.get_kind() == Some(SymbolKind::Pub32) {
+                if let Some(public) = PublicSymbol::from_record(&record) {
+                    pdb.public_symbols.push(public);
+                }
+            } else {
+                pdb.global_symbols.push(record);
+            }
+        }
+    }
+
+    Ok(pdb)
+}
+
+impl Pdb {
+    /// Returns the age the image's CodeView record must match.
+    /// The DBI age is authoritative, as the PDB info age may be bumped on its own.
+    pub fn get_age(&self) -> u32 {
+        match self.dbi {
+            Some(dbi) => dbi.age,
+            None => self.info.age,
+        }
+    }
+
+    /// Returns true if this PDB was written for the image
+    /// the CodeView record came from
+    pub fn matches(&self, codeview: &CodeViewPdb70) -> bool {
+        self.info.guid == codeview.guid && self.get_age() == codeview.age
+    }
+
+    /// Read a stream listed in the optional debug header.
+    /// Missing streams read as empty.
+    pub fn read_debug_stream(&self, binary: &[u8], stream: DebugStream) -> Result<Vec<u8>, Error> {
+        match self.debug_streams.get(stream as usize) {
+            Some(&index) if index != NIL_STREAM_INDEX => self.msf.read_stream(binary, index.into()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Parse the FPO records of the image
+    pub fn parse_fpo_data(&self, binary: &[u8]) -> Result<Vec<FpoData>, Error> {
+        parse_records(&self.read_debug_stream(binary, DebugStream::Fpo)?)
+    }
+
+    /// Parse an OMAP table, `DebugStream::OmapToSrc` or `DebugStream::OmapFromSrc`
+    pub fn parse_omap(&self, binary: &[u8], stream: DebugStream) -> Result<Vec<OmapEntry>, Error> {
+        parse_records(&self.read_debug_stream(binary, stream)?)
+    }
+
+    /// Get a string from the `/names` stream
+    pub fn get_name(&self, offset: u32) -> Option<String> {
+        read_c_string(&self.names, offset as usize).ok()
+    }
+
+    /// Returns true if the image was rearranged after linking,
+    /// so its RVAs must be translated with OMAP
+    pub fn is_rearranged(&self) -> bool {
+        !self.omap_from_src.is_empty()
+    }
+
+    /// The section headers symbols and lines refer to
+    fn get_symbol_section_headers(&self) -> &[SectionHeader] {
+        if self.is_rearranged() && !self.original_section_headers.is_empty() {
+            &self.original_section_headers
+        } else {
+            &self.section_headers
+        }
+    }
+
+    /// Translate an image RVA to a one-based section index and offset
+    /// in the layout symbols and lines refer to
+    pub fn rva_to_section_offset(&self, rva: u32) -> Option<(u16, u32)> {
+        let rva = if self.is_rearranged() {
+            translate_omap(&self.omap_to_src, rva)?
+        } else {
+            rva
+        };
+
+        self.get_symbol_section_headers().iter().enumerate().find_map(|(index, section)| {
+            let offset = rva.checked_sub(section.virtual_address)?;
+            let size = section.virtual_size.max(section.size_of_raw_data);
+            (offset < size).then_some(((index + 1) as u16, offset))
+        })
+    }
+
+    /// Translate a one-based section index and offset
+    /// from a symbol or line to an image RVA
+    pub fn section_offset_to_rva(&self, section: u16, offset: u32) -> Option<u32> {
+        let header = self.get_symbol_section_headers().get((section as usize).checked_sub(1)?)?;
+        let rva = header.virtual_address.checked_add(offset)?;
+        if self.is_rearranged() {
+            translate_omap(&self.omap_from_src, rva)
+        } else {
+            Some(rva)
+        }
+    }
+
+    /// Find the module whose section contribution covers an RVA
+    pub fn find_module(&self, rva: u32) -> Option<&DbiModule> {
+        let (section, offset) = self.rva_to_section_offset(rva)?;
+        let contribution = self.section_contributions.iter().find(|contribution| contribution.contains(section, offset))?;
+        self.modules.get(contribution.module_index as usize)
+    }
+
+    /// Find the public symbol at or closest before an RVA
+    pub fn find_public_symbol(&self, rva: u32) -> Option<&PublicSymbol> {
+        let (section, offset) = self.rva_to_section_offset(rva)?;
+        self.public_symbols.iter()
+            .filter(|public| public.section == section && public.offset <= offset)
+            .max_by_key(|public| public.offset)
+    }
+
+    /// Parse the symbols and C13 line information of a module
+    pub fn parse_module(&self, binary: &[u8], module: &DbiModule) -> Result<ModuleDebugInfo, Error> {
+        let mut info = ModuleDebugInfo {
+            symbols: Vec::new(),
+            subsections: Vec::new(),
+        };
+
+        if module.header.module_sym_stream == NIL_STREAM_INDEX {
+            return Ok(info);
+        }
+
+        let data = self.msf.read_stream(binary, module.header.module_sym_stream.into())?;
+        let symbols_end = module.header.sym_byte_size as usize;
+        let c13_start = symbols_end + module.header.c11_byte_size as usize;
+        let c13_end = c13_start + module.header.c13_byte_size as usize;
+
+        // Symbols are preceded by the CodeView signature
+        if let Some(symbols) = data.get(4.min(symbols_end)..symbols_end) {
+            info.symbols = parse_symbol_records(symbols)?;
+        } else {
+            return Err(Error::OffsetOutOfRange);
+        }
+
+        match data.get(c13_start..c13_end) {
+            Some(c13) => info.subsections = parse_subsection_list(c13)?,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Resolve an RVA to its module, function, source file and line
+    pub fn symbolize(&self, binary: &[u8], rva: u32) -> Result<SourceLocation, Error> {
+        let mut location = SourceLocation {
+            function: self.find_public_symbol(rva).map(|public| public.name.clone()),
+            ..SourceLocation::default()
+        };
+
+        let (section, offset) = match self.rva_to_section_offset(rva) {
+            Some(section_offset) => section_offset,
+            None => {
+                return Ok(location);
+            }
+        };
+
+        let module = match self.find_module(rva) {
+            Some(module) => module,
+            None => {
+                return Ok(location);
+            }
+        };
+        location.module = Some(module.module_name.clone());
+
+        let info = self.parse_module(binary, module)?;
+        for symbol in info.symbols.iter() {
+            if !matches!(symbol.get_kind(), Some(SymbolKind::GProc32 | SymbolKind::LProc32 | SymbolKind::GProc32Id | SymbolKind::LProc32Id)) {
+                continue;
+            }
+
+            let length = read_u32(&symbol.data, 12)?;
+            let start = read_u32(&symbol.data, 28)?;
+            if read_u16(&symbol.data, 32)? == section && offset >= start && offset - start < length {
+                location.function = symbol.get_name();
+                break;
+            }
+        }
+
+        let mut best: Option<(u32, u32, u32)> = None;
+        for subsection in info.subsections.iter() {
+            let DebugSubsection::Lines(lines) = subsection else {
+                continue;
+            };
+
+            if lines.segment != section || offset < lines.offset || offset - lines.offset >= lines.code_size {
+                continue;
+            }
+
+            for block in lines.blocks.iter() {
+                for line in block.lines.iter() {
+                    let line_offset = lines.offset.saturating_add(line.offset);
+                    if line_offset <= offset && best.is_none_or(|(best_offset, _, _)| line_offset >= best_offset) {
+                        best = Some((line_offset, block.file_checksum_offset, line.line_start));
+                    }
+                }
+            }
+        }
+
+        if let Some((_, file_checksum_offset, line)) = best {
+            location.line = Some(line);
+            location.file = info.subsections.iter().find_map(|subsection| match subsection {
+                DebugSubsection::FileChecksums(checksums) => checksums.iter()
+                    .find(|checksum| checksum.offset == file_checksum_offset)
+                    .and_then(|checksum| self.get_name(checksum.file_name_offset)),
+                _ => None,
+            });
+        }
+
+        Ok(location)
+    }
+}
+
+impl fmt::Display for Pdb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.msf.super_block)?;
+        writeln!(f, "{}", self.info)?;
+
+        if let Some(dbi) = self.dbi {
+            writeln!(f, "{}", dbi)?;
+        }
+
+        for module in self.modules.iter() {
+            writeln!(f, "{}", module)?;
+        }
+
+        for section in self.section_headers.iter() {
+            writeln!(f, "{}", section)?;
+        }
+
+        if !self.public_symbols.is_empty() {
+            writeln!(f, "Public Symbols")?;
+            writeln!(f, "--------------")?;
+            for public in self.public_symbols.iter() {
+                writeln!(f, "{}", public)?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.global_symbols.is_empty() {
+            writeln!(f, "Global Symbols")?;
+            writeln!(f, "--------------")?;
+            for global in self.global_symbols.iter() {
+                writeln!(f, "{}", global)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::bytes_of;
+    use crate::codeview::{CV_SIGNATURE_C13, DebugSubsectionKind};
+
+    const BLOCK_SIZE: usize = 512;
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    fn record(kind: SymbolKind, data: &[u8]) -> Vec<u8> {
+        let mut bytes = ((data.len() + 2) as u16).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(kind as u16).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn subsection(kind: DebugSubsectionKind, data: &[u8]) -> Vec<u8> {
+        let mut bytes = words(&[kind as u32, data.len() as u32]);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Lay out streams in blocks after the super block, free block map and block map,
+    /// followed by the stream directory
+    fn write_msf(streams: &[Option<Vec<u8>>]) -> Vec<u8> {
+        let mut binary = vec![0u8; 3 * BLOCK_SIZE];
+        let mut directory = words(&[streams.len() as u32]);
+        let mut block_lists: Vec<u8> = Vec::new();
+        for stream in streams.iter() {
+            let Some(stream) = stream else {
+                directory.extend(words(&[u32::MAX]));
+                continue;
+            };
+
+            directory.extend(words(&[stream.len() as u32]));
+            for chunk in stream.chunks(BLOCK_SIZE) {
+                block_lists.extend(words(&[(binary.len() / BLOCK_SIZE) as u32]));
+                binary.extend_from_slice(chunk);
+                binary.resize(binary.len().next_multiple_of(BLOCK_SIZE), 0);
+            }
+        }
+        directory.extend(block_lists);
+
+        let mut block_map: Vec<u8> = Vec::new();
+        for chunk in directory.chunks(BLOCK_SIZE) {
+            block_map.extend(words(&[(binary.len() / BLOCK_SIZE) as u32]));
+            binary.extend_from_slice(chunk);
+            binary.resize(binary.len().next_multiple_of(BLOCK_SIZE), 0);
+        }
+        binary[2 * BLOCK_SIZE..2 * BLOCK_SIZE + block_map.len()].copy_from_slice(&block_map);
+
+        let super_block = MsfSuperBlock {
+            magic: MSF_MAGIC,
+            block_size: BLOCK_SIZE as u32,
+            free_block_map_block: 1,
+            number_of_blocks: (binary.len() / BLOCK_SIZE) as u32,
+            number_of_directory_bytes: directory.len() as u32,
+            unknown: 0,
+            block_map_address: 2,
+        };
+        binary[..size_of::<MsfSuperBlock>()].copy_from_slice(bytes_of(&super_block));
+        binary
+    }
+
+    /// PDB info stream naming each stream in turn,
+    /// with every name in a present bucket of a hash table twice its size
+    fn write_pdb_info(named_streams: &[(&str, u32)]) -> Vec<u8> {
+        let mut data = words(&[20000404, 0x5f000000, 2]);
+        data.extend_from_slice(&[0x11; 16]);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut pairs: Vec<u8> = Vec::new();
+        for (name, index) in named_streams.iter() {
+            pairs.extend(words(&[buffer.len() as u32, *index]));
+            buffer.extend_from_slice(name.as_bytes());
+            buffer.push(0);
+        }
+
+        let present = (1u32 << named_streams.len()) - 1;
+        data.extend(words(&[buffer.len() as u32]));
+        data.extend(buffer);
+        data.extend(words(&[named_streams.len() as u32, 2 * named_streams.len() as u32, 1, present, 0]));
+        data.extend(pairs);
+        data
+    }
+
+    fn text_section(virtual_address: u32) -> SectionHeader {
+        SectionHeader {
+            name: *b".text\0\0\0",
+            virtual_size: 0x100,
+            virtual_address,
+            ..Default::default()
+        }
+    }
+
+    /// A PDB with one module, `t.obj`, whose function `add` covers 0x10..0x30 of section 1
+    /// with lines 3 and 4 of `t.c` at 0x10 and 0x18, and the given optional debug streams
+    fn write_pdb(debug_streams: &[(DebugStream, Vec<u8>)]) -> Vec<u8> {
+        let mut proc = words(&[0, 0, 0, 0x20, 0, 0, 0, 0x10]);
+        proc.extend_from_slice(&1u16.to_le_bytes());
+        proc.extend_from_slice(b"\0add\0");
+        let symbols = record(SymbolKind::GProc32, &proc);
+
+        let mut lines = words(&[0x10, 1, 0x20]);
+        lines.extend(words(&[0, 2, 12 + 2 * 8, 0, 3 | 0x80000000, 8, 4 | 0x80000000]));
+        let mut c13 = subsection(DebugSubsectionKind::Lines, &lines);
+        c13.extend(subsection(DebugSubsectionKind::FileChecksums, &words(&[1, 0])));
+
+        let mut module_stream = words(&[CV_SIGNATURE_C13]);
+        module_stream.extend_from_slice(&symbols);
+        module_stream.extend_from_slice(&c13);
+
+        let contribution = SectionContribution { section: 1, offset: 0, size: 0x100, ..Default::default() };
+        let header = ModuleInfoHeader {
+            section_contribution: contribution,
+            module_sym_stream: 4,
+            sym_byte_size: 4 + symbols.len() as u32,
+            c13_byte_size: c13.len() as u32,
+            ..Default::default()
+        };
+        let mut modules = bytes_of(&header).to_vec();
+        modules.extend_from_slice(b"t.obj\0t.obj\0");
+
+        let mut contributions = words(&[SECTION_CONTRIBUTION_VER60]);
+        contributions.extend_from_slice(bytes_of(&contribution));
+
+        let mut optional_header = [NIL_STREAM_INDEX; 11];
+        for (index, (stream, _)) in debug_streams.iter().enumerate() {
+            optional_header[*stream as usize] = 7 + index as u16;
+        }
+
+        let dbi_header = DbiHeader {
+            version_signature: -1,
+            version_header: 19990903,
+            age: 2,
+            global_stream_index: NIL_STREAM_INDEX,
+            public_stream_index: NIL_STREAM_INDEX,
+            sym_record_stream: 6,
+            mod_info_size: modules.len() as i32,
+            section_contribution_size: contributions.len() as i32,
+            optional_dbg_header_size: 22,
+            ..Default::default()
+        };
+        let mut dbi = bytes_of(&dbi_header).to_vec();
+        dbi.extend(modules);
+        dbi.extend(contributions);
+        dbi.extend(optional_header.iter().flat_map(|index| index.to_le_bytes()));
+
+        let mut names = words(&[NAMES_STREAM_SIGNATURE, 1, 5]);
+        names.extend_from_slice(b"\0t.c\0");
+
+        let mut public = words(&[2, 0x10]);
+        public.extend_from_slice(&1u16.to_le_bytes());
+        public.extend_from_slice(b"_add\0");
+
+        let mut streams = vec![
+            Some(Vec::new()),
+            Some(write_pdb_info(&[("/names", 5)])),
+            Some(Vec::new()),
+            Some(dbi),
+            Some(module_stream),
+            Some(names),
+            Some(record(SymbolKind::Pub32, &public)),
+        ];
+        streams.extend(debug_streams.iter().map(|(_, data)| Some(data.clone())));
+        write_msf(&streams)
+    }
+
+    #[test]
+    fn multi_block_directory() {
+        // 200 nil streams push the directory past one block
+        let mut streams: Vec<Option<Vec<u8>>> = vec![None; 200];
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        streams.push(Some(data.clone()));
+        let binary = write_msf(&streams);
+
+        let msf = parse_msf(&binary).unwrap();
+        assert!(msf.super_block.number_of_directory_bytes as usize > BLOCK_SIZE);
+        assert_eq!(msf.streams.len(), 201);
+        assert!(msf.streams[0].is_none());
+        assert_eq!(msf.streams[200].as_ref().map(|stream| stream.blocks.len()), Some(2));
+        assert_eq!(msf.read_stream(&binary, 200).unwrap(), data);
+        assert!(msf.read_stream(&binary, 0).unwrap().is_empty());
+        assert!(msf.read_stream(&binary, 500).unwrap().is_empty());
+
+        let mut bad = binary.clone();
+        bad[32..36].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(matches!(parse_msf(&bad), Err(Error::BadPdb)));
+        assert!(matches!(parse_msf(&binary[..BLOCK_SIZE * 4]), Err(Error::OffsetOutOfRange)));
+        assert!(matches!(parse_msf(&binary[1..]), Err(Error::MissingMagicNumber)));
+    }
+
+    #[test]
+    fn named_streams() {
+        let info = parse_pdb_info(&write_pdb_info(&[("/names", 5), ("/LinkInfo", 6), ("/src/headerblock", 9)])).unwrap();
+        assert_eq!((info.version, info.age, info.guid), (20000404, 2, [0x11; 16]));
+        let named_streams: Vec<(&str, u32)> = info.named_streams.iter().map(|(name, &index)| (name.as_str(), index)).collect();
+        assert_eq!(named_streams, [("/LinkInfo", 6), ("/names", 5), ("/src/headerblock", 9)]);
+
+        let data = write_pdb_info(&[("/names", 5)]);
+        assert!(matches!(parse_pdb_info(&data[..data.len() - 4]), Err(Error::OffsetOutOfRange)));
+        assert!(matches!(parse_pdb_info(&data[..20]), Err(Error::OffsetOutOfRange)));
+    }
+
+    #[test]
+    fn section_offsets() {
+        let binary = write_pdb(&[(DebugStream::SectionHeaders, bytes_of(&text_section(0x1000)).to_vec())]);
+        let pdb = parse_pdb(&binary).unwrap();
+        assert!(!pdb.is_rearranged());
+        assert_eq!(pdb.get_age(), 2);
+        assert_eq!(pdb.rva_to_section_offset(0x1018), Some((1, 0x18)));
+        assert_eq!(pdb.rva_to_section_offset(0xfff), None);
+        assert_eq!(pdb.rva_to_section_offset(0x1100), None);
+        assert_eq!(pdb.section_offset_to_rva(1, 0x18), Some(0x1018));
+        assert_eq!(pdb.section_offset_to_rva(0, 0x18), None);
+        assert_eq!(pdb.section_offset_to_rva(2, 0x18), None);
+    }
+
+    #[test]
+    fn symbolize_lines() {
+        let binary = write_pdb(&[(DebugStream::SectionHeaders, bytes_of(&text_section(0x1000)).to_vec())]);
+        let pdb = parse_pdb(&binary).unwrap();
+        assert_eq!(pdb.modules.len(), 1);
+        assert_eq!(pdb.public_symbols.len(), 1);
+
+        let location = pdb.symbolize(&binary, 0x1018).unwrap();
+        assert_eq!(location, SourceLocation {
+            module: Some(String::from("t.obj")),
+            function: Some(String::from("add")),
+            file: Some(String::from("t.c")),
+            line: Some(4),
+        });
+        assert_eq!(location.to_string(), "add at t.c:4 [t.obj]");
+
+        // Before the function: only the module is known
+        let location = pdb.symbolize(&binary, 0x1004).unwrap();
+        assert_eq!(location, SourceLocation { module: Some(String::from("t.obj")), ..Default::default() });
+
+        // Past the function: the closest public symbol is still reported
+        let location = pdb.symbolize(&binary, 0x1040).unwrap();
+        assert_eq!((location.function.as_deref(), location.line), (Some("_add"), None));
+
+        assert_eq!(pdb.symbolize(&binary, 0x5000).unwrap(), SourceLocation::default());
+    }
+
+    fn omap(entries: &[(u32, u32)]) -> Vec<u8> {
+        entries.iter().flat_map(|&(rva, rva_to)| words(&[rva, rva_to])).collect()
+    }
+
+    #[test]
+    fn rearranged_image() {
+        // The image moved .text to 0x3000 and swapped its first 0x10 bytes
+        // with `add`, dropping everything past it
+        let binary = write_pdb(&[
+            (DebugStream::SectionHeaders, bytes_of(&text_section(0x3000)).to_vec()),
+            (DebugStream::OmapToSrc, omap(&[(0x3000, 0x1010), (0x3020, 0x1000), (0x3030, 0)])),
+            (DebugStream::OmapFromSrc, omap(&[(0x1000, 0x3020), (0x1010, 0x3000), (0x1030, 0)])),
+            (DebugStream::OriginalSectionHeaders, bytes_of(&text_section(0x1000)).to_vec()),
+        ]);
+        let pdb = parse_pdb(&binary).unwrap();
+        assert!(pdb.is_rearranged());
+
+        assert_eq!(pdb.rva_to_section_offset(0x3008), Some((1, 0x18)));
+        assert_eq!(pdb.rva_to_section_offset(0x3024), Some((1, 0x4)));
+        assert_eq!(pdb.rva_to_section_offset(0x3030), None);
+        assert_eq!(pdb.rva_to_section_offset(0x1018), None);
+        assert_eq!(pdb.section_offset_to_rva(1, 0x18), Some(0x3008));
+        assert_eq!(pdb.section_offset_to_rva(1, 0x4), Some(0x3024));
+        assert_eq!(pdb.section_offset_to_rva(1, 0x40), None);
+
+        let location = pdb.symbolize(&binary, 0x3008).unwrap();
+        assert_eq!(location.to_string(), "add at t.c:4 [t.obj]");
+        assert_eq!(pdb.symbolize(&binary, 0x1018).unwrap(), SourceLocation::default());
+    }
+
+    #[test]
+    fn rearranged_image_without_original_headers() {
+        // Only the RVAs were remapped; the image's own section headers still apply
+        let binary = write_pdb(&[
+            (DebugStream::SectionHeaders, bytes_of(&text_section(0x1000)).to_vec()),
+            (DebugStream::OmapToSrc, omap(&[(0x1000, 0x1010), (0x1020, 0x1000), (0x1030, 0)])),
+            (DebugStream::OmapFromSrc, omap(&[(0x1000, 0x1020), (0x1010, 0x1000), (0x1030, 0)])),
+        ]);
+        let pdb = parse_pdb(&binary).unwrap();
+        assert!(pdb.original_section_headers.is_empty());
+        assert_eq!(pdb.rva_to_section_offset(0x1008), Some((1, 0x18)));
+        assert_eq!(pdb.section_offset_to_rva(1, 0x18), Some(0x1008));
+        assert_eq!(pdb.symbolize(&binary, 0x1008).unwrap().line, Some(4));
+    }
+}
@@ -1,13 +1,13 @@
-use crate::{coff::CoffFileHeader, optional::{OptionalHeader32, OptionalHeader64, Magic, Optional}, section::{SectionHeader, parse_section_table}, Error};
+use crate::{coff::CoffFileHeader, dos::{DosHeader, parse_dos_header}, optional::{DataDirectories, OptionalHeader32, OptionalHeader64, Magic, Optional}, section::{SectionHeader, parse_section_table}, symbol::{Symbol, SYMBOL_SIZE, parse_symbol_table}, string_table::{StringTable, parse_string_table}, Error};
 use bytemuck::checked::try_from_bytes;
 use num_traits::FromPrimitive;
 use core::fmt;
 use crate::prelude::*;
 
-const IMAGE_DOS_PE_SIGNATURE_OFFSET: usize = 0x3c;
-
 /// Representation of the sections of a Portable Executable
 pub struct PortableExecutable {
+    /// MS-DOS Header (Image Only)
+    pub dos_header: DosHeader,
     /// COFF File Header (Object and Image)
     pub coff: CoffFileHeader,
     /// PE32 Optional Header (Image Only)
@@ -16,13 +16,18 @@ pub struct PortableExecutable {
     pub optional_header_64: Option<OptionalHeader64>,
     /// Table containing a list of section headers
     pub section_table: Vec<SectionHeader>,
+    /// COFF symbol table, if present (deprecated for images)
+    pub symbol_table: Vec<Symbol>,
+    /// COFF string table following the symbol table, if present
+    pub string_table: Option<StringTable>,
 }
 
 /// Parse a Portable Executable from a given byte array
 pub fn parse_portable_executable(binary: &[u8]) -> Result<PortableExecutable, Error> {
-    let mut offset = read_u16(binary, IMAGE_DOS_PE_SIGNATURE_OFFSET)?.into();
+    let dos_header = parse_dos_header(binary)?;
+    let mut offset = dos_header.e_lfanew as usize;
 
-    let slice = match binary.get(offset..offset+4) {
+    let slice = match offset.checked_add(4).and_then(|end| binary.get(offset..end)) {
         Some(slice) => slice,
         None => {
             return Err(Error::OffsetOutOfRange);
@@ -43,10 +48,13 @@ pub fn parse_portable_executable(binary: &[u8]) -> Result<PortableExecutable, Er
     offset += 4;
 
     let mut pe: PortableExecutable = PortableExecutable { 
+        dos_header,
         coff: CoffFileHeader::default(),
         optional_header_32: None, 
         optional_header_64: None, 
-        section_table: Vec::new()
+        section_table: Vec::new(),
+        symbol_table: Vec::new(),
+        string_table: None
     };
 
     let slice = match binary.get(offset..offset+20) {
@@ -85,8 +93,18 @@ pub fn parse_portable_executable(binary: &[u8]) -> Result<PortableExecutable, Er
 
     pe.section_table = parse_section_table(binary, offset, pe.coff.number_of_sections);
 
+    // COFF debugging information is deprecated for images, so a
+    // malformed symbol table should not prevent the image from loading
+    if pe.coff.pointer_to_symbol_table != 0 {
+        let symbol_offset = pe.coff.pointer_to_symbol_table as usize;
+        pe.symbol_table = parse_symbol_table(binary, symbol_offset, pe.coff.number_of_symbols)
+            .unwrap_or_default();
+        pe.string_table = parse_string_table(binary, symbol_offset + pe.coff.number_of_symbols as usize * SYMBOL_SIZE)
+            .ok();
+    }
+
     /*for section in pe.section_table.iter() {
-        let name = match section.get_name() {
+        let name = match section.get_name(None) {
             Some(name) => name,
             None => {
                 return Err(Error::new(ErrorKind::Other, "Failed to get section name"));
@@ -107,8 +125,67 @@ pub fn parse_portable_executable(binary: &[u8]) -> Result<PortableExecutable, Er
     Ok(pe)
 }
 
+impl PortableExecutable {
+    /// Returns the data directories from whichever optional header is present
+    pub fn get_data_directories(&self) -> Option<&DataDirectories> {
+        match (&self.optional_header_32, &self.optional_header_64) {
+            (Some(header), _) => Some(&header.data_directories),
+            (None, Some(header)) => Some(&header.data_directories),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns true if the image is PE32+
+    pub fn is_64_bit(&self) -> bool {
+        self.optional_header_64.is_some()
+    }
+
+    /// Returns the preferred load address of the image
+    pub fn get_image_base(&self) -> u64 {
+        match (&self.optional_header_32, &self.optional_header_64) {
+            (Some(header), _) => header.image_base.into(),
+            (None, Some(header)) => header.image_base,
+            (None, None) => 0,
+        }
+    }
+
+    /// Translate a virtual address into a relative virtual address,
+    /// using the image's preferred base
+    pub fn va_to_rva(&self, va: u64) -> Option<u32> {
+        va.checked_sub(self.get_image_base())?.try_into().ok()
+    }
+
+    /// Translate a relative virtual address into a file offset,
+    /// using the section that contains it or the headers
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        for section in self.section_table.iter() {
+            let size = section.virtual_size.max(section.size_of_raw_data);
+            if rva >= section.virtual_address && rva - section.virtual_address < size {
+                let delta = rva - section.virtual_address;
+                if delta >= section.size_of_raw_data {
+                    return None;
+                }
+                return Some(section.pointer_to_raw_data as usize + delta as usize);
+            }
+        }
+
+        let size_of_headers = match (&self.optional_header_32, &self.optional_header_64) {
+            (Some(header), _) => header.size_of_headers,
+            (None, Some(header)) => header.size_of_headers,
+            (None, None) => 0,
+        };
+
+        if rva < size_of_headers {
+            Some(rva as usize)
+        } else {
+            None
+        }
+    }
+}
+
 impl fmt::Display for PortableExecutable {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.dos_header)?;
         writeln!(f, "{}", self.coff)?;
 
         match self.optional_header_32 {
@@ -126,14 +203,18 @@ impl fmt::Display for PortableExecutable {
         }
 
         for section in self.section_table.iter() {
-            writeln!(f, "{}", section)?;
+            writeln!(f, "{}", section.with_string_table(self.string_table.as_ref()))?;
+        }
+
+        for symbol in self.symbol_table.iter() {
+            writeln!(f, "{}", symbol.with_string_table(self.string_table.as_ref()))?;
         }
 
         Ok(())
     }
 }
 
-fn read_u16(binary: &[u8], offset: usize) -> Result<u16, Error> {
+pub(crate) fn read_u16(binary: &[u8], offset: usize) -> Result<u16, Error> {
     if let Some(array) = binary.get(offset..offset+2) {
         if let Ok(slice) = array.try_into() {
             Ok(u16::from_le_bytes(slice))
@@ -143,4 +224,34 @@ fn read_u16(binary: &[u8], offset: usize) -> Result<u16, Error> {
     } else {
         Err(Error::OffsetOutOfRange)
     }
-}
\ No newline at end of file
+}
+
+pub(crate) fn read_u32(binary: &[u8], offset: usize) -> Result<u32, Error> {
+    if let Some(array) = binary.get(offset..offset+4) {
+        if let Ok(slice) = array.try_into() {
+            Ok(u32::from_le_bytes(slice))
+        } else {
+            unreachable!()
+        }
+    } else {
+        Err(Error::OffsetOutOfRange)
+    }
+}
+
+pub(crate) fn read_c_string(binary: &[u8], offset: usize) -> Result<String, Error> {
+    let slice = match binary.get(offset..) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let end = match slice.iter().position(|&c| c == 0) {
+        Some(end) => end,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    String::from_utf8(slice[..end].to_vec()).map_err(Error::BadString)
+}
@@ -3,6 +3,8 @@ pub use core::prelude::v1::*;
 extern crate alloc;
 pub use alloc::string::String;
 pub use alloc::vec::Vec;
+pub use alloc::format;
+pub use alloc::vec;
 pub use core::{write, writeln};
 pub use core::convert::TryInto;
 pub use core::unreachable;
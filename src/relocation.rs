@@ -1,4 +1,82 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
 use num_derive::FromPrimitive;
+use core::fmt;
+use crate::{prelude::*, section::{SectionHeader, SectionFlags}, Error};
+
+/// Size in bytes of a COFF relocation record
+pub const RELOCATION_SIZE: usize = 10;
+
+/// Parse the COFF relocations of a section, as described by
+/// `pointer_to_relocations` and `number_of_relocations` in its header.
+/// Handles `IMAGE_SCN_LNK_NRELOC_OVFL`, where the real count is stored
+/// in the first relocation record.
+pub fn parse_relocations(binary: &[u8], section: &SectionHeader) -> Result<Vec<CoffRelocation>, Error> {
+    let mut relocations: Vec<CoffRelocation> = Vec::new();
+    let mut offset = section.pointer_to_relocations as usize;
+    let mut count = section.number_of_relocations as u32;
+
+    if offset == 0 || count == 0 {
+        return Ok(relocations);
+    }
+
+    if section.characteristics & SectionFlags::IMAGE_SCN_LNK_NRELOC_OVFL.bits() != 0 && count == 0xffff {
+        let first = read_relocation(binary, offset)?;
+        // The count includes the record holding it
+        count = first.virtual_address.saturating_sub(1);
+        offset += RELOCATION_SIZE;
+    }
+
+    for _ in 0..count {
+        relocations.push(read_relocation(binary, offset)?);
+        offset += RELOCATION_SIZE;
+    }
+
+    Ok(relocations)
+}
+
+fn read_relocation(binary: &[u8], offset: usize) -> Result<CoffRelocation, Error> {
+    let slice = match binary.get(offset..offset+RELOCATION_SIZE) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    match try_pod_read_unaligned::<CoffRelocation>(slice) {
+        Ok(relocation) => Ok(relocation),
+        Err(_) => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// COFF Relocation record (Object Only)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct CoffRelocation {
+    /// The address of the item to which relocation is applied.
+    /// This is the offset from the beginning of the section, plus the value of the section's `virtual_address` field.
+    pub virtual_address: u32,
+    /// A zero-based index into the symbol table.
+    /// This symbol gives the address that is to be used for the relocation.
+    pub symbol_table_index: u32,
+    /// A value that indicates the kind of relocation that should be performed.
+    /// Valid relocation types depend on machine type.
+    pub relocation_type: u16
+}
+
+impl fmt::Display for CoffRelocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (virtual_address, symbol_table_index, relocation_type) =
+            (self.virtual_address, self.symbol_table_index, self.relocation_type);
+
+        writeln!(f, "Relocation")?;
+        writeln!(f, "----------")?;
+        writeln!(f, "Virtual Address:         {:#010x}", virtual_address)?;
+        writeln!(f, "Symbol Table Index:      {}", symbol_table_index)?;
+        writeln!(f, "Type:                    {:#06x}", relocation_type)?;
+
+        Ok(())
+    }
+}
 
 /// Relocation type indicators for x64 and compatible processors.
 #[derive(FromPrimitive, Debug, PartialEq)]
@@ -0,0 +1,164 @@
+use core::fmt;
+use crate::{pe::{read_u16, read_u32}, prelude::*, Error};
+
+/// The empty entry every 32-bit resource file starts with,
+/// which distinguishes it from a 16-bit resource file.
+pub const RESOURCE_FILE_SIGNATURE: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+];
+
+/// Representation of a compiled Win32 resource file (.res)
+pub struct ResourceFile {
+    /// Resources in the order they appear in the file,
+    /// excluding the leading empty entry
+    pub entries: Vec<ResourceEntry>,
+}
+
+/// Returns true if the binary starts with the 32-bit resource file signature
+pub fn is_resource_file(binary: &[u8]) -> bool {
+    binary.get(0..RESOURCE_FILE_SIGNATURE.len()) == Some(&RESOURCE_FILE_SIGNATURE[..])
+}
+
+/// Parse a 32-bit resource file from a given byte array
+pub fn parse_resource_file(binary: &[u8]) -> Result<ResourceFile, Error> {
+    if !is_resource_file(binary) {
+        return Err(Error::MissingMagicNumber);
+    }
+
+    let mut entries: Vec<ResourceEntry> = Vec::new();
+    let mut offset = RESOURCE_FILE_SIGNATURE.len();
+
+    while offset < binary.len() {
+        let data_size = read_u32(binary, offset)?;
+        let header_size = read_u32(binary, offset + 4)?;
+        if (header_size as usize) < RESOURCE_FILE_SIGNATURE.len() {
+            return Err(Error::BadResource);
+        }
+
+        let mut cursor = offset + 8;
+        let resource_type = read_resource_id(binary, &mut cursor)?;
+        let name = read_resource_id(binary, &mut cursor)?;
+        cursor = align4(cursor);
+
+        let entry = ResourceEntry {
+            resource_type,
+            name,
+            data_version: read_u32(binary, cursor)?,
+            memory_flags: read_u16(binary, cursor + 4)?,
+            language_id: read_u16(binary, cursor + 6)?,
+            version: read_u32(binary, cursor + 8)?,
+            characteristics: read_u32(binary, cursor + 12)?,
+            data_offset: offset + header_size as usize,
+            data_size,
+        };
+
+        let data_end = match entry.data_offset.checked_add(data_size as usize) {
+            Some(end) if end <= binary.len() => end,
+            _ => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        entries.push(entry);
+        offset = align4(data_end);
+    }
+
+    Ok(ResourceFile { entries })
+}
+
+fn read_resource_id(binary: &[u8], offset: &mut usize) -> Result<ResourceId, Error> {
+    let first = read_u16(binary, *offset)?;
+
+    if first == 0xffff {
+        let ordinal = read_u16(binary, *offset + 2)?;
+        *offset += 4;
+        return Ok(ResourceId::Ordinal(ordinal));
+    }
+
+    let mut name: Vec<u16> = Vec::new();
+    loop {
+        let c = read_u16(binary, *offset)?;
+        *offset += 2;
+        if c == 0 {
+            break;
+        }
+        name.push(c);
+    }
+
+    Ok(ResourceId::Name(String::from_utf16_lossy(&name)))
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// A resource type or name, stored either as a number or as a string
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResourceId {
+    /// Numeric identifier (for types, one of the predefined `RT_*` values)
+    Ordinal(u16),
+    /// String identifier
+    Name(String),
+}
+
+impl fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceId::Ordinal(ordinal) => write!(f, "#{}", ordinal),
+            ResourceId::Name(name) => write!(f, "{:?}", name),
+        }
+    }
+}
+
+/// A single resource and its header (RESOURCEHEADER)
+#[derive(Clone)]
+pub struct ResourceEntry {
+    /// The resource type.
+    pub resource_type: ResourceId,
+    /// The resource name.
+    pub name: ResourceId,
+    /// Predefined resource data version.
+    pub data_version: u32,
+    /// Attribute flags of the resource (moveable, pure, preload, discardable).
+    pub memory_flags: u16,
+    /// The language of the resource.
+    pub language_id: u16,
+    /// User-defined version of the resource data.
+    pub version: u32,
+    /// User-defined characteristics of the resource data.
+    pub characteristics: u32,
+    /// File offset of the resource data.
+    pub data_offset: usize,
+    /// Size of the resource data in bytes.
+    pub data_size: u32
+}
+
+impl fmt::Display for ResourceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Resource")?;
+        writeln!(f, "--------")?;
+        writeln!(f, "Type:                    {}", self.resource_type)?;
+        writeln!(f, "Name:                    {}", self.name)?;
+        writeln!(f, "Language ID:             {:#06x}", self.language_id)?;
+        writeln!(f, "Memory Flags:            {:#06x}", self.memory_flags)?;
+        writeln!(f, "Version:                 {}", self.version)?;
+        writeln!(f, "Characteristics:         {:#010x}", self.characteristics)?;
+        writeln!(f, "Data Offset:             {:#010x}", self.data_offset)?;
+        writeln!(f, "Data Size:               {}", self.data_size)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ResourceFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use core::{fmt, mem::offset_of};
+use crate::{dos::{DosHeader, IMAGE_DOS_HEADER_SIZE, parse_dos_header}, pe::read_u32, prelude::*, Error};
+
+/// Offset of `e_lfanew` in the DOS header, which the checksum skips
+const IMAGE_DOS_NEW_HEADER_OFFSET: usize = offset_of!(DosHeader, e_lfanew);
+/// "Rich", stored unmasked before the checksum key
+pub const RICH_SIGNATURE: u32 = 0x68636952;
+/// "DanS", stored masked at the start of the header
+pub const DANS_SIGNATURE: u32 = 0x536e6144;
+
+/// One entry of the Rich header: how many objects a tool build contributed
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct RichEntry {
+    /// The product identifier of the tool.
+    pub product_id: u16,
+    /// The build number of the tool.
+    pub build: u16,
+    /// The number of objects (or imports, for product 1) the tool produced.
+    pub count: u32,
+}
+
+/// The Rich header the MSVC linker writes into the DOS stub
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct RichHeader {
+    /// The file offset of the masked "DanS" signature.
+    pub offset: usize,
+    /// The checksum key the header is masked with.
+    pub key: u32,
+    /// The decoded entries.
+    pub entries: Vec<RichEntry>,
+}
+
+/// The kind of tool a Rich header product identifier describes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ToolKind {
+    /// The count of imported functions.
+    Imports,
+    /// The linker.
+    Linker,
+    /// The C compiler.
+    CCompiler,
+    /// The C++ compiler.
+    CppCompiler,
+    /// The Microsoft Macro Assembler.
+    Masm,
+    /// The resource compiler (CVTRES).
+    ResourceCompiler,
+    /// The export file generator.
+    Export,
+    /// The import library generator.
+    ImportLibrary,
+    /// Any other tool, such as CVTOMF, CVTPGD or ALIASOBJ.
+    Other,
+}
+
+/// The Visual Studio release a tool shipped with
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VisualStudioVersion {
+    /// Visual Studio 97 (Visual C++ 5.0).
+    Vs97,
+    /// Visual Studio 6.0.
+    Vs6,
+    /// Visual Studio .NET 2002.
+    Vs2002,
+    /// Visual Studio .NET 2003.
+    Vs2003,
+    /// Visual Studio 2005.
+    Vs2005,
+    /// Visual Studio 2008.
+    Vs2008,
+    /// Visual Studio 2010.
+    Vs2010,
+    /// Visual Studio 2012.
+    Vs2012,
+    /// Visual Studio 2013.
+    Vs2013,
+    /// Visual Studio 2015.
+    Vs2015,
+    /// Visual Studio 2017.
+    Vs2017,
+    /// Visual Studio 2019.
+    Vs2019,
+    /// Visual Studio 2022.
+    Vs2022,
+}
+
+/// Parse the Rich header between the DOS header and `e_lfanew`.
+/// Returns `None` if the image has no Rich header.
+pub fn parse_rich_header(binary: &[u8]) -> Result<Option<RichHeader>, Error> {
+    let end = parse_dos_header(binary)?.e_lfanew as usize;
+    let stub = match binary.get(..end.min(binary.len())) {
+        Some(stub) => stub,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    // The header ends with "Rich" and the key, both unmasked
+    let rich = (IMAGE_DOS_HEADER_SIZE..stub.len().saturating_sub(7)).step_by(4)
+        .rev()
+        .find(|offset| read_u32(stub, *offset).ok() == Some(RICH_SIGNATURE));
+    let rich = match rich {
+        Some(rich) => rich,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    let key = read_u32(stub, rich + 4)?;
+    let offset = (IMAGE_DOS_HEADER_SIZE..rich).step_by(4)
+        .rev()
+        .find(|offset| read_u32(stub, *offset).ok().map(|value| value ^ key) == Some(DANS_SIGNATURE));
+    let offset = match offset {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::BadRichHeader);
+        }
+    };
+
+    // "DanS" is followed by three masked zeros, then the entries
+    let mut header = RichHeader { offset, key, entries: Vec::new() };
+    let start = offset + 16;
+    if start > rich || !(rich - start).is_multiple_of(8) {
+        return Err(Error::BadRichHeader);
+    }
+
+    for entry in stub[start..rich].chunks_exact(8) {
+        let id = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) ^ key;
+        let count = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) ^ key;
+        header.entries.push(RichEntry {
+            product_id: (id >> 16) as u16,
+            build: id as u16,
+            count,
+        });
+    }
+
+    Ok(Some(header))
+}
+
+impl RichHeader {
+    /// Compute the checksum the key should equal, from the DOS header and
+    /// stub before the Rich header (skipping `e_lfanew`) and the entries
+    pub fn compute_checksum(&self, binary: &[u8]) -> Result<u32, Error> {
+        let stub = match binary.get(..self.offset) {
+            Some(stub) => stub,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        let mut checksum = self.offset as u32;
+        for (index, byte) in stub.iter().enumerate() {
+            if (IMAGE_DOS_NEW_HEADER_OFFSET..IMAGE_DOS_NEW_HEADER_OFFSET + 4).contains(&index) {
+                continue;
+            }
+            checksum = checksum.wrapping_add(u32::from(*byte).rotate_left(index as u32));
+        }
+
+        for entry in self.entries.iter() {
+            checksum = checksum.wrapping_add(entry.get_comp_id().rotate_left(entry.count));
+        }
+
+        Ok(checksum)
+    }
+
+    /// Returns true if the checksum key matches the computed checksum
+    pub fn is_checksum_valid(&self, binary: &[u8]) -> bool {
+        self.compute_checksum(binary).ok() == Some(self.key)
+    }
+}
+
+impl RichEntry {
+    /// Returns the combined product identifier and build number
+    pub fn get_comp_id(&self) -> u32 {
+        u32::from(self.product_id) << 16 | u32::from(self.build)
+    }
+
+    /// Returns the kind of tool the product identifier describes
+    pub fn get_tool_kind(&self) -> Option<ToolKind> {
+        match self.product_id {
+            0x0001 => Some(ToolKind::Imports),
+            0x0002 | 0x0004 | 0x0010 | 0x0013 | 0x001e | 0x0020 | 0x0025 | 0x0028 | 0x003c | 0x003d | 0x0047 | 0x0056 | 0x005a | 0x0078 | 0x0091 | 0x009d => Some(ToolKind::Linker),
+            0x0008 | 0x000a | 0x0015 | 0x0017 | 0x001c | 0x0023 | 0x002b | 0x0030 | 0x0032 | 0x0034 | 0x0039 | 0x0041 | 0x0043 => Some(ToolKind::CCompiler),
+            0x000b | 0x0016 | 0x0018 | 0x001d | 0x0024 | 0x002c | 0x0031 | 0x0033 | 0x0035 | 0x003a | 0x0042 | 0x0044 => Some(ToolKind::CppCompiler),
+            0x000e | 0x000f | 0x0012 | 0x002a | 0x002d | 0x0040 | 0x004b | 0x007d | 0x0095 | 0x009e => Some(ToolKind::Masm),
+            0x0006 | 0x0038 | 0x0045 | 0x0046 | 0x005e | 0x007c | 0x0094 | 0x0097 | 0x009a => Some(ToolKind::ResourceCompiler),
+            0x003e | 0x003f | 0x0049 | 0x0058 | 0x005c | 0x007a | 0x0092 | 0x009b => Some(ToolKind::Export),
+            0x0019 | 0x0036 | 0x004a | 0x0059 | 0x005d | 0x007b | 0x0093 | 0x009c => Some(ToolKind::ImportLibrary),
+            0x004c | 0x004e | 0x0050 | 0x0052 | 0x0054 | 0x005f | 0x0061 | 0x0063 | 0x0065 | 0x0067 | 0x006d | 0x006f | 0x0071 | 0x0073 | 0x0075 => Some(ToolKind::CCompiler),
+            0x004d | 0x004f | 0x0051 | 0x0053 | 0x0055 | 0x0060 | 0x0062 | 0x0064 | 0x0066 | 0x0068 | 0x006e | 0x0070 | 0x0072 | 0x0074 | 0x0076 => Some(ToolKind::CppCompiler),
+            0x0083 | 0x0085 | 0x0087 | 0x0089 | 0x008c | 0x008e => Some(ToolKind::CCompiler),
+            0x0084 | 0x0086 | 0x0088 | 0x008a | 0x008b | 0x008d | 0x008f => Some(ToolKind::CppCompiler),
+            0x0080 | 0x009f | 0x00a1 | 0x00a3 | 0x00a6 | 0x00a8 => Some(ToolKind::CCompiler),
+            0x0081 | 0x0082 | 0x00a0 | 0x00a2 | 0x00a4 | 0x00a5 | 0x00a7 | 0x00a9 => Some(ToolKind::CppCompiler),
+            0x00aa..=0x00b4 => Some(get_compiler_kind(self.product_id - 0x00aa)),
+            // From Visual C++ 10.1 on, each release has a block of 18 identifiers
+            0x00b5..=0x010e => match (self.product_id - 0x00b5) % 18 {
+                2 => Some(ToolKind::ResourceCompiler),
+                3 => Some(ToolKind::Export),
+                4 => Some(ToolKind::ImportLibrary),
+                5 => Some(ToolKind::Linker),
+                6 => Some(ToolKind::Masm),
+                index @ 7.. => Some(get_compiler_kind(index - 7)),
+                _ => Some(ToolKind::Other),
+            },
+            0x0003..=0x0099 => Some(ToolKind::Other),
+            _ => None,
+        }
+    }
+
+    /// Returns the Visual Studio release the tool shipped with.
+    /// Visual C++ 14.x releases share product identifiers and are told apart by build.
+    pub fn get_visual_studio_version(&self) -> Option<VisualStudioVersion> {
+        match self.product_id {
+            0x0002 | 0x0003 | 0x0006..=0x0008 | 0x0010 | 0x0011 | 0x0013 | 0x0014 => Some(VisualStudioVersion::Vs97),
+            0x0004 | 0x0005 | 0x0009..=0x000e | 0x0012 | 0x0015..=0x0018 | 0x001e..=0x0026 | 0x0028..=0x002a | 0x002d | 0x002f..=0x0038 | 0x003c | 0x003e | 0x0056..=0x0059 => Some(VisualStudioVersion::Vs6),
+            0x0019..=0x001d | 0x0027 | 0x002b | 0x002c | 0x002e | 0x0039..=0x003b | 0x003d | 0x003f..=0x0045 => Some(VisualStudioVersion::Vs2002),
+            0x000f | 0x0046..=0x0055 | 0x005a..=0x006c => Some(VisualStudioVersion::Vs2003),
+            0x006d..=0x007e | 0x0080..=0x0082 => Some(VisualStudioVersion::Vs2005),
+            0x0083..=0x0096 => Some(VisualStudioVersion::Vs2008),
+            0x0098..=0x00c6 => Some(VisualStudioVersion::Vs2010),
+            0x00c7..=0x00d8 => Some(VisualStudioVersion::Vs2012),
+            0x00d9..=0x00fc => Some(VisualStudioVersion::Vs2013),
+            0x00fd..=0x010e => match self.build {
+                0..=24999 => Some(VisualStudioVersion::Vs2015),
+                25000..=27499 => Some(VisualStudioVersion::Vs2017),
+                27500..=30399 => Some(VisualStudioVersion::Vs2019),
+                _ => Some(VisualStudioVersion::Vs2022),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Get the kind of compiler at an index of a Visual C++ 10.0+ compiler block,
+/// where C and C++ alternate around the MSIL code generator
+fn get_compiler_kind(index: u16) -> ToolKind {
+    match index {
+        6 => ToolKind::CppCompiler,
+        0..=5 if index.is_multiple_of(2) => ToolKind::CCompiler,
+        7.. if !index.is_multiple_of(2) => ToolKind::CCompiler,
+        _ => ToolKind::CppCompiler,
+    }
+}
+
+impl fmt::Display for ToolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolKind::Imports => f.write_str("Imports"),
+            ToolKind::Linker => f.write_str("Linker"),
+            ToolKind::CCompiler => f.write_str("C Compiler"),
+            ToolKind::CppCompiler => f.write_str("C++ Compiler"),
+            ToolKind::Masm => f.write_str("MASM"),
+            ToolKind::ResourceCompiler => f.write_str("Resource Compiler"),
+            ToolKind::Export => f.write_str("Export"),
+            ToolKind::ImportLibrary => f.write_str("Import Library"),
+            ToolKind::Other => f.write_str("Other"),
+        }
+    }
+}
+
+impl fmt::Display for VisualStudioVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisualStudioVersion::Vs97 => f.write_str("Visual Studio 97"),
+            VisualStudioVersion::Vs6 => f.write_str("Visual Studio 6.0"),
+            VisualStudioVersion::Vs2002 => f.write_str("Visual Studio .NET 2002"),
+            VisualStudioVersion::Vs2003 => f.write_str("Visual Studio .NET 2003"),
+            VisualStudioVersion::Vs2005 => f.write_str("Visual Studio 2005"),
+            VisualStudioVersion::Vs2008 => f.write_str("Visual Studio 2008"),
+            VisualStudioVersion::Vs2010 => f.write_str("Visual Studio 2010"),
+            VisualStudioVersion::Vs2012 => f.write_str("Visual Studio 2012"),
+            VisualStudioVersion::Vs2013 => f.write_str("Visual Studio 2013"),
+            VisualStudioVersion::Vs2015 => f.write_str("Visual Studio 2015"),
+            VisualStudioVersion::Vs2017 => f.write_str("Visual Studio 2017"),
+            VisualStudioVersion::Vs2019 => f.write_str("Visual Studio 2019"),
+            VisualStudioVersion::Vs2022 => f.write_str("Visual Studio 2022"),
+        }
+    }
+}
+
+impl fmt::Display for RichEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x} {:>5} {:>6}", self.product_id, self.build, self.count)?;
+        if let Some(kind) = self.get_tool_kind() {
+            write!(f, "  {}", kind)?;
+        }
+        if let Some(version) = self.get_visual_studio_version() {
+            write!(f, " ({})", version)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RichHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rich Header")?;
+        writeln!(f, "-----------")?;
+        writeln!(f, "Offset:                  {:#x}", self.offset)?;
+        writeln!(f, "Checksum Key:            {:#010x}", self.key)?;
+        writeln!(f, "Entries:                 {}", self.entries.len())?;
+        writeln!(f, "Product Build  Count  Tool")?;
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
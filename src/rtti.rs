@@ -0,0 +1,455 @@
+use alloc::collections::BTreeMap;
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable};
+use core::{fmt, str};
+use crate::{load_config::read_versioned, pe::{PortableExecutable, read_c_string, read_u32}, prelude::*, section::{SectionFlags, SectionHeader}, Error};
+
+/// `CompleteObjectLocator` signature of x86 images, whose references are VAs
+pub const COL_SIGNATURE32: u32 = 0;
+/// `CompleteObjectLocator` signature of x64 images, whose references are RVAs
+pub const COL_SIGNATURE64: u32 = 1;
+/// Prefix of the decorated name of a class
+pub const CLASS_NAME_PREFIX: &str = ".?AV";
+/// Prefix of the decorated name of a struct
+pub const STRUCT_NAME_PREFIX: &str = ".?AU";
+
+/// `RTTICompleteObjectLocator`, pointed to by the slot before a vtable.
+/// References are RVAs on x64 and VAs on x86.
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct CompleteObjectLocator {
+    /// `COL_SIGNATURE32` or `COL_SIGNATURE64`.
+    pub signature: u32,
+    /// The offset of the vtable within the complete class.
+    pub offset: u32,
+    /// The constructor displacement offset.
+    pub cd_offset: u32,
+    /// The class's `TypeDescriptor`.
+    pub type_descriptor: u32,
+    /// The class's `RTTIClassHierarchyDescriptor`.
+    pub class_descriptor: u32,
+    /// The RVA of the locator itself (x64 only).
+    pub object_base: u32,
+}
+
+/// `RTTIClassHierarchyDescriptor`, describing the bases of a class
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct ClassHierarchyDescriptor {
+    /// Always zero.
+    pub signature: u32,
+    /// Inheritance attributes; see `ClassHierarchyAttributes`.
+    pub attributes: u32,
+    /// The number of entries in the base class array, including the class itself.
+    pub num_base_classes: u32,
+    /// The `RTTIBaseClassArray`, an array of references to base class descriptors.
+    pub base_class_array: u32,
+}
+
+/// `RTTIBaseClassDescriptor`, describing one base of a class
+#[derive(Copy, Clone, Pod, Zeroable, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct BaseClassDescriptor {
+    /// The base's `TypeDescriptor`.
+    pub type_descriptor: u32,
+    /// The number of bases the base itself has.
+    pub num_contained_bases: u32,
+    /// The displacement of the base within the class.
+    pub mdisp: i32,
+    /// The displacement of the virtual base table pointer, or -1 if the base is not virtual.
+    pub pdisp: i32,
+    /// The displacement of the base within the virtual base table.
+    pub vdisp: i32,
+    /// Attributes; see `BaseClassAttributes`.
+    pub attributes: u32,
+    /// The base's `RTTIClassHierarchyDescriptor` (with `BCD_HASPCHD`).
+    pub class_descriptor: u32,
+}
+
+bitflags! {
+    /// Attributes of a class hierarchy descriptor
+    pub struct ClassHierarchyAttributes: u32 {
+        /// The class uses multiple inheritance.
+        const CHD_MULTINH = 0x00000001;
+        /// The class uses virtual inheritance.
+        const CHD_VIRTINH = 0x00000002;
+        /// The class has an ambiguous base.
+        const CHD_AMBIGUOUS = 0x00000004;
+    }
+}
+
+// Allow Class hierarchy attributes to be easily printed
+impl fmt::Debug for ClassHierarchyAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for ClassHierarchyAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for ClassHierarchyAttributes {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+bitflags! {
+    /// Attributes of a base class descriptor
+    pub struct BaseClassAttributes: u32 {
+        /// The base is not visible.
+        const BCD_NOTVISIBLE = 0x00000001;
+        /// The base is ambiguous.
+        const BCD_AMBIGUOUS = 0x00000002;
+        /// The base is private or protected.
+        const BCD_PRIVORPROTBASE = 0x00000004;
+        /// The base is private or protected in the complete object.
+        const BCD_PRIVORPROTINCOMPOBJ = 0x00000008;
+        /// The base is a virtual base of the complete object.
+        const BCD_VBOFCONTOBJ = 0x00000010;
+        /// The base is not polymorphic.
+        const BCD_NONPOLYMORPHIC = 0x00000020;
+        /// The descriptor has a class hierarchy descriptor.
+        const BCD_HASPCHD = 0x00000040;
+    }
+}
+
+// Allow Base class attributes to be easily printed
+impl fmt::Debug for BaseClassAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for BaseClassAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for BaseClassAttributes {
+    type Err = bitflags::parser::ParseError;
+
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        Ok(Self(flags.parse()?))
+    }
+}
+
+/// A `TypeDescriptor`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDescriptor {
+    /// The VA of the `type_info` vtable, usually 0 on disk.
+    pub vftable: u64,
+    /// The decorated name, such as `.?AVexception@std@@`.
+    pub name: String
+}
+
+/// A base class, with its descriptor and name
+#[derive(Clone, Debug, PartialEq)]
+pub struct RttiBaseClass {
+    /// The base class descriptor.
+    pub descriptor: BaseClassDescriptor,
+    /// The base's type descriptor.
+    pub type_descriptor: TypeDescriptor
+}
+
+/// A complete object locator and the structures it references
+#[derive(Clone, Debug, PartialEq)]
+pub struct RttiObjectLocator {
+    /// The RVA of the locator.
+    pub rva: u32,
+    /// The locator.
+    pub locator: CompleteObjectLocator,
+    /// The class's type descriptor.
+    pub type_descriptor: TypeDescriptor,
+    /// The class hierarchy descriptor.
+    pub hierarchy: ClassHierarchyDescriptor,
+    /// The base class array, starting with the class itself.
+    pub base_classes: Vec<RttiBaseClass>
+}
+
+/// A vtable found through the locator pointer that precedes it
+#[derive(Clone, Debug, PartialEq)]
+pub struct RttiVtable {
+    /// The RVA of the vtable's first entry.
+    pub rva: u32,
+    /// The locator of the vtable's class.
+    pub locator: RttiObjectLocator
+}
+
+/// Translate an RTTI reference, an RVA on x64 and a VA on x86, into an RVA
+fn reference_to_rva(pe: &PortableExecutable, reference: u32) -> Option<u32> {
+    if pe.is_64_bit() {
+        Some(reference)
+    } else {
+        pe.va_to_rva(reference.into())
+    }
+}
+
+fn read_structure<T: Pod>(binary: &[u8], pe: &PortableExecutable, rva: u32, size: usize) -> Result<T, Error> {
+    match pe.rva_to_offset(rva).and_then(|offset| binary.get(offset..)) {
+        Some(data) if data.len() >= size => read_versioned::<T>(data, size),
+        _ => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Parse a `TypeDescriptor`, whose name follows two pointers
+pub fn parse_type_descriptor(binary: &[u8], pe: &PortableExecutable, rva: u32) -> Result<TypeDescriptor, Error> {
+    let pointer_size: u32 = if pe.is_64_bit() { 8 } else { 4 };
+    let offset = match pe.rva_to_offset(rva) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let vftable = if pe.is_64_bit() {
+        u64::from(read_u32(binary, offset)?) | u64::from(read_u32(binary, offset + 4)?) << 32
+    } else {
+        read_u32(binary, offset)?.into()
+    };
+
+    match rva.checked_add(pointer_size * 2).and_then(|rva| pe.rva_to_offset(rva)) {
+        Some(offset) => Ok(TypeDescriptor { vftable, name: read_c_string(binary, offset)? }),
+        None => Err(Error::OffsetOutOfRange),
+    }
+}
+
+/// Parse a complete object locator, its type descriptor,
+/// class hierarchy descriptor and base classes
+pub fn parse_complete_object_locator(binary: &[u8], pe: &PortableExecutable, rva: u32) -> Result<RttiObjectLocator, Error> {
+    // x86 locators end before the self reference
+    let locator_size = if pe.is_64_bit() { size_of::<CompleteObjectLocator>() } else { 20 };
+    let locator = read_structure::<CompleteObjectLocator>(binary, pe, rva, locator_size)?;
+    let expected_signature = if pe.is_64_bit() { COL_SIGNATURE64 } else { COL_SIGNATURE32 };
+    if locator.signature != expected_signature {
+        return Err(Error::BadRtti);
+    }
+
+    let type_descriptor = match reference_to_rva(pe, locator.type_descriptor) {
+        Some(type_descriptor) => parse_type_descriptor(binary, pe, type_descriptor)?,
+        None => {
+            return Err(Error::BadRtti);
+        }
+    };
+
+    let hierarchy_rva = match reference_to_rva(pe, locator.class_descriptor) {
+        Some(hierarchy_rva) => hierarchy_rva,
+        None => {
+            return Err(Error::BadRtti);
+        }
+    };
+    let hierarchy = read_structure::<ClassHierarchyDescriptor>(binary, pe, hierarchy_rva, size_of::<ClassHierarchyDescriptor>())?;
+
+    let mut object_locator = RttiObjectLocator {
+        rva,
+        locator,
+        type_descriptor,
+        hierarchy,
+        base_classes: Vec::new(),
+    };
+
+    let array = match reference_to_rva(pe, hierarchy.base_class_array).and_then(|rva| pe.rva_to_offset(rva)) {
+        Some(array) => array,
+        None => {
+            return Err(Error::BadRtti);
+        }
+    };
+
+    for index in 0..hierarchy.num_base_classes as usize {
+        let descriptor_rva = match index.checked_mul(4).and_then(|delta| array.checked_add(delta)) {
+            Some(offset) => reference_to_rva(pe, read_u32(binary, offset)?),
+            None => None,
+        };
+
+        let descriptor_rva = match descriptor_rva {
+            Some(descriptor_rva) => descriptor_rva,
+            None => {
+                return Err(Error::BadRtti);
+            }
+        };
+
+        // x86 descriptors end before the hierarchy reference unless they have one
+        let descriptor = read_structure::<BaseClassDescriptor>(binary, pe, descriptor_rva, 24)?;
+        let descriptor = if pe.is_64_bit() || BaseClassAttributes::from_bits_retain(descriptor.attributes).contains(BaseClassAttributes::BCD_HASPCHD) {
+            read_structure::<BaseClassDescriptor>(binary, pe, descriptor_rva, size_of::<BaseClassDescriptor>())?
+        } else {
+            descriptor
+        };
+
+        let type_descriptor = match reference_to_rva(pe, descriptor.type_descriptor) {
+            Some(type_descriptor) => parse_type_descriptor(binary, pe, type_descriptor)?,
+            None => {
+                return Err(Error::BadRtti);
+            }
+        };
+
+        object_locator.base_classes.push(RttiBaseClass { descriptor, type_descriptor });
+    }
+
+    Ok(object_locator)
+}
+
+/// Scan the initialized data sections of an image for complete object locators.
+/// On x64 a locator is recognized by its self reference; on x86, by its
+/// type descriptor naming a class or struct.
+pub fn find_complete_object_locators(binary: &[u8], pe: &PortableExecutable) -> Vec<RttiObjectLocator> {
+    let mut locators: Vec<RttiObjectLocator> = Vec::new();
+    let expected_signature = if pe.is_64_bit() { COL_SIGNATURE64 } else { COL_SIGNATURE32 };
+
+    for section in pe.section_table.iter().filter(|section| is_data_section(section)) {
+        let data = match get_section_data(binary, section) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for position in (0..data.len().saturating_sub(19)).step_by(4) {
+            let rva = match section.virtual_address.checked_add(position as u32) {
+                Some(rva) => rva,
+                None => break,
+            };
+            if read_u32(data, position).ok() != Some(expected_signature) {
+                continue;
+            }
+            if pe.is_64_bit() && read_u32(data, position + 20).ok() != Some(rva) {
+                continue;
+            }
+
+            if let Ok(locator) = parse_complete_object_locator(binary, pe, rva)
+                && locator.type_descriptor.is_class() {
+                locators.push(locator);
+            }
+        }
+    }
+
+    locators
+}
+
+/// Scan the initialized data sections of an image for vtables, each
+/// preceded by a pointer to the complete object locator of its class
+pub fn find_vtables(binary: &[u8], pe: &PortableExecutable) -> Vec<RttiVtable> {
+    let mut vtables: Vec<RttiVtable> = Vec::new();
+    let locators: BTreeMap<u64, RttiObjectLocator> = find_complete_object_locators(binary, pe).into_iter()
+        .map(|locator| (u64::from(locator.rva), locator))
+        .collect();
+    if locators.is_empty() {
+        return vtables;
+    }
+
+    let pointer_size = if pe.is_64_bit() { 8 } else { 4 };
+    let image_base = pe.get_image_base();
+    for section in pe.section_table.iter().filter(|section| is_data_section(section)) {
+        let data = match get_section_data(binary, section) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for (index, slot) in data.chunks_exact(pointer_size).enumerate() {
+            let pointer = if pe.is_64_bit() {
+                u64::from_le_bytes([slot[0], slot[1], slot[2], slot[3], slot[4], slot[5], slot[6], slot[7]])
+            } else {
+                u32::from_le_bytes([slot[0], slot[1], slot[2], slot[3]]).into()
+            };
+
+            let locator = pointer.checked_sub(image_base).and_then(|rva| locators.get(&rva));
+            let rva = u32::try_from((index + 1) * pointer_size).ok()
+                .and_then(|offset| section.virtual_address.checked_add(offset));
+            if let (Some(locator), Some(rva)) = (locator, rva) {
+                vtables.push(RttiVtable {
+                    rva,
+                    locator: locator.clone(),
+                });
+            }
+        }
+    }
+
+    vtables
+}
+
+fn is_data_section(section: &SectionHeader) -> bool {
+    let flags = SectionFlags::from_bits_retain(section.characteristics);
+    flags.contains(SectionFlags::IMAGE_SCN_CNT_INITALIZED_DATA) && !flags.contains(SectionFlags::IMAGE_SCN_MEM_EXECUTE)
+}
+
+fn get_section_data<'a>(binary: &'a [u8], section: &SectionHeader) -> Option<&'a [u8]> {
+    let start = section.pointer_to_raw_data as usize;
+    let size = section.size_of_raw_data.min(section.virtual_size.max(1)) as usize;
+    binary.get(start..start.checked_add(size)?)
+}
+
+impl TypeDescriptor {
+    /// Returns true if the type is a class or struct
+    pub fn is_class(&self) -> bool {
+        self.name.starts_with(CLASS_NAME_PREFIX) || self.name.starts_with(STRUCT_NAME_PREFIX)
+    }
+
+    /// Returns the undecorated name of a class or struct, such as
+    /// `std::exception` for `.?AVexception@std@@`.
+    /// Names using templates or other special forms are returned decorated.
+    pub fn get_class_name(&self) -> String {
+        let name = match self.name.strip_prefix(CLASS_NAME_PREFIX).or_else(|| self.name.strip_prefix(STRUCT_NAME_PREFIX)) {
+            Some(name) => name,
+            None => {
+                return self.name.clone();
+            }
+        };
+
+        match name.strip_suffix("@@") {
+            Some(name) if !name.contains(['?', '$']) => {
+                let mut parts: Vec<&str> = name.split('@').collect();
+                parts.reverse();
+                parts.join("::")
+            }
+            _ => self.name.clone(),
+        }
+    }
+}
+
+impl RttiObjectLocator {
+    /// Returns the hierarchy attributes as bitflags
+    pub fn get_attributes(&self) -> ClassHierarchyAttributes {
+        ClassHierarchyAttributes::from_bits_retain(self.hierarchy.attributes)
+    }
+}
+
+impl RttiBaseClass {
+    /// Returns the base class attributes as bitflags
+    pub fn get_attributes(&self) -> BaseClassAttributes {
+        BaseClassAttributes::from_bits_retain(self.descriptor.attributes)
+    }
+}
+
+impl fmt::Display for RttiVtable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Vtable")?;
+        writeln!(f, "------")?;
+        writeln!(f, "Address:                 {:#010x}", self.rva)?;
+        write!(f, "{}", self.locator)
+    }
+}
+
+impl fmt::Display for RttiObjectLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Class:                   {}", self.type_descriptor.get_class_name())?;
+        writeln!(f, "Decorated Name:          {}", self.type_descriptor.name)?;
+        writeln!(f, "Object Locator:          {:#010x}", self.rva)?;
+        writeln!(f, "Vtable Offset:           {:#x}", self.locator.offset)?;
+        writeln!(f, "Constructor Disp Offset: {:#x}", self.locator.cd_offset)?;
+        writeln!(f, "Hierarchy Attributes:    {}", self.get_attributes())?;
+        for base in self.base_classes.iter() {
+            write!(f, "Base Class:              {} (mdisp {}, pdisp {}, vdisp {})", base.type_descriptor.get_class_name(),
+                base.descriptor.mdisp, base.descriptor.pdisp, base.descriptor.vdisp)?;
+            if !base.get_attributes().is_empty() {
+                write!(f, " {}", base.get_attributes())?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
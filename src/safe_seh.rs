@@ -0,0 +1,116 @@
+use core::fmt;
+use crate::{coff::MachineTypes, load_config::parse_load_config, optional::DLLCharacteristics, pe::PortableExecutable, prelude::*, section::{SectionFlags, SectionHeader}, Error};
+
+/// Name of the section listing an object's safe exception handlers
+pub const SXDATA_SECTION_NAME: [u8; 8] = *b".sxdata\0";
+/// Name of the absolute symbol holding an object's feature flags
+pub const FEAT00_SYMBOL_NAME: &str = "@feat.00";
+/// `@feat.00` bit set when the object's handlers are registered in `.sxdata`
+pub const FEAT00_SAFE_SEH: u32 = 0x1;
+
+/// Returns true if a section is an object's `.sxdata` section
+pub fn is_sxdata_section(section: &SectionHeader) -> bool {
+    section.name == SXDATA_SECTION_NAME
+        && SectionFlags::from_bits_retain(section.characteristics).contains(SectionFlags::IMAGE_SCN_LNK_INFO)
+}
+
+/// Parse the symbol table indices of the exception handlers
+/// registered in an object's `.sxdata` section
+pub fn parse_sxdata_section(binary: &[u8], section: &SectionHeader) -> Result<Vec<u32>, Error> {
+    let start = section.pointer_to_raw_data as usize;
+    let data = match start.checked_add(section.size_of_raw_data as usize).and_then(|end| binary.get(start..end)) {
+        Some(data) => data,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    Ok(data.chunks_exact(4)
+        .map(|index| u32::from_le_bytes([index[0], index[1], index[2], index[3]]))
+        .collect())
+}
+
+/// Parse the RVAs of the safe exception handlers of an x86 image,
+/// from the load configuration's SE handler table.
+/// Returns an empty list if the image has no table.
+pub fn parse_se_handler_table(binary: &[u8], pe: &PortableExecutable) -> Result<Vec<u32>, Error> {
+    let mut handlers: Vec<u32> = Vec::new();
+    let config = match parse_load_config(binary, pe)? {
+        Some(config) => config,
+        None => {
+            return Ok(handlers);
+        }
+    };
+
+    if config.se_handler_table == 0 || config.se_handler_count == 0 {
+        return Ok(handlers);
+    }
+
+    let offset = match pe.va_to_rva(config.se_handler_table).and_then(|rva| pe.rva_to_offset(rva)) {
+        Some(offset) => offset,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    let table = match usize::try_from(config.se_handler_count).ok().and_then(|count| count.checked_mul(4)).and_then(|size| binary.get(offset..offset.checked_add(size)?)) {
+        Some(table) => table,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    for handler in table.chunks_exact(4) {
+        handlers.push(u32::from_le_bytes([handler[0], handler[1], handler[2], handler[3]]));
+    }
+
+    Ok(handlers)
+}
+
+/// Whether an image only dispatches exceptions to registered handlers
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SafeSehStatus {
+    /// The image is not x86, where exception handling is table based.
+    NotApplicable,
+    /// The image declares it uses no structured exception handlers.
+    NoSeh,
+    /// The image has a safe exception handler table.
+    SafeSeh,
+    /// The image may use handlers that are not registered.
+    NotSafeSeh,
+}
+
+/// Report whether an x86 image is SafeSEH compliant, from
+/// `IMAGE_DLLCHARACTERISTICS_NO_SEH` and its SE handler table
+pub fn get_safe_seh_status(binary: &[u8], pe: &PortableExecutable) -> Result<SafeSehStatus, Error> {
+    if pe.coff.get_machine_type() != Some(MachineTypes::I386) {
+        return Ok(SafeSehStatus::NotApplicable);
+    }
+
+    let dll_characteristics = match (&pe.optional_header_32, &pe.optional_header_64) {
+        (Some(header), _) => header.dll_characteristics,
+        (None, Some(header)) => header.dll_characteristics,
+        (None, None) => 0,
+    };
+
+    if DLLCharacteristics::from_bits_retain(dll_characteristics).contains(DLLCharacteristics::IMAGE_DLLCHARACTERISTICS_NO_SEH) {
+        return Ok(SafeSehStatus::NoSeh);
+    }
+
+    // An empty table still restricts dispatch to registered handlers
+    match parse_load_config(binary, pe)? {
+        Some(config) if config.se_handler_table != 0 => Ok(SafeSehStatus::SafeSeh),
+        _ => Ok(SafeSehStatus::NotSafeSeh),
+    }
+}
+
+impl fmt::Display for SafeSehStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SafeSehStatus::NotApplicable => f.write_str("Not applicable (not x86)"),
+            SafeSehStatus::NoSeh => f.write_str("No SEH"),
+            SafeSehStatus::SafeSeh => f.write_str("SafeSEH"),
+            SafeSehStatus::NotSafeSeh => f.write_str("Not SafeSEH"),
+        }
+    }
+}
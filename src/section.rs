@@ -1,23 +1,24 @@
-use bytemuck::checked::try_from_bytes;
-use bytemuck::{Pod, Zeroable};
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
 use bitflags::bitflags;
 use core::{fmt, str};
 use core::writeln;
-use crate::prelude::*;
+use crate::{prelude::*, string_table::{StringTable, decode_long_name_offset}};
 
 /// Parse the section table from a byte array at a given offset.
 /// `number_of_sections` should be equal to number of sections
-/// defined in the COFF header.
-pub fn parse_section_table(binary: &[u8], offset: usize, number_of_sections: u16) -> Vec<SectionHeader> {
+/// defined in the COFF header (or big object header).
+pub fn parse_section_table(binary: &[u8], offset: usize, number_of_sections: impl Into<u32>) -> Vec<SectionHeader> {
     let mut offset = offset;
     let mut headers: Vec<SectionHeader> = Vec::new();
     let header_size = size_of::<SectionHeader>();
 
-    for _ in 0..number_of_sections {
-        if let Some(slice) = binary.get(offset..offset+header_size) {
-            if let Ok(header) = try_from_bytes::<SectionHeader>(slice) {
-                headers.push(*header);
-            }
+    for _ in 0..number_of_sections.into() {
+        let Some(slice) = binary.get(offset..offset+header_size) else {
+            break;
+        };
+
+        if let Ok(header) = try_pod_read_unaligned::<SectionHeader>(slice) {
+            headers.push(header);
         }
         offset += header_size;
     }
@@ -72,25 +73,43 @@ pub struct SectionHeader {
     pub characteristics: u32
 }
 
+/// A section header paired with the string table its long name refers to,
+/// so that it is printed with its resolved name
+pub struct ResolvedSectionHeader<'a> {
+    /// The section header.
+    pub header: &'a SectionHeader,
+    /// The COFF string table, if the file has one.
+    pub string_table: Option<&'a StringTable>
+}
+
 impl fmt::Display for SectionHeader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = self.get_name()
-            .expect("Failed to get name");
-        let characteristics = self.get_characteristics()
-            .expect("Failed to get characteristics");
+        write!(f, "{}", self.with_string_table(None))
+    }
+}
+
+impl fmt::Display for ResolvedSectionHeader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = self.header;
 
         writeln!(f, "Section Header")?;
         writeln!(f, "--------------")?;
-        writeln!(f, "Name:                    {:?}", name)?;
-        writeln!(f, "Virtual Size:            {}", self.virtual_size)?;
-        writeln!(f, "Virtual Address:         {:#010x}", self.virtual_address)?;
-        writeln!(f, "Size of Raw Data:        {}", self.size_of_raw_data)?;
-        writeln!(f, "Pointer to Raw Data:     {}", self.pointer_to_raw_data)?;
-        writeln!(f, "Pointer to Relocations:  {}", self.pointer_to_relocations)?;
-        writeln!(f, "Pointer to Line-numbers: {}", self.pointer_to_line_numbers)?;
-        writeln!(f, "Number of Relocations:   {}", self.number_of_relocations)?;
-        writeln!(f, "Number of Line-numbers:  {}", self.number_of_line_numbers)?;
-        writeln!(f, "Characteristics:         {}", characteristics)?;
+        match header.get_name(self.string_table) {
+            Some(name) => writeln!(f, "Name:                    {:?}", name)?,
+            None => writeln!(f, "Name:                    {:?}", String::from_utf8_lossy(&header.name))?,
+        }
+        writeln!(f, "Virtual Size:            {}", header.virtual_size)?;
+        writeln!(f, "Virtual Address:         {:#010x}", header.virtual_address)?;
+        writeln!(f, "Size of Raw Data:        {}", header.size_of_raw_data)?;
+        writeln!(f, "Pointer to Raw Data:     {}", header.pointer_to_raw_data)?;
+        writeln!(f, "Pointer to Relocations:  {}", header.pointer_to_relocations)?;
+        writeln!(f, "Pointer to Line-numbers: {}", header.pointer_to_line_numbers)?;
+        writeln!(f, "Number of Relocations:   {}", header.number_of_relocations)?;
+        writeln!(f, "Number of Line-numbers:  {}", header.number_of_line_numbers)?;
+        match header.get_characteristics() {
+            Some(characteristics) => writeln!(f, "Characteristics:         {}", characteristics)?,
+            None => writeln!(f, "Characteristics:         {:#010x}", header.characteristics)?,
+        }
 
         Ok(())
     }
@@ -230,14 +249,56 @@ impl str::FromStr for SectionFlags {
 }
 
 impl SectionHeader {
-    /// Get the name of a section as a string.
-    /// Note that this string may contain null characters.
-    pub fn get_name(&self) -> Option<String> {
-        String::from_utf8(self.name.to_vec()).ok()
+    /// Get the name of a section as a string, with trailing nulls removed.
+    /// Long names (`/123` or `//AAAAAA`) are resolved through the COFF
+    /// string table if one is given, and returned as-is otherwise.
+    pub fn get_name(&self, string_table: Option<&StringTable>) -> Option<String> {
+        let end = self.name.iter().position(|&c| c == 0).unwrap_or(self.name.len());
+        let name = String::from_utf8(self.name[..end].to_vec()).ok()?;
+
+        match (string_table, decode_long_name_offset(&name)) {
+            (Some(string_table), Some(offset)) => string_table.get_string(offset),
+            _ => Some(name),
+        }
+    }
+
+    /// Pair the section header with a string table, so that
+    /// it is printed with its long name resolved
+    pub fn with_string_table<'a>(&'a self, string_table: Option<&'a StringTable>) -> ResolvedSectionHeader<'a> {
+        ResolvedSectionHeader { header: self, string_table }
     }
 
     /// Returns the Section Characteristics as bitflags
     pub fn get_characteristics(&self) -> Option<SectionFlags> {
         SectionFlags::from_bits(self.characteristics)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string_table::parse_string_table;
+
+    fn section(name: &[u8], characteristics: u32) -> SectionHeader {
+        let mut header = SectionHeader { characteristics, ..Default::default() };
+        header.name[..name.len()].copy_from_slice(name);
+        header
+    }
+
+    #[test]
+    fn long_names_are_resolved() {
+        let mut binary = 16u32.to_le_bytes().to_vec();
+        binary.extend_from_slice(b".debug_info\0");
+        let string_table = parse_string_table(&binary, 0).unwrap();
+
+        assert_eq!(section(b"/4", 0).get_name(Some(&string_table)).as_deref(), Some(".debug_info"));
+        assert_eq!(section(b"//AAAAAE", 0).get_name(Some(&string_table)).as_deref(), Some(".debug_info"));
+        assert_eq!(section(b"/4", 0).get_name(None).as_deref(), Some("/4"));
+        assert_eq!(section(b".text", 0).get_name(Some(&string_table)).as_deref(), Some(".text"));
+    }
+
+    #[test]
+    fn undefined_characteristics_are_printed_raw() {
+        let text = format!("{}", section(b".text", 0x60004020));
+        assert!(text.contains("Characteristics:         0x60004020"));
+    }
+}
@@ -0,0 +1,121 @@
+use core::fmt;
+use crate::{prelude::*, Error};
+
+/// Parse the COFF string table located at the given offset.
+/// The string table immediately follows the symbol table, so for
+/// regular object files and images the offset is
+/// `pointer_to_symbol_table + number_of_symbols * SYMBOL_SIZE`.
+pub fn parse_string_table(binary: &[u8], offset: usize) -> Result<StringTable, Error> {
+    let size = match binary.get(offset..offset+4) {
+        Some(slice) => u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]),
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    // The size includes the size field itself, so anything
+    // smaller than 4 describes an empty table
+    let end = offset + (size as usize).max(4);
+    let data = match binary.get(offset..end) {
+        Some(slice) => slice.to_vec(),
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    Ok(StringTable { size, data })
+}
+
+/// COFF String Table (Object and Image)
+/// Holds the names of symbols and sections that
+/// do not fit in their 8-byte name fields.
+#[derive(Clone, Default)]
+pub struct StringTable {
+    /// The total size of the string table in bytes, including the size field itself.
+    pub size: u32,
+    /// Raw contents of the table, including the 4-byte size field,
+    /// so that offsets from symbols and section names index it directly.
+    pub data: Vec<u8>
+}
+
+impl fmt::Display for StringTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "String Table")?;
+        writeln!(f, "------------")?;
+        writeln!(f, "Size:                    {}", self.size)?;
+
+        for (offset, string) in self.iter() {
+            writeln!(f, "{:#010x}:              {:?}", offset, string)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StringTable {
+    /// Get the null-terminated string starting at `offset`.
+    /// Offsets are relative to the start of the table, so
+    /// the first string is at offset 4.
+    pub fn get_string(&self, offset: u32) -> Option<String> {
+        let offset = offset as usize;
+        if offset < 4 {
+            return None;
+        }
+
+        let slice = self.data.get(offset..)?;
+        let end = slice.iter().position(|&c| c == 0).unwrap_or(slice.len());
+        String::from_utf8(slice[..end].to_vec()).ok()
+    }
+
+    /// Iterate over every string in the table along with its offset
+    pub fn iter(&self) -> impl Iterator<Item = (u32, String)> + '_ {
+        let mut offset = 4;
+        core::iter::from_fn(move || {
+            while offset < self.data.len() {
+                let start = offset;
+                let slice = &self.data[start..];
+                let end = slice.iter().position(|&c| c == 0).unwrap_or(slice.len());
+                offset += end + 1;
+                if let Ok(string) = String::from_utf8(slice[..end].to_vec()) {
+                    return Some((start as u32, string));
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Decode the offset of a long section name.
+/// Long names are either `/` followed by an ASCII decimal number,
+/// or `//` followed by up to six base64 digits for offsets that
+/// do not fit in seven decimal digits.
+pub fn decode_long_name_offset(name: &str) -> Option<u32> {
+    if let Some(digits) = name.strip_prefix("//") {
+        if digits.is_empty() || digits.len() > 6 {
+            return None;
+        }
+
+        let mut offset: u64 = 0;
+        for c in digits.bytes() {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            offset = offset * 64 + value as u64;
+        }
+
+        u32::try_from(offset).ok()
+    } else if let Some(digits) = name.strip_prefix('/') {
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,705 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use core::fmt;
+use crate::{prelude::*, comdat::ComdatSelection, string_table::StringTable, Error};
+
+/// Size in bytes of a standard COFF symbol table record
+/// and each of its auxiliary records.
+pub const SYMBOL_SIZE: usize = 18;
+/// Size in bytes of a big object symbol table record
+/// and each of its auxiliary records.
+pub const SYMBOL_EX_SIZE: usize = 20;
+
+/// Parse the COFF symbol table from a byte array at a given offset.
+/// `number_of_symbols` should be equal to number of symbols defined
+/// in the COFF header, which counts auxiliary records as well.
+/// Each returned symbol carries its decoded auxiliary records.
+pub fn parse_symbol_table(binary: &[u8], offset: usize, number_of_symbols: u32) -> Result<Vec<Symbol>, Error> {
+    parse_symbols(binary, offset, number_of_symbols, SYMBOL_SIZE, |slice| {
+        let record = try_pod_read_unaligned::<CoffSymbol>(slice).ok()?;
+        Some(Symbol {
+            index: 0,
+            name: record.name,
+            value: record.value,
+            section_number: record.section_number.into(),
+            symbol_type: record.symbol_type,
+            storage_class: record.storage_class,
+            number_of_aux_symbols: record.number_of_aux_symbols,
+            aux_symbols: Vec::new(),
+        })
+    })
+}
+
+/// Parse the symbol table of a big object file (`/bigobj`) from a byte array at a given offset.
+/// Big object symbols use 20-byte records with 32-bit section numbers,
+/// but are otherwise decoded the same way as `parse_symbol_table`.
+pub fn parse_symbol_table_ex(binary: &[u8], offset: usize, number_of_symbols: u32) -> Result<Vec<Symbol>, Error> {
+    parse_symbols(binary, offset, number_of_symbols, SYMBOL_EX_SIZE, |slice| {
+        let record = try_pod_read_unaligned::<CoffSymbolEx>(slice).ok()?;
+        Some(Symbol {
+            index: 0,
+            name: record.name,
+            value: record.value,
+            section_number: record.section_number,
+            symbol_type: record.symbol_type,
+            storage_class: record.storage_class,
+            number_of_aux_symbols: record.number_of_aux_symbols,
+            aux_symbols: Vec::new(),
+        })
+    })
+}
+
+fn parse_symbols<F>(binary: &[u8], offset: usize, number_of_symbols: u32, record_size: usize, read_record: F) -> Result<Vec<Symbol>, Error>
+where
+    F: Fn(&[u8]) -> Option<Symbol>,
+{
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut index = 0;
+
+    while index < number_of_symbols {
+        let record_offset = offset + index as usize * record_size;
+        let slice = match binary.get(record_offset..record_offset+record_size) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        let mut symbol = match read_record(slice) {
+            Some(symbol) => symbol,
+            None => {
+                return Err(Error::BadSymbol);
+            }
+        };
+
+        let aux_count = symbol.number_of_aux_symbols as u32;
+        if index + aux_count >= number_of_symbols {
+            return Err(Error::BadSymbol);
+        }
+
+        let aux_start = record_offset + record_size;
+        let aux_end = aux_start + aux_count as usize * record_size;
+        let aux_records = match binary.get(aux_start..aux_end) {
+            Some(slice) => slice,
+            None => {
+                return Err(Error::OffsetOutOfRange);
+            }
+        };
+
+        symbol.index = index;
+        symbol.aux_symbols = parse_aux_symbols(&symbol, aux_records, record_size)?;
+
+        symbols.push(symbol);
+        index += 1 + aux_count;
+    }
+
+    Ok(symbols)
+}
+
+/// Decode the auxiliary records following `symbol`.
+/// `record_size` is the stride between records; only the
+/// first 18 bytes of each record are meaningful.
+fn parse_aux_symbols(symbol: &Symbol, records: &[u8], record_size: usize) -> Result<Vec<AuxSymbol>, Error> {
+    let mut aux_symbols: Vec<AuxSymbol> = Vec::new();
+
+    if records.is_empty() {
+        return Ok(aux_symbols);
+    }
+
+    let storage_class = symbol.get_storage_class();
+
+    // File names are spread across every auxiliary record
+    if storage_class == Some(StorageClass::File) {
+        let mut name: Vec<u8> = Vec::new();
+        for record in records.chunks(record_size) {
+            name.extend_from_slice(&record[..SYMBOL_SIZE]);
+        }
+        let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        name.truncate(end);
+
+        return match String::from_utf8(name) {
+            Ok(name) => {
+                aux_symbols.push(AuxSymbol::File(name));
+                Ok(aux_symbols)
+            }
+            Err(e) => Err(Error::BadString(e)),
+        };
+    }
+
+    for record in records.chunks(record_size) {
+        let record = &record[..SYMBOL_SIZE];
+        let aux = match storage_class {
+            Some(StorageClass::External) if symbol.is_function_definition() => {
+                AuxSymbol::FunctionDefinition(read_aux(record)?)
+            }
+            Some(StorageClass::Function) => {
+                AuxSymbol::BeginEndFunction(read_aux(record)?)
+            }
+            Some(StorageClass::WeakExternal) => {
+                AuxSymbol::WeakExternal(read_aux(record)?)
+            }
+            Some(StorageClass::External) if symbol.section_number == IMAGE_SYM_UNDEFINED && symbol.value == 0 => {
+                AuxSymbol::WeakExternal(read_aux(record)?)
+            }
+            Some(StorageClass::Static) if symbol.value == 0 && symbol.section_number > 0 => {
+                let mut definition: AuxSectionDefinition = read_aux(record)?;
+                // The high bits of the associated section are padding outside big object files
+                if record_size == SYMBOL_SIZE {
+                    definition.high_number = 0;
+                }
+                AuxSymbol::SectionDefinition(definition)
+            }
+            Some(StorageClass::ClrToken) => {
+                AuxSymbol::ClrToken(read_aux(record)?)
+            }
+            _ => {
+                let mut raw = [0u8; SYMBOL_SIZE];
+                raw.copy_from_slice(record);
+                AuxSymbol::Unknown(raw)
+            }
+        };
+        aux_symbols.push(aux);
+    }
+
+    Ok(aux_symbols)
+}
+
+fn read_aux<T: Pod>(record: &[u8]) -> Result<T, Error> {
+    match try_pod_read_unaligned::<T>(record) {
+        Ok(aux) => Ok(aux),
+        Err(_) => Err(Error::BadSymbol),
+    }
+}
+
+/// The symbol record is not yet assigned a section.
+/// A value of zero indicates that a reference to an external symbol is defined elsewhere.
+/// A value of non-zero is a common symbol with a size that is specified by the value.
+pub const IMAGE_SYM_UNDEFINED: i32 = 0;
+/// The symbol has an absolute (non-relocatable) value and is not an address.
+pub const IMAGE_SYM_ABSOLUTE: i32 = -1;
+/// The symbol provides general type or debugging information but does not correspond to a section.
+pub const IMAGE_SYM_DEBUG: i32 = -2;
+
+/// COFF Symbol Table record (Object and Image)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct CoffSymbol {
+    /// The name of the symbol.
+    /// If the name is longer than 8 bytes, the first 4 bytes are zero
+    /// and the last 4 bytes are an offset into the string table.
+    pub name: [u8; 8],
+    /// The value that is associated with the symbol.
+    /// The interpretation of this field depends on `section_number` and `storage_class`.
+    /// A typical meaning is the relocatable address.
+    pub value: u32,
+    /// The signed integer that identifies the section, using a one-based index into the section table.
+    /// Some values have special meaning.
+    pub section_number: i16,
+    /// A number that represents type.
+    /// Microsoft tools set this field to 0x20 (function) or 0x0 (not a function).
+    pub symbol_type: u16,
+    /// An enumerated value that represents storage class.
+    pub storage_class: u8,
+    /// The number of auxiliary symbol table entries that follow this record.
+    pub number_of_aux_symbols: u8
+}
+
+/// Big object COFF Symbol Table record (Object Only)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct CoffSymbolEx {
+    /// The name of the symbol. See `CoffSymbol::name`.
+    pub name: [u8; 8],
+    /// The value that is associated with the symbol.
+    pub value: u32,
+    /// The signed integer that identifies the section, using a one-based index into the section table.
+    /// Big object files widen this to 32 bits to allow more than 65279 sections.
+    pub section_number: i32,
+    /// A number that represents type.
+    pub symbol_type: u16,
+    /// An enumerated value that represents storage class.
+    pub storage_class: u8,
+    /// The number of auxiliary symbol table entries that follow this record.
+    pub number_of_aux_symbols: u8
+}
+
+/// A symbol table record together with its decoded auxiliary records
+#[derive(Clone)]
+pub struct Symbol {
+    /// Index of this symbol in the symbol table.
+    /// Auxiliary records count towards the index,
+    /// so this is the value relocations refer to.
+    pub index: u32,
+    /// The name of the symbol. See `CoffSymbol::name`.
+    pub name: [u8; 8],
+    /// The value that is associated with the symbol.
+    pub value: u32,
+    /// The signed integer that identifies the section, using a one-based index into the section table.
+    /// Widened to 32 bits so both regular and big object symbols fit.
+    pub section_number: i32,
+    /// A number that represents type.
+    pub symbol_type: u16,
+    /// An enumerated value that represents storage class.
+    pub storage_class: u8,
+    /// The number of auxiliary symbol table entries that followed this record.
+    pub number_of_aux_symbols: u8,
+    /// Decoded auxiliary records.
+    /// File names that span several records are decoded into a single `AuxSymbol::File`.
+    pub aux_symbols: Vec<AuxSymbol>
+}
+
+/// A symbol paired with the string table its long name refers to,
+/// so that it is printed with its resolved name
+pub struct ResolvedSymbol<'a> {
+    /// The symbol.
+    pub symbol: &'a Symbol,
+    /// The COFF string table, if the file has one.
+    pub string_table: Option<&'a StringTable>
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.with_string_table(None))
+    }
+}
+
+impl fmt::Display for ResolvedSymbol<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = self.symbol;
+        writeln!(f, "Symbol")?;
+        writeln!(f, "------")?;
+        writeln!(f, "Index:                   {}", symbol.index)?;
+        match symbol.get_name(self.string_table) {
+            Some(name) => writeln!(f, "Name:                    {:?}", name)?,
+            None => writeln!(f, "Name:                    <string table {:#x}>", symbol.get_string_table_offset().unwrap_or(0))?,
+        }
+        writeln!(f, "Value:                   {:#010x}", symbol.value)?;
+        writeln!(f, "Section Number:          {}", symbol.section_number)?;
+        writeln!(f, "Type:                    {:#06x}", symbol.symbol_type)?;
+        match symbol.get_storage_class() {
+            Some(storage_class) => writeln!(f, "Storage Class:           {:?}", storage_class)?,
+            None => writeln!(f, "Storage Class:           {:#04x}", symbol.storage_class)?,
+        }
+        writeln!(f, "Number of Aux Symbols:   {}", symbol.number_of_aux_symbols)?;
+
+        for aux in symbol.aux_symbols.iter() {
+            writeln!(f, "Aux Symbol:              {}", aux)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Symbol {
+    /// Get the name of a symbol as a string, with trailing nulls removed.
+    /// Long names are looked up in `string_table`; if no string table
+    /// is given, `None` is returned for them.
+    pub fn get_name(&self, string_table: Option<&StringTable>) -> Option<String> {
+        if let Some(offset) = self.get_string_table_offset() {
+            return string_table?.get_string(offset);
+        }
+
+        let end = self.name.iter().position(|&c| c == 0).unwrap_or(self.name.len());
+        String::from_utf8(self.name[..end].to_vec()).ok()
+    }
+
+    /// Pair the symbol with a string table, so that
+    /// it is printed with its long name resolved
+    pub fn with_string_table<'a>(&'a self, string_table: Option<&'a StringTable>) -> ResolvedSymbol<'a> {
+        ResolvedSymbol { symbol: self, string_table }
+    }
+
+    /// Returns the string table offset of the symbol name,
+    /// if the name is too long to be stored inline.
+    pub fn get_string_table_offset(&self) -> Option<u32> {
+        if self.name[..4] == [0; 4] {
+            Some(u32::from_le_bytes([self.name[4], self.name[5], self.name[6], self.name[7]]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the storage class as an enum
+    pub fn get_storage_class(&self) -> Option<StorageClass> {
+        StorageClass::from_u8(self.storage_class)
+    }
+
+    /// Returns the base (simple) type as an enum
+    pub fn get_base_type(&self) -> Option<BaseType> {
+        BaseType::from_u16(self.symbol_type & 0xf)
+    }
+
+    /// Returns the complex type as an enum
+    pub fn get_complex_type(&self) -> Option<ComplexType> {
+        ComplexType::from_u16((self.symbol_type >> 4) & 0xf)
+    }
+
+    /// Returns true if this symbol defines a function, i.e.
+    /// has an external storage class, a function type and
+    /// belongs to a section.
+    pub fn is_function_definition(&self) -> bool {
+        self.get_storage_class() == Some(StorageClass::External)
+            && self.get_complex_type() == Some(ComplexType::Function)
+            && self.section_number > 0
+    }
+}
+
+/// The storage class field of the symbol table indicates what kind of definition a symbol represents.
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum StorageClass {
+    /// A special symbol that represents the end of function, for debugging purposes.
+    EndOfFunction = 0xff,
+    /// No assigned storage class.
+    Null = 0,
+    /// The automatic (stack) variable. The value field specifies the stack frame offset.
+    Automatic = 1,
+    /// A value that Microsoft tools use for external symbols.
+    /// The value field indicates the size if the section number is `IMAGE_SYM_UNDEFINED` (0).
+    /// If the section number is not zero, then the value field specifies the offset within the section.
+    External = 2,
+    /// The offset of the symbol within the section.
+    /// If the value field is zero, then the symbol represents a section name.
+    Static = 3,
+    /// A register variable. The value field specifies the register number.
+    Register = 4,
+    /// A symbol that is defined externally.
+    ExternalDef = 5,
+    /// A code label that is defined within the module.
+    /// The value field specifies the offset of the symbol within the section.
+    Label = 6,
+    /// A reference to a code label that is not defined.
+    UndefinedLabel = 7,
+    /// The structure member. The value field specifies the n th member.
+    MemberOfStruct = 8,
+    /// A formal argument (parameter) of a function. The value field specifies the n th argument.
+    Argument = 9,
+    /// The structure tag-name entry.
+    StructTag = 10,
+    /// A union member. The value field specifies the n th member.
+    MemberOfUnion = 11,
+    /// The Union tag-name entry.
+    UnionTag = 12,
+    /// A Typedef entry.
+    TypeDefinition = 13,
+    /// A static data declaration.
+    UndefinedStatic = 14,
+    /// An enumerated type tagname entry.
+    EnumTag = 15,
+    /// A member of an enumeration. The value field specifies the n th member.
+    MemberOfEnum = 16,
+    /// A register parameter.
+    RegisterParam = 17,
+    /// A bit-field reference. The value field specifies the n th bit in the bit field.
+    BitField = 18,
+    /// A .bb (beginning of block) or .eb (end of block) record.
+    /// The value field is the relocatable address of the code location.
+    Block = 100,
+    /// A value that Microsoft tools use for symbol records that define the extent of a function:
+    /// begin function (.bf), end function (.ef), and lines in function (.lf).
+    /// For .lf records, the value field gives the number of source lines in the function.
+    /// For .ef records, the value field gives the size of the function code.
+    Function = 101,
+    /// An end-of-structure entry.
+    EndOfStruct = 102,
+    /// A value that Microsoft tools, as well as traditional COFF format, use for the source-file symbol record.
+    /// The symbol is followed by auxiliary records that name the file.
+    File = 103,
+    /// A definition of a section (Microsoft tools use `Static` storage class instead).
+    Section = 104,
+    /// A weak external.
+    WeakExternal = 105,
+    /// A CLR token symbol. The name is an ASCII string that consists of the hexadecimal value of the token.
+    ClrToken = 107,
+}
+
+/// The least significant byte of the symbol type specifies the simple (base) data type.
+#[derive(FromPrimitive, Debug, PartialEq)]
+#[repr(u16)]
+pub enum BaseType {
+    /// No type information or unknown base type. Microsoft tools use this setting.
+    Null = 0,
+    /// No valid type; used with void pointers and functions.
+    Void = 1,
+    /// A character (signed byte).
+    Char = 2,
+    /// A 2-byte signed integer.
+    Short = 3,
+    /// A natural integer type (normally 4 bytes in Windows).
+    Int = 4,
+    /// A 4-byte signed integer.
+    Long = 5,
+    /// A 4-byte floating-point number.
+    Float = 6,
+    /// An 8-byte floating-point number.
+    Double = 7,
+    /// A structure.
+    Struct = 8,
+    /// A union.
+    Union = 9,
+    /// An enumerated type.
+    Enum = 10,
+    /// A member of enumeration (a specific value).
+    MemberOfEnum = 11,
+    /// A byte; unsigned 1-byte integer.
+    Byte = 12,
+    /// A word; unsigned 2-byte integer.
+    Word = 13,
+    /// An unsigned integer of natural size (normally, 4 bytes).
+    UInt = 14,
+    /// An unsigned 4-byte integer.
+    DWord = 15,
+}
+
+/// The most significant byte of the symbol type specifies whether the symbol is a pointer to, function returning, or array of the base type.
+#[derive(FromPrimitive, Debug, PartialEq)]
+#[repr(u16)]
+pub enum ComplexType {
+    /// No derived type; the symbol is a simple scalar variable.
+    Null = 0,
+    /// The symbol is a pointer to base type.
+    Pointer = 1,
+    /// The symbol is a function that returns a base type.
+    Function = 2,
+    /// The symbol is an array of base type.
+    Array = 3,
+}
+
+/// Auxiliary record following a symbol, decoded according
+/// to the storage class of the symbol it belongs to
+#[derive(Clone)]
+pub enum AuxSymbol {
+    /// Function definition (external function symbols)
+    FunctionDefinition(AuxFunctionDefinition),
+    /// Line information for .bf and .ef symbols
+    BeginEndFunction(AuxBeginEndFunction),
+    /// Weak external resolution information
+    WeakExternal(AuxWeakExternal),
+    /// Source file name, joined from every auxiliary record of a .file symbol
+    File(String),
+    /// Section definition (static symbols naming a section)
+    SectionDefinition(AuxSectionDefinition),
+    /// CLR token definition
+    ClrToken(AuxClrToken),
+    /// An auxiliary record with no known format
+    Unknown([u8; SYMBOL_SIZE]),
+}
+
+impl fmt::Display for AuxSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuxSymbol::FunctionDefinition(aux) => {
+                let (tag_index, total_size, pointer_to_line_number, pointer_to_next_function) =
+                    (aux.tag_index, aux.total_size, aux.pointer_to_line_number, aux.pointer_to_next_function);
+                write!(f, "Function Definition (Tag Index: {}, Total Size: {}, Pointer to Line-numbers: {}, Next Function: {})",
+                    tag_index, total_size, pointer_to_line_number, pointer_to_next_function)
+            }
+            AuxSymbol::BeginEndFunction(aux) => {
+                let (line_number, pointer_to_next_function) = (aux.line_number, aux.pointer_to_next_function);
+                write!(f, "Begin/End Function (Line Number: {}, Next Function: {})", line_number, pointer_to_next_function)
+            }
+            AuxSymbol::WeakExternal(aux) => {
+                let tag_index = aux.tag_index;
+                match aux.get_characteristics() {
+                    Some(characteristics) => write!(f, "Weak External (Tag Index: {}, Characteristics: {:?})", tag_index, characteristics),
+                    None => write!(f, "Weak External (Tag Index: {}, Characteristics: {})", tag_index, { aux.characteristics }),
+                }
+            }
+            AuxSymbol::File(name) => write!(f, "File ({:?})", name),
+            AuxSymbol::SectionDefinition(aux) => {
+                let (length, number_of_relocations, number_of_line_numbers, checksum) =
+                    (aux.length, aux.number_of_relocations, aux.number_of_line_numbers, aux.checksum);
+                write!(f, "Section Definition (Length: {}, Relocations: {}, Line-numbers: {}, Checksum: {:#010x}, Number: {}, Selection: {})",
+                    length, number_of_relocations, number_of_line_numbers, checksum, aux.get_number(), aux.selection)
+            }
+            AuxSymbol::ClrToken(aux) => {
+                let symbol_table_index = aux.symbol_table_index;
+                write!(f, "CLR Token (Symbol Table Index: {})", symbol_table_index)
+            }
+            AuxSymbol::Unknown(raw) => write!(f, "Unknown ({:02x?})", raw),
+        }
+    }
+}
+
+/// Auxiliary Format 1: Function Definitions
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct AuxFunctionDefinition {
+    /// The symbol-table index of the corresponding .bf (begin function) symbol record.
+    pub tag_index: u32,
+    /// The size of the executable code for the function itself.
+    pub total_size: u32,
+    /// The file offset of the first COFF line-number entry for the function, or zero if none exists.
+    pub pointer_to_line_number: u32,
+    /// The symbol-table index of the record for the next function.
+    /// If the function is the last in the symbol table, this field is set to zero.
+    pub pointer_to_next_function: u32,
+    /// Unused.
+    pub unused: [u8; 2]
+}
+
+/// Auxiliary Format 2: .bf and .ef Symbols
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct AuxBeginEndFunction {
+    /// Unused.
+    pub unused1: [u8; 4],
+    /// The actual ordinal line number (1, 2, 3, and so on) within the source file, corresponding to the .bf or .ef record.
+    pub line_number: u16,
+    /// Unused.
+    pub unused2: [u8; 6],
+    /// The symbol-table index of the next .bf symbol record.
+    /// If the function is the last in the symbol table, this field is set to zero. It is not used for .ef records.
+    pub pointer_to_next_function: u32,
+    /// Unused.
+    pub unused3: [u8; 2]
+}
+
+/// Auxiliary Format 3: Weak Externals
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct AuxWeakExternal {
+    /// The symbol-table index of sym2, the symbol to be linked if sym1 is not found.
+    pub tag_index: u32,
+    /// How the linker should search for sym1.
+    pub characteristics: u32,
+    /// Unused.
+    pub unused: [u8; 10]
+}
+
+impl AuxWeakExternal {
+    /// Returns the search characteristics as an enum
+    pub fn get_characteristics(&self) -> Option<WeakExternalCharacteristics> {
+        WeakExternalCharacteristics::from_u32(self.characteristics)
+    }
+}
+
+/// How the linker should resolve a weak external symbol
+#[derive(FromPrimitive, Debug, PartialEq)]
+#[repr(u32)]
+pub enum WeakExternalCharacteristics {
+    /// No library search for sym1 should be performed.
+    SearchNoLibrary = 1,
+    /// A library search for sym1 should be performed.
+    SearchLibrary = 2,
+    /// sym1 is an alias for sym2.
+    SearchAlias = 3,
+    /// sym1 is an anti-dependency of sym2.
+    AntiDependency = 4,
+}
+
+/// Auxiliary Format 5: Section Definitions
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct AuxSectionDefinition {
+    /// The size of section data; the same as `size_of_raw_data` in the section header.
+    pub length: u32,
+    /// The number of relocation entries for the section.
+    pub number_of_relocations: u16,
+    /// The number of line-number entries for the section.
+    pub number_of_line_numbers: u16,
+    /// The checksum for communal data.
+    /// It is applicable if the `IMAGE_SCN_LNK_COMDAT` flag is set in the section header.
+    pub checksum: u32,
+    /// One-based index into the section table for the associated section.
+    /// This is used when the COMDAT selection setting is 5.
+    pub number: u16,
+    /// The COMDAT selection number. This is applicable if the section is a COMDAT section.
+    pub selection: u8,
+    /// Unused.
+    pub unused: u8,
+    /// High 16 bits of the associated section index (big object files only).
+    /// Parsed as zero for regular object files, where these bytes are unused.
+    pub high_number: u16
+}
+
+impl AuxSectionDefinition {
+    /// Returns the full one-based associated section index
+    pub fn get_number(&self) -> u32 {
+        (self.high_number as u32) << 16 | self.number as u32
+    }
+
+    /// Returns the COMDAT selection as an enum
+    pub fn get_selection(&self) -> Option<ComdatSelection> {
+        ComdatSelection::from_u8(self.selection)
+    }
+}
+
+/// Auxiliary Format 6: CLR Token Definition (Object Only)
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C, packed(2))]
+pub struct AuxClrToken {
+    /// Must be `IMAGE_AUX_SYMBOL_TYPE_TOKEN_DEF` (1).
+    pub aux_type: u8,
+    /// Reserved, must be zero.
+    pub reserved1: u8,
+    /// The symbol index of the COFF symbol to which this CLR token definition refers.
+    pub symbol_table_index: u32,
+    /// Reserved, must be zero.
+    pub reserved2: [u8; 12]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::bytes_of;
+
+    fn record(name: &[u8], value: u32, section_number: i16, storage_class: StorageClass, number_of_aux_symbols: u8) -> Vec<u8> {
+        let mut symbol = CoffSymbol {
+            value,
+            section_number,
+            storage_class: storage_class as u8,
+            number_of_aux_symbols,
+            ..Default::default()
+        };
+        symbol.name[..name.len()].copy_from_slice(name);
+        bytes_of(&symbol).to_vec()
+    }
+
+    fn section_definition(number: u16, high_number: u16) -> Vec<u8> {
+        let definition = AuxSectionDefinition { length: 16, number, selection: 5, high_number, ..Default::default() };
+        bytes_of(&definition).to_vec()
+    }
+
+    #[test]
+    fn auxiliary_records_are_stepped_over() {
+        let mut binary = record(b".file", 0, IMAGE_SYM_DEBUG as i16, StorageClass::File, 2);
+        binary.extend_from_slice(b"a_rather_long_file");
+        binary.extend_from_slice(b"_name.c\0\0\0\0\0\0\0\0\0\0\0");
+        binary.extend(record(b".text", 0, 1, StorageClass::Static, 1));
+        binary.extend(section_definition(2, 0xffff));
+        binary.extend(record(b"main", 0, 1, StorageClass::External, 0));
+
+        let symbols = parse_symbol_table(&binary, 0, 6).unwrap();
+        assert_eq!(symbols.iter().map(|symbol| symbol.index).collect::<Vec<u32>>(), [0, 3, 5]);
+        assert!(matches!(&symbols[0].aux_symbols[..], [AuxSymbol::File(name)] if name == "a_rather_long_file_name.c"));
+        // The high bytes of the associated section are padding in regular objects
+        assert!(matches!(symbols[1].aux_symbols[..], [AuxSymbol::SectionDefinition(definition)] if definition.get_number() == 2));
+        assert!(symbols[2].aux_symbols.is_empty());
+    }
+
+    #[test]
+    fn big_object_section_numbers_are_32_bits() {
+        let symbol = CoffSymbolEx {
+            name: *b".text\0\0\0",
+            section_number: 0x10001,
+            storage_class: StorageClass::Static as u8,
+            number_of_aux_symbols: 1,
+            ..Default::default()
+        };
+        let mut binary = bytes_of(&symbol).to_vec();
+        binary.extend(section_definition(2, 1));
+        binary.extend_from_slice(&[0, 0]);
+
+        let symbols = parse_symbol_table_ex(&binary, 0, 2).unwrap();
+        assert_eq!(symbols[0].section_number, 0x10001);
+        assert!(matches!(symbols[0].aux_symbols[..], [AuxSymbol::SectionDefinition(definition)] if definition.get_number() == 0x10002));
+    }
+
+    #[test]
+    fn auxiliary_records_past_the_end() {
+        let binary = record(b".text", 0, 1, StorageClass::Static, 1);
+        assert!(matches!(parse_symbol_table(&binary, 0, 1), Err(Error::BadSymbol)));
+    }
+}
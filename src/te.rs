@@ -0,0 +1,124 @@
+use bytemuck::{Pod, Zeroable, checked::try_pod_read_unaligned};
+use num_traits::FromPrimitive;
+use core::fmt;
+use crate::{coff::MachineTypes, optional::{DataDirectory, Subsystem}, prelude::*, section::{SectionHeader, parse_section_table}, Error};
+
+/// Signature at the start of a TE image ("VZ")
+pub const TE_SIGNATURE: [u8; 2] = *b"VZ";
+
+/// Representation of a Terse Executable (TE) image,
+/// the stripped-down PE variant used by UEFI PI firmware
+pub struct TerseExecutable {
+    /// TE image header
+    pub header: TeHeader,
+    /// Table containing a list of section headers
+    pub section_table: Vec<SectionHeader>,
+}
+
+/// Parse a Terse Executable from a given byte array
+pub fn parse_terse_executable(binary: &[u8]) -> Result<TerseExecutable, Error> {
+    let header_size = size_of::<TeHeader>();
+    let slice = match binary.get(0..header_size) {
+        Some(slice) => slice,
+        None => {
+            return Err(Error::OffsetOutOfRange);
+        }
+    };
+
+    if slice[0..2] != TE_SIGNATURE {
+        return Err(Error::MissingPeHeader);
+    }
+
+    let header = match try_pod_read_unaligned::<TeHeader>(slice) {
+        Ok(header) => header,
+        Err(_) => {
+            return Err(Error::MissingCoffHeader);
+        }
+    };
+
+    let section_table = parse_section_table(binary, header_size, header.number_of_sections);
+
+    Ok(TerseExecutable { header, section_table })
+}
+
+/// TE Image Header
+/// Replaces the MS-DOS stub, PE signature, COFF header and optional header.
+#[derive(Copy, Clone, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct TeHeader {
+    /// Must be `TE_SIGNATURE` ("VZ").
+    pub signature: u16,
+    /// The number that identifies the type of target machine, from the original COFF header.
+    pub machine: u16,
+    /// The number of sections, from the original COFF header.
+    pub number_of_sections: u8,
+    /// The subsystem, from the original optional header.
+    pub subsystem: u8,
+    /// The number of bytes removed from the start of the original PE image.
+    /// Addresses in the image are still relative to the original layout.
+    pub stripped_size: u16,
+    /// The address of the entry point, from the original optional header.
+    pub address_of_entry_point: u32,
+    /// The base of code, from the original optional header.
+    pub base_of_code: u32,
+    /// The image base, from the original optional header.
+    pub image_base: u64,
+    /// The base relocation table address and size.
+    pub base_relocation_table: DataDirectory,
+    /// The debug data starting address and size.
+    pub debug: DataDirectory
+}
+
+impl fmt::Display for TeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "TE Header")?;
+        writeln!(f, "---------")?;
+        match self.get_machine_type() {
+            Some(machine_type) => writeln!(f, "Machine Type:            {:?}", machine_type)?,
+            None => writeln!(f, "Machine Type:            {:#06x}", self.machine)?,
+        }
+        writeln!(f, "Number of Sections:      {}", self.number_of_sections)?;
+        match self.get_subsystem() {
+            Some(subsystem) => writeln!(f, "Subsystem:               {:?}", subsystem)?,
+            None => writeln!(f, "Subsystem:               {}", self.subsystem)?,
+        }
+        writeln!(f, "Stripped Size:           {}", self.stripped_size)?;
+        writeln!(f, "Address of Entry Point:  {:#010x}", self.address_of_entry_point)?;
+        writeln!(f, "Base of Code:            {:#010x}", self.base_of_code)?;
+        writeln!(f, "Image Base:              {:#018x}", self.image_base)?;
+        writeln!(f, "Base Relocation Table:   {:#010x} ({})", self.base_relocation_table.virtual_address, self.base_relocation_table.size)?;
+        writeln!(f, "Debug:                   {:#010x} ({})", self.debug.virtual_address, self.debug.size)?;
+
+        Ok(())
+    }
+}
+
+impl TeHeader {
+    /// Returns the machine type as an enum
+    pub fn get_machine_type(&self) -> Option<MachineTypes> {
+        MachineTypes::from_u16(self.machine)
+    }
+
+    /// Returns the subsystem as an enum
+    pub fn get_subsystem(&self) -> Option<Subsystem> {
+        Subsystem::from_u8(self.subsystem)
+    }
+
+    /// Convert a file offset or RVA from the original PE layout
+    /// to an offset in the TE image
+    pub fn adjust_offset(&self, original: u32) -> Option<u32> {
+        original.checked_add(size_of::<TeHeader>() as u32)?.checked_sub(self.stripped_size as u32)
+    }
+}
+
+impl fmt::Display for TerseExecutable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.header)?;
+
+        for section in self.section_table.iter() {
+            writeln!(f, "{}", section)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,16 @@
 use std::path::Path;
 use datatest_stable::Result;
 use pe_parser::pe::parse_portable_executable;
+use pe_parser::file::{ParsedFile, parse_file};
 use std::fs;
 
 fn gauntlet(path: &Path) -> Result<()> {
     let binary = fs::read(path)?;
     let pe = parse_portable_executable(binary.as_slice())?;
 
+    // Format detection should agree that this is an image
+    assert!(matches!(parse_file(binary.as_slice())?, ParsedFile::Image(_)));
+
     // Binary passed initial parsing, now check if reserved fields are 0
 
     if let Some(optional) = pe.optional_header_32 {